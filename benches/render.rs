@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::io;
+use std::time::Duration;
+
+use rpomodoro::app::App;
+use rpomodoro::config::Config;
+use rpomodoro::render::screen::Screen;
+use rpomodoro::stats::Stats;
+use rpomodoro::theme::ColorDepth;
+
+// One frame as the main loop makes it: drawn into a Screen, then sent in
+// full (no previous frame) to a sink
+fn frames(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join("rpomodoro-bench");
+    let faces = [("digital", "block"), ("digital", "inverted"), ("analog", "block")];
+    for (width, height) in [(80, 24), (200, 50)] {
+        for (face, style) in faces {
+            let config = Config { clock_face: face.to_string(), digit_style: style.to_string(), ..Config::default() };
+            let mut app = App::with_config(config, dir.join("config.json"), Stats::default(), dir.join("stats.json"), width, height);
+            app.engine.time_remaining = Duration::from_secs(12 * 60 + 34);
+
+            c.bench_function(&format!("frame {} {} {}x{}", face, style, width, height), |b| {
+                b.iter(|| {
+                    let mut screen = Screen::new(width, height);
+                    app.draw_frame(&mut screen);
+                    screen.flush(&mut io::sink(), None, ColorDepth::TrueColor).unwrap();
+                })
+            });
+        }
+    }
+}
+
+criterion_group!(benches, frames);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::time::Duration;
+
+use rpomodoro::app::{App, PomodoroState};
+use rpomodoro::config::Config;
+use rpomodoro::stats::Stats;
+
+// Whatever a damaged config.json holds, loading it either fails or gives a
+// config the timer can run on
+fuzz_target!(|data: &[u8]| {
+    let Ok(json) = std::str::from_utf8(data) else {
+        return;
+    };
+    let _ = serde_json::from_str::<Stats>(json);
+    let Ok(config) = Config::from_json(json) else {
+        return;
+    };
+    assert!(config.cycles_before_long >= 1);
+    for state in [PomodoroState::Work, PomodoroState::ShortBreak, PomodoroState::LongBreak] {
+        assert!(!config.duration_for(state).is_zero());
+    }
+
+    let dir = std::env::temp_dir().join("rpomodoro-fuzz");
+    let mut app = App::with_config(config, dir.join("config.json"), Stats::default(), dir.join("stats.json"), 80, 24);
+    app.toggle_pause();
+    for _ in 0..10 {
+        let _ = app.tick(Duration::from_secs(3600));
+    }
+});
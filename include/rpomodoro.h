@@ -0,0 +1,64 @@
+/* C interface to the rpomodoro timer engine (cargo feature "ffi").
+ *
+ * Build the library with
+ *   cargo rustc --lib --release --features ffi --crate-type cdylib
+ * and link against target/release/librpomodoro.so (.dylib, .dll).
+ *
+ * The engine only moves by the time passed to rpomodoro_engine_tick, so
+ * the caller's own timer drives it. An engine is used from one thread at a
+ * time. */
+#ifndef RPOMODORO_H
+#define RPOMODORO_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RPOMODORO_ABI_VERSION 2
+
+typedef struct RpomodoroEngine RpomodoroEngine;
+
+enum {
+    RPOMODORO_WORK = 0,
+    RPOMODORO_SHORT_BREAK = 1,
+    RPOMODORO_LONG_BREAK = 2,
+};
+
+typedef struct {
+    uint32_t state;
+    bool paused;
+    uint64_t remaining_ms;
+    uint32_t cycle;
+    uint32_t cycles_before_long;
+    uint32_t extended_minutes;
+    /* Counting up after "stopwatch"; remaining_ms then stays as it was */
+    bool stopwatch;
+    /* Time the session has run, counted down or up, overtime included */
+    uint64_t counted_ms;
+} RpomodoroStatus;
+
+/* Compare with RPOMODORO_ABI_VERSION before using the rest */
+uint32_t rpomodoro_abi_version(void);
+
+/* Paused at the start of the first work session. Lengths are minutes; 0
+ * takes the default (25, 5, 15, 4). */
+RpomodoroEngine *rpomodoro_engine_new(uint32_t work, uint32_t short_break, uint32_t long_break, uint32_t cycles_before_long);
+void rpomodoro_engine_free(RpomodoroEngine *engine);
+
+/* 1 when a session ended (the next one starts paused), 0 otherwise */
+int rpomodoro_engine_tick(RpomodoroEngine *engine, uint64_t elapsed_ms);
+
+/* "toggle", "pause", "resume", "skip", "extend", "stopwatch" or "reset"; 0 or -1 */
+int rpomodoro_engine_command(RpomodoroEngine *engine, const char *command);
+
+/* 0, or -1 for a null pointer */
+int rpomodoro_engine_status(const RpomodoroEngine *engine, RpomodoroStatus *status);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
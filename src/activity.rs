@@ -0,0 +1,300 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::app::{App, PomodoroState};
+
+// A small diversion for breaks, in a panel over the dimmed clock: opened as
+// a break begins when "break_activity" names one, and closed when the break
+// ends or with esc. New ones go in BREAK_ACTIVITIES and new_activity.
+pub trait BreakActivity {
+    fn title(&self) -> String;
+    fn lines(&self) -> Vec<String>;
+    fn help(&self) -> &'static str;
+    // A key while it's open. False leaves the key to the main view, so the
+    // timer's own keys still work where the activity has no use for them.
+    fn key(&mut self, code: KeyCode) -> bool;
+}
+
+pub fn new_activity(name: &str, seed: u64) -> Option<Box<dyn BreakActivity>> {
+    match name {
+        "scramble" => Some(Box::new(Scramble::new(seed))),
+        "2048" => Some(Box::new(Tiles::new(seed))),
+        _ => None,
+    }
+}
+
+impl App {
+    // The activity for the session that just began; none for work
+    pub fn open_break_activity(&mut self, next: PomodoroState) {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos() as u64);
+        self.break_activity = match next {
+            PomodoroState::Work => None,
+            _ => new_activity(&self.config.break_activity, seed),
+        };
+    }
+
+    // Keys while an activity is open: esc closes it, the rest go to it
+    // first. False when neither wanted the key.
+    pub fn handle_activity_input(&mut self, key: KeyEvent) -> bool {
+        let Some(activity) = &mut self.break_activity else {
+            return false;
+        };
+        if key.code == KeyCode::Esc {
+            self.break_activity = None;
+            return true;
+        }
+        activity.key(key.code)
+    }
+}
+
+// xorshift64, plenty for shuffling letters and placing tiles
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+const WORDS: [&str; 24] = [
+    "balance", "breathe", "compass", "coffee", "garden", "harvest", "horizon", "journey", "lantern", "meadow", "orchard", "pebble", "puzzle", "quiet", "river",
+    "shadow", "silence", "stretch", "sunrise", "thunder", "velvet", "walnut", "whisper", "window",
+];
+
+// A word with its letters shuffled, to be typed back. Tab gives up on it.
+pub struct Scramble {
+    rng: Rng,
+    word: &'static str,
+    shuffled: String,
+    guess: String,
+    solved: u32,
+    // How the last guess or give-up went
+    said: String,
+}
+
+impl Scramble {
+    fn new(seed: u64) -> Self {
+        let mut scramble = Scramble { rng: Rng::new(seed), word: "", shuffled: String::new(), guess: String::new(), solved: 0, said: String::new() };
+        scramble.next_word();
+        scramble
+    }
+
+    fn next_word(&mut self) {
+        let previous = self.word;
+        while self.word == previous {
+            self.word = WORDS[self.rng.below(WORDS.len())];
+        }
+        let mut letters: Vec<char> = self.word.chars().collect();
+        while letters.iter().collect::<String>() == self.word {
+            for i in (1..letters.len()).rev() {
+                letters.swap(i, self.rng.below(i + 1));
+            }
+        }
+        self.shuffled = letters.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(" ");
+        self.guess.clear();
+    }
+}
+
+impl BreakActivity for Scramble {
+    fn title(&self) -> String {
+        format!("word scramble, {} solved", self.solved)
+    }
+
+    fn lines(&self) -> Vec<String> {
+        vec![self.shuffled.to_uppercase(), format!("> {}_", self.guess), self.said.clone()]
+    }
+
+    fn help(&self) -> &'static str {
+        "type the word, enter: check, tab: give up, esc: close"
+    }
+
+    fn key(&mut self, code: KeyCode) -> bool {
+        match code {
+            KeyCode::Char(c) if c.is_alphabetic() => self.guess.extend(c.to_lowercase()),
+            KeyCode::Backspace => {
+                self.guess.pop();
+            }
+            KeyCode::Enter if self.guess == self.word => {
+                self.solved += 1;
+                self.said = format!("yes, {}", self.word);
+                self.next_word();
+            }
+            KeyCode::Enter => {
+                self.said = format!("not {}", self.guess);
+                self.guess.clear();
+            }
+            KeyCode::Tab => {
+                self.said = format!("it was {}", self.word);
+                self.next_word();
+            }
+            _ => return false,
+        }
+        true
+    }
+}
+
+const SIDE: usize = 4;
+
+// 2048 on a 4x4 board: arrows or h/j/k/l slide every tile, equal ones that
+// meet merge, and a 2 (sometimes a 4) lands in a free cell after each move
+pub struct Tiles {
+    rng: Rng,
+    board: [[u32; SIDE]; SIDE],
+    score: u32,
+}
+
+impl Tiles {
+    fn new(seed: u64) -> Self {
+        let mut tiles = Tiles { rng: Rng::new(seed), board: [[0; SIDE]; SIDE], score: 0 };
+        tiles.spawn();
+        tiles.spawn();
+        tiles
+    }
+
+    fn spawn(&mut self) {
+        let free: Vec<(usize, usize)> = (0..SIDE * SIDE).map(|i| (i / SIDE, i % SIDE)).filter(|&(row, col)| self.board[row][col] == 0).collect();
+        if free.is_empty() {
+            return;
+        }
+        let (row, col) = free[self.rng.below(free.len())];
+        self.board[row][col] = if self.rng.below(10) == 0 { 4 } else { 2 };
+    }
+
+    // Slide toward `(drow, dcol)`; whether anything moved
+    fn slide(&mut self, drow: isize, dcol: isize) -> bool {
+        let before = self.board;
+        for line in 0..SIDE {
+            // Cells of this line, starting from the edge tiles slide toward
+            let cell = |i: usize| match (drow, dcol) {
+                (0, -1) => (line, i),
+                (0, _) => (line, SIDE - 1 - i),
+                (-1, _) => (i, line),
+                _ => (SIDE - 1 - i, line),
+            };
+            let tiles: Vec<u32> = (0..SIDE).map(cell).map(|(row, col)| self.board[row][col]).filter(|&tile| tile != 0).collect();
+            let mut merged = Vec::with_capacity(SIDE);
+            let mut i = 0;
+            while i < tiles.len() {
+                if i + 1 < tiles.len() && tiles[i] == tiles[i + 1] {
+                    merged.push(tiles[i] * 2);
+                    self.score += tiles[i] * 2;
+                    i += 2;
+                } else {
+                    merged.push(tiles[i]);
+                    i += 1;
+                }
+            }
+            for i in 0..SIDE {
+                let (row, col) = cell(i);
+                self.board[row][col] = merged.get(i).copied().unwrap_or(0);
+            }
+        }
+        self.board != before
+    }
+
+    fn stuck(&self) -> bool {
+        (0..SIDE).all(|row| {
+            (0..SIDE).all(|col| {
+                let tile = self.board[row][col];
+                tile != 0 && (col + 1 == SIDE || self.board[row][col + 1] != tile) && (row + 1 == SIDE || self.board[row + 1][col] != tile)
+            })
+        })
+    }
+}
+
+impl BreakActivity for Tiles {
+    fn title(&self) -> String {
+        format!("2048, score {}", self.score)
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .board
+            .iter()
+            .map(|row| row.iter().map(|&tile| if tile == 0 { format!("{:>5}", ".") } else { format!("{:>5}", tile) }).collect())
+            .collect();
+        if self.stuck() {
+            lines.push("no moves left, n: new game".to_string());
+        }
+        lines
+    }
+
+    fn help(&self) -> &'static str {
+        "arrows or h/j/k/l: slide, esc: close"
+    }
+
+    fn key(&mut self, code: KeyCode) -> bool {
+        let (drow, dcol) = match code {
+            KeyCode::Left | KeyCode::Char('h') => (0, -1),
+            KeyCode::Right | KeyCode::Char('l') => (0, 1),
+            KeyCode::Up | KeyCode::Char('k') => (-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => (1, 0),
+            KeyCode::Char('n') if self.stuck() => {
+                let seed = self.rng.0;
+                *self = Tiles::new(seed);
+                return true;
+            }
+            _ => return false,
+        };
+        if self.slide(drow, dcol) {
+            self.spawn();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn scramble_takes_the_word_back() {
+        let mut scramble = Scramble::new(7);
+        let word = scramble.word;
+        assert_ne!(scramble.shuffled.replace(' ', ""), word);
+        for c in word.chars() {
+            scramble.key(KeyCode::Char(c.to_ascii_uppercase()));
+        }
+        scramble.key(KeyCode::Enter);
+        assert_eq!((scramble.solved, scramble.said.as_str()), (1, format!("yes, {}", word).as_str()));
+        assert_ne!(scramble.word, word);
+        assert!(!scramble.key(KeyCode::Char(' ')), "space is left to the timer");
+    }
+
+    #[test]
+    fn tiles_slide_and_merge() {
+        let mut tiles = Tiles::new(1);
+        tiles.board = [[2, 2, 4, 0], [0, 0, 0, 0], [0, 4, 0, 4], [2, 0, 0, 0]];
+        assert!(tiles.slide(0, -1));
+        assert_eq!(tiles.board, [[4, 4, 0, 0], [0, 0, 0, 0], [8, 0, 0, 0], [2, 0, 0, 0]]);
+        assert_eq!(tiles.score, 12);
+        assert!(tiles.slide(1, 0));
+        assert_eq!(tiles.board, [[0, 0, 0, 0], [4, 0, 0, 0], [8, 0, 0, 0], [2, 4, 0, 0]]);
+        assert!(!tiles.slide(1, 0), "nothing left to move down");
+
+        tiles.board = [[2, 4, 2, 4], [4, 2, 4, 2], [2, 4, 2, 4], [4, 2, 4, 2]];
+        assert!(tiles.stuck());
+    }
+
+    #[test]
+    fn activities_last_as_long_as_the_break() {
+        let mut app = App::for_test(Config { break_activity: "2048".to_string(), ..Config::default() });
+        app.advance_state(true);
+        assert_eq!(app.break_activity.as_ref().map(|activity| activity.help()), Some("arrows or h/j/k/l: slide, esc: close"));
+        assert!(!app.handle_activity_input(KeyEvent::from(KeyCode::Char(' '))));
+        app.advance_state(true);
+        assert!(app.break_activity.is_none());
+
+        app.advance_state(true);
+        assert!(app.handle_activity_input(KeyEvent::from(KeyCode::Esc)));
+        assert!(app.break_activity.is_none());
+    }
+}
@@ -0,0 +1,78 @@
+use crossterm::style::Color;
+use std::time::Duration;
+
+use crate::app::{reminder_due, App};
+use crate::integrations::focus::WINDOW_TITLE;
+
+// Quiet cues for a terminal kept in view on another monitor, given at the
+// "ambient_milestones" of a work session and taken back when it ends:
+// "urgent" rings the bell, which most window managers turn into an urgency
+// hint, and marks the window title; "cursor" turns the cursor the theme's
+// urgent color (OSC 12); "profile" switches iTerm2 to "ambient_profile".
+impl App {
+    // The countdown went from `before` to `after` in a work session
+    pub fn ambient_cue(&mut self, before: Duration, after: Duration) {
+        if !self.config.ambient_milestones.iter().any(|&minutes| minutes > 0 && reminder_due(minutes, before, after)) {
+            return;
+        }
+        let sequence = match self.config.ambient_cue.as_str() {
+            "urgent" => {
+                self.ring_bell = true;
+                osc_title(&format!("● {}", WINDOW_TITLE))
+            }
+            "cursor" => match self.theme.urgent {
+                Color::Rgb { r, g, b } => format!("\x1b]12;#{:02x}{:02x}{:02x}\x07", r, g, b),
+                _ => return,
+            },
+            "profile" => format!("\x1b]1337;SetProfile={}\x07", self.config.ambient_profile),
+            _ => return,
+        };
+        self.terminal_output.push_str(&sequence);
+        self.ambient_shown = true;
+    }
+
+    // Back to how the terminal was, once the session is over or on exit
+    pub fn clear_ambient_cue(&mut self) {
+        if !std::mem::take(&mut self.ambient_shown) {
+            return;
+        }
+        let sequence = match self.config.ambient_cue.as_str() {
+            "urgent" => osc_title(WINDOW_TITLE),
+            "cursor" => "\x1b]112\x07".to_string(),
+            // iTerm2 can't say which profile was in use before
+            "profile" => "\x1b]1337;SetProfile=Default\x07".to_string(),
+            _ => return,
+        };
+        self.terminal_output.push_str(&sequence);
+    }
+}
+
+fn osc_title(title: &str) -> String {
+    format!("\x1b]2;{}\x07", title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::PomodoroState;
+    use crate::config::Config;
+
+    #[test]
+    fn cues_come_at_milestones_and_go_with_the_session() {
+        let config = Config { ambient_cue: "urgent".to_string(), ambient_milestones: vec![5], ..Config::default() };
+        let mut app = App::for_test(config);
+        app.toggle_pause();
+        app.tick(Duration::from_secs(19 * 60)).unwrap();
+        assert!(app.terminal_output.is_empty() && !app.ring_bell);
+
+        app.tick(Duration::from_secs(60)).unwrap();
+        assert!(std::mem::take(&mut app.ring_bell));
+        assert_eq!(std::mem::take(&mut app.terminal_output), "\x1b]2;● rpomodoro\x07");
+        app.tick(Duration::from_secs(4 * 60)).unwrap();
+        assert!(app.terminal_output.is_empty());
+
+        app.tick(Duration::from_secs(61)).unwrap();
+        assert_eq!(app.engine.state, PomodoroState::ShortBreak);
+        assert_eq!(app.terminal_output, "\x1b]2;rpomodoro\x07");
+    }
+}
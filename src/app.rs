@@ -0,0 +1,1500 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use crossterm::style::Color;
+use crossterm::terminal;
+use serde_json::{json, Value};
+
+use crate::activity::BreakActivity;
+use crate::config::{cycle_option, Config, Overrides, Preset};
+use crate::countdown::Countdown;
+use crate::crash;
+use crate::crypto::{is_encrypted_file, Passphrase};
+pub use crate::engine::PomodoroState;
+use crate::engine::{PomodoroEngine, Transition};
+use crate::error::{Error, Result};
+use crate::events::Command;
+use crate::escalation::Escalation;
+use crate::flow::{suggested_work_minutes, EXTEND_STEP};
+use crate::history::{self, summarize, HistoryEntry, Lap, StatsView};
+use crate::input::Keymap;
+use crate::integrations::focus::focus_terminal;
+use crate::integrations::health::Health;
+use crate::integrations::hooks::run_hook;
+use crate::integrations::notify::{notifier_for, Notifier};
+use crate::integrations::sound::{default_sound, play};
+use crate::integrations::speech::speak;
+use crate::integrations::webhook;
+use crate::live::Live;
+use crate::navigation::Navigation;
+use crate::locale::TimeLocale;
+use crate::render::overlay::FrameStats;
+use crate::session::Session;
+use crate::settings::ConfigView;
+use crate::stats::Stats;
+use crate::storage::{create_dir, expand_home, write_atomic, Dirs};
+use crate::tasks::{TaskPane, Tasks};
+use crate::theme::{Theme, ThemeEditor};
+
+pub struct App {
+    pub config: Config,
+    pub config_path: PathBuf,
+    // The config as read from disk, and the command-line settings applied
+    // over it for this run
+    pub file_config: Config,
+    pub overrides: Overrides,
+    pub stats: Stats,
+    pub stats_path: PathBuf,
+    pub history_path: PathBuf,
+    pub session_path: PathBuf,
+    // The timer moved since the session was last saved, and whether that
+    // included a transition
+    pub session_dirty: bool,
+    pub transitioned: bool,
+    pub last_autosave: Instant,
+    // Set when the config asks for encrypted data files, or some are still
+    // encrypted from before it was switched off; only written with the first
+    pub passphrase: Option<Passphrase>,
+    // Where the timer is in the work/break sequence
+    pub engine: PomodoroEngine,
+    pub last_tick: Instant,
+    // The same moment by the wall clock, which unlike Instant keeps going
+    // while the machine is suspended on Linux and macOS
+    pub last_wall_tick: SystemTime,
+    // A work session "idle_pause_minutes" paused, until the user is back
+    pub idle_paused: bool,
+    // When the timer last stopped running, for "dim_paused_seconds"
+    pub paused_since: Option<Instant>,
+    // A sleep gap the user is being asked whether to count ("on_sleep" is
+    // "ask")
+    pub sleep_gap: Option<Duration>,
+    // Last key press or command, for noticing the user is away
+    pub last_input: Instant,
+    // The current break ran on because the user was away
+    pub idle_extended: bool,
+    // When the current session first started running, for the history
+    pub session_started: Option<String>,
+    // The current session was carried over from before a restart
+    pub session_resumed: bool,
+    // Laps marked in the current session, and the label being typed for a
+    // new one
+    pub laps: Vec<Lap>,
+    pub lap_label: Option<String>,
+    // The command line while it's open, as typed after the ":"
+    pub palette: Option<String>,
+    // A one-off countdown running in place of the pomodoro timer, and its
+    // length while it's typed
+    pub countdown: Option<Countdown>,
+    pub countdown_input: Option<String>,
+    // The break's diversion, per "break_activity", while it's open
+    pub break_activity: Option<Box<dyn BreakActivity>>,
+    pub tasks: Tasks,
+    pub tasks_path: PathBuf,
+    // The task pane, toggled with a
+    pub task_pane: Option<TaskPane>,
+    pub theme: Theme,
+    // config.keybindings as key codes
+    pub keymap: Keymap,
+    // Open over the config screen, previewing its colors in `theme`
+    pub theme_editor: Option<ThemeEditor>,
+    // The terminal's background color, when known
+    pub background: Option<Color>,
+    pub locale: TimeLocale,
+    pub width: u16,
+    pub height: u16,
+    pub config_mode: bool,
+    pub config_view: ConfigView,
+    // Config mode paused a running session, to be resumed on leaving
+    pub resume_after_config: bool,
+    // The stats screen while it's open, toggled with t. Summarized from the
+    // history when opened.
+    pub stats_view: Option<StatsView>,
+    // The day highlighted in the stats chart, today when it opens
+    pub stats_cursor: usize,
+    // The half-typed gg shared by every screen's navigation
+    pub navigation: Navigation,
+    // The integrations screen, toggled with i
+    pub show_integrations: bool,
+    pub show_overlay: bool,
+    pub frame_stats: FrameStats,
+    pub toast: Option<(String, Instant)>,
+    // A longer work length accepted from a flow suggestion, and the day it
+    // applies to
+    pub work_override: Option<(String, u32)>,
+    // Last text written to the OBS badge file, and whether writing failed
+    pub last_badge: String,
+    pub badge_failed: bool,
+    // Published each frame for clients on other threads
+    pub live: Live,
+    // Last known state of each integration, also written from their threads
+    pub health: Health,
+    pub notifier: Box<dyn Notifier>,
+    // Every integration, hook and export is held off while set; the timer
+    // itself carries on
+    pub offline: bool,
+    // Set when the terminal bell should ring with the next frame
+    pub ring_bell: bool,
+    // Escape sequences to write with the next frame, and whether an ambient
+    // cue is showing
+    pub terminal_output: String,
+    pub ambient_shown: bool,
+    // Alerts building up after a session ended, until a key is pressed
+    pub escalation: Option<Escalation>,
+    // A break skip waiting on the "justify" or "cooldown" rule
+    pub skip_request: Option<SkipRequest>,
+    // Showing a timer that runs in another process (a daemon). Commands
+    // are queued for it instead of run here.
+    pub attached: bool,
+    pub outbox: Vec<Command>,
+    #[cfg(test)]
+    pub scratch: Option<ScratchDir>,
+}
+
+// A test app's directory, removed when the app is dropped
+#[cfg(test)]
+pub struct ScratchDir(PathBuf);
+
+#[cfg(test)]
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// Sends an attached App's queued commands to the timer it shows and reads
+// that timer's state back (daemon::attach)
+pub type Attachment = Box<dyn FnMut(&mut App) -> Result<()>>;
+
+pub enum SkipRequest {
+    // Reason typed so far
+    Justify(String),
+    // When the countdown started
+    Cooldown(Instant),
+}
+
+pub const SKIP_COOLDOWN: Duration = Duration::from_secs(10);
+// Shortest reason accepted under the "justify" rule
+pub const MIN_JUSTIFICATION: usize = 3;
+
+impl App {
+    pub fn new(dirs: &Dirs, overrides: Overrides) -> Result<Self> {
+        let mut dirs = dirs.clone();
+        create_dir(&dirs.config)?;
+        let config_path = dirs.config_file();
+
+        let file_config = Config::load(&config_path)?;
+        // Checked here rather than with the other flags, since custom
+        // themes come from the config
+        if let Some(theme) = overrides.theme.as_ref().filter(|theme| !file_config.theme_names().contains(&theme.as_str())) {
+            let names = file_config.theme_names().join(", ");
+            return Err(Error::Usage(format!("unknown theme {:?}, pick one of {}", theme, names)));
+        }
+        if let Some(profile) = overrides.profile.as_ref().filter(|profile| !file_config.profiles.contains_key(*profile)) {
+            let names = file_config.profile_names().join(", ");
+            return Err(Error::Usage(format!("unknown profile {:?}, pick one of {}", profile, names)));
+        }
+        if let Some(preset) = overrides.preset.as_ref().filter(|preset| file_config.preset(preset).is_none()) {
+            let names = file_config.preset_names().join(", ");
+            return Err(Error::Usage(format!("unknown preset {:?}, pick one of {}", preset, names)));
+        }
+        let mut config = file_config.clone();
+        overrides.apply(&mut config);
+        dirs.apply_config(&config.data_dir);
+
+        create_dir(&dirs.data)?;
+        let stats_path = dirs.data.join("stats.json");
+        // An encrypted file needs the passphrase to load even if encryption
+        // has since been switched off; it's then saved in plain JSON
+        let tasks_path = dirs.data.join("tasks.json");
+        let history_path = stats_path.with_file_name("history.jsonl");
+        let passphrase = if config.encrypt_data || is_encrypted_file(&stats_path) || is_encrypted_file(&tasks_path) || history::has_encrypted_lines(&history_path) {
+            Some(Passphrase::obtain(&stats_path)?)
+        } else {
+            None
+        };
+        let stats = Stats::load(&stats_path, passphrase.as_ref())?;
+        let tasks = Tasks::load(&tasks_path, passphrase.as_ref())?;
+
+        // A daemon has no terminal
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        let mut app = App::with_config(config, config_path, stats, stats_path, width, height);
+        app.passphrase = passphrase;
+        app.file_config = file_config;
+        app.overrides = overrides;
+        app.tasks = tasks;
+        create_dir(&dirs.state)?;
+        app.session_path = dirs.state.join("session.json");
+        if let Err(e) = app.push_weekly_summary() {
+            app.show_error(e);
+        }
+        if let Some(session) = Session::load(&app.session_path) {
+            if let Err(e) = app.restore_session(session) {
+                app.show_error(e);
+            }
+        }
+        // Offered once, then left to `rpomodoro migrate-config`
+        let offered = dirs.state.join("toml-migration-offered");
+        if dirs.has_legacy_config() && !offered.exists() {
+            app.toast = Some(("config.json can move to config.toml: run rpomodoro migrate-config".to_string(), Instant::now()));
+            write_atomic(&offered, "")?;
+        }
+        Ok(app)
+    }
+
+    pub fn with_config(config: Config, config_path: PathBuf, stats: Stats, stats_path: PathBuf, width: u16, height: u16) -> Self {
+        let theme = Theme::from_config(&config);
+        let keymap = Keymap::from_config(&config.keybindings);
+        let locale = TimeLocale::from_tag(&config.time_locale);
+        let notifier = notifier_for(&config.notifier);
+        let offline = config.offline;
+        let mut engine = PomodoroEngine::new(config.lengths());
+        engine.set_sequence(config.sequence());
+
+        App {
+            engine,
+            file_config: config.clone(),
+            config,
+            config_path,
+            overrides: Overrides::default(),
+            stats,
+            history_path: stats_path.with_file_name("history.jsonl"),
+            session_path: stats_path.with_file_name("session.json"),
+            tasks_path: stats_path.with_file_name("tasks.json"),
+            session_dirty: false,
+            transitioned: false,
+            last_autosave: Instant::now(),
+            stats_path,
+            passphrase: None,
+            last_tick: Instant::now(),
+            last_wall_tick: SystemTime::now(),
+            sleep_gap: None,
+            paused_since: None,
+            idle_paused: false,
+            last_input: Instant::now(),
+            idle_extended: false,
+            session_started: None,
+            session_resumed: false,
+            laps: Vec::new(),
+            lap_label: None,
+            palette: None,
+            countdown: None,
+            countdown_input: None,
+            break_activity: None,
+            tasks: Tasks::default(),
+            task_pane: None,
+            theme,
+            keymap,
+            theme_editor: None,
+            background: None,
+            locale,
+            width,
+            height,
+            config_mode: false,
+            config_view: ConfigView::default(),
+            resume_after_config: false,
+            stats_view: None,
+            stats_cursor: 0,
+            navigation: Navigation::default(),
+            show_integrations: false,
+            show_overlay: false,
+            frame_stats: FrameStats::new(),
+            toast: None,
+            skip_request: None,
+            work_override: None,
+            live: Live::default(),
+            health: Health::default(),
+            notifier,
+            offline,
+            ring_bell: false,
+            terminal_output: String::new(),
+            ambient_shown: false,
+            escalation: None,
+            attached: false,
+            outbox: Vec::new(),
+            last_badge: String::new(),
+            badge_failed: false,
+            #[cfg(test)]
+            scratch: None,
+        }
+    }
+
+    // App backed by a scratch directory of its own, removed with it, and
+    // an 80x24 screen
+    #[cfg(test)]
+    pub fn for_test(config: Config) -> Self {
+        static NEXT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let n = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("rpomodoro-test-{}-{}", std::process::id(), n));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut app = App::with_config(config, dir.join("config.json"), Stats::default(), dir.join("stats.json"), 80, 24);
+        app.notifier = Box::new(crate::integrations::notify::Silent);
+        app.scratch = Some(ScratchDir(dir));
+        app
+    }
+
+    // The configured theme adjusted to the background, with a warning when
+    // it will still be hard to read
+    pub fn apply_theme(&mut self) {
+        self.theme = Theme::from_config(&self.config).on_background(self.background);
+        if !self.theme.readable_on(self.background) {
+            let reason = format!("theme \"{}\" is hard to read on this background", self.config.theme);
+            self.show_error(Error::config(&self.config_path, reason));
+        }
+    }
+
+    pub fn save_config(&self) -> Result<()> {
+        let mut config = self.config.clone();
+        self.overrides.unapply(&mut config, &self.file_config);
+        config.save(&self.config_path)
+    }
+
+    // Non-fatal errors are shown in the statusline for a few seconds
+    pub fn show_error(&mut self, error: Error) {
+        crash::log(&error.to_string());
+        self.toast = Some((error.to_string(), Instant::now()));
+    }
+
+    pub fn active_toast(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < Duration::from_secs(5))
+            .map(|(message, _)| message.as_str())
+    }
+
+    // The passphrase to write with, if "encrypt_data" is on
+    pub fn encrypting(&self) -> Option<&Passphrase> {
+        self.passphrase.as_ref().filter(|_| self.config.encrypt_data)
+    }
+
+    pub fn save_stats(&self) -> Result<()> {
+        self.stats.save(&self.stats_path, self.encrypting())
+    }
+
+    pub fn save_tasks(&self) -> Result<()> {
+        self.tasks.save(&self.tasks_path, self.encrypting())
+    }
+
+    pub fn update(&mut self) -> Result<()> {
+        // Today's flow length lapses at midnight
+        if self.engine.lengths != self.lengths() {
+            self.engine.set_lengths(self.lengths());
+        }
+        if !self.engine.paused {
+            let (now, wall) = (Instant::now(), SystemTime::now());
+            let elapsed = now.duration_since(self.last_tick);
+            let gap = elapsed.max(wall.duration_since(self.last_wall_tick).unwrap_or_default());
+            (self.last_tick, self.last_wall_tick) = (now, wall);
+            if gap >= Duration::from_secs(self.config.sleep_gap_seconds as u64) && !self.attached {
+                self.slept(gap)?;
+            } else {
+                self.tick(elapsed)?;
+            }
+        }
+
+        // Alerts stop once the next session is running
+        if !self.engine.paused {
+            self.escalation = None;
+        }
+        self.paused_since = if self.engine.paused { self.paused_since.or(Some(Instant::now())) } else { None };
+        if let Some(escalation) = &self.escalation {
+            self.escalate(escalation.since.elapsed())?;
+        }
+
+        // A pending skip lapses once the break is over
+        if self.engine.state == PomodoroState::Work {
+            self.skip_request = None;
+        }
+        if let Some(SkipRequest::Cooldown(since)) = self.skip_request {
+            if since.elapsed() >= SKIP_COOLDOWN {
+                self.confirm_skip(None)?;
+            }
+        }
+        Ok(())
+    }
+
+    // The machine was suspended for `gap` while the timer ran
+    fn slept(&mut self, gap: Duration) -> Result<()> {
+        if self.config.on_sleep == "count" {
+            return self.tick(gap);
+        }
+        self.execute(Command::Pause);
+        let minutes = gap.as_secs().div_ceil(60);
+        if self.config.on_sleep == "ask" {
+            self.sleep_gap = Some(gap);
+        } else {
+            self.toast = Some((format!("paused after {} min asleep", minutes), Instant::now()));
+        }
+        Ok(())
+    }
+
+    // A work session about to start for a task at its "task_quotas" limit
+    // for today gets a warning, or with "quota_action" set to "refuse"
+    // doesn't start
+    fn quota_allows_start(&mut self) -> bool {
+        let Some(task) = self.tasks.active_task().map(|task| task.title.clone()) else {
+            return true;
+        };
+        let Some(&quota) = self.config.task_quotas.get(&task) else {
+            return true;
+        };
+        if self.engine.state != PomodoroState::Work || self.countdown.is_some() {
+            return true;
+        }
+        let today = Stats::today();
+        let done = match history::load(&self.history_path, self.passphrase.as_ref()) {
+            Ok(entries) => entries.iter().filter(|e| e.state == "work" && !e.skipped && e.task.as_deref() == Some(&task) && e.end.starts_with(&today)).count() as u32,
+            Err(e) => {
+                self.show_error(e);
+                return true;
+            }
+        };
+        if done < quota {
+            return true;
+        }
+        let refuse = self.config.quota_action == "refuse";
+        let outcome = if refuse { "pick another task to start" } else { "starting anyway" };
+        self.toast = Some((format!("{} is at its {} a day: {}", task, quota, outcome), Instant::now()));
+        !refuse
+    }
+
+    // The desktop has seen no input for `idle`. A work session pauses as
+    // of when that began; once there's input again it resumes, or waits to
+    // be resumed with "idle_return" set to "ask". A countdown runs on.
+    pub fn desktop_idle(&mut self, idle: Duration) {
+        let threshold = Duration::from_secs(self.config.idle_pause_minutes as u64 * 60);
+        if threshold.is_zero() {
+            return;
+        }
+        if self.idle_paused {
+            if idle < threshold {
+                self.idle_paused = false;
+                if self.config.idle_return == "ask" {
+                    self.toast = Some((format!("welcome back: {} resumes", self.config.keybindings.toggle), Instant::now()));
+                } else {
+                    self.execute(Command::Resume);
+                }
+            }
+            return;
+        }
+        if self.engine.state == PomodoroState::Work && self.countdown.is_none() && !self.engine.paused && idle >= threshold {
+            self.execute(Command::Pause);
+            // Given back from a stopwatch's count, or from any overtime
+            // first and then from the session
+            let mut away = idle;
+            if let Some(counted) = self.engine.stopwatch.as_mut() {
+                *counted = counted.saturating_sub(idle);
+                away = Duration::ZERO;
+            }
+            if let Some(over) = self.engine.overtime {
+                self.engine.overtime = over.checked_sub(idle);
+                away = idle.saturating_sub(over);
+            }
+            self.engine.time_remaining = (self.engine.time_remaining + away).min(self.session_length());
+            self.idle_paused = true;
+            self.toast = Some((format!("paused, away for {} min", idle.as_secs() / 60), Instant::now()));
+        }
+    }
+
+    // Answer to the sleep gap question: y counts the gap and carries on, n
+    // carries on without it, anything else leaves the timer paused
+    pub fn answer_sleep_gap(&mut self, count: Option<bool>) -> Result<()> {
+        let Some(gap) = self.sleep_gap.take() else {
+            return Ok(());
+        };
+        match count {
+            Some(true) => {
+                self.execute(Command::Resume);
+                self.tick(gap)
+            }
+            Some(false) => {
+                self.execute(Command::Resume);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Skip to the next session, unless the config puts a break skip behind
+    // a typed reason or a cooldown. A stopwatch session has no end to run
+    // out and one in overtime has run past it, so this is how they end, as
+    // finished ones.
+    pub fn request_skip(&mut self) {
+        if self.countdown.is_some() {
+            if let Err(e) = self.finish_countdown(false) {
+                self.show_error(e);
+            }
+            return;
+        }
+        if self.engine.stopwatch.is_some() {
+            self.advance_state(false);
+            return;
+        }
+        if self.engine.overtime.is_some() {
+            if let Err(e) = self.end_overtime() {
+                self.show_error(e);
+            }
+            return;
+        }
+        if self.engine.state == PomodoroState::Work {
+            self.advance_state(true);
+            return;
+        }
+        match self.config.break_skip.as_str() {
+            "justify" => self.skip_request = Some(SkipRequest::Justify(String::new())),
+            "cooldown" => self.skip_request = Some(SkipRequest::Cooldown(Instant::now())),
+            _ => self.advance_state(true),
+        }
+    }
+
+    // Skip the break a request was waiting on, and log it
+    pub fn confirm_skip(&mut self, reason: Option<String>) -> Result<()> {
+        self.skip_request = None;
+        self.advance_state(true);
+        self.stats.record_skipped_break(reason);
+        self.save_stats()
+    }
+
+    // Switch to the previous preset's durations. A session that hasn't
+    // started yet starts over at the new length.
+    pub fn switch_preset(&mut self) -> Result<()> {
+        if self.attached {
+            return Err(Error::Usage("presets can't be switched on an attached timer".to_string()));
+        }
+        if !self.config.switch_preset() {
+            let reason = format!("no preset named \"{}\"", self.config.previous_preset);
+            return Err(Error::config(&self.config_path, reason));
+        }
+        if self.session_started.is_none() {
+            self.engine.time_remaining = self.session_length();
+        }
+        self.apply_config();
+        self.session_dirty = true;
+        self.save_config()
+    }
+
+    // The config screen's profile row: on to the next or previous profile,
+    // with its theme previewed
+    pub fn cycle_profile(&mut self, forward: bool) {
+        let names = self.config.profile_names();
+        if names.is_empty() {
+            return;
+        }
+        let name = cycle_option(&names, &self.config.profile, forward);
+        self.config.switch_profile(&name);
+        self.apply_theme();
+    }
+
+    // Ask for a label for a lap marker in the session under way. The lap is
+    // recorded with the session in the history when it ends.
+    pub fn start_lap(&mut self) {
+        if self.attached {
+            self.show_error(Error::Usage("laps can't be marked on an attached timer".to_string()));
+        } else if self.session_started.is_some() {
+            self.lap_label = Some(String::new());
+        }
+    }
+
+    pub fn mark_lap(&mut self, label: Option<String>) {
+        let at = self.engine.counted().as_secs();
+        self.laps.push(Lap { at, label });
+        self.lap_label = None;
+    }
+
+    // How long the main loop may sleep before the screen would change: until
+    // the displayed second rolls over while running, otherwise a second so
+    // the wall clock and toasts stay current
+    pub fn redraw_timeout(&self) -> Duration {
+        // An attached timer is polled a few times a second
+        if self.attached {
+            return Duration::from_millis(250);
+        }
+        if self.engine.paused {
+            return Duration::from_secs(1);
+        }
+        // A breathing clock changes color between seconds
+        if self.engine.state != PomodoroState::Work && self.config.breathing_pace > 0 {
+            return Duration::from_millis(100);
+        }
+        let until_next_second = match self.engine.stopwatch.or(self.engine.overtime) {
+            Some(counted) => Duration::from_nanos(1_000_000_000 - counted.subsec_nanos() as u64),
+            None => Duration::from_nanos(self.engine.time_remaining.subsec_nanos() as u64),
+        };
+        (until_next_second + Duration::from_millis(1)).min(Duration::from_secs(1))
+    }
+
+    // Run the current session forward by `elapsed`, if it isn't paused
+    pub fn tick(&mut self, elapsed: Duration) -> Result<()> {
+        if self.engine.paused {
+            return Ok(());
+        }
+        if self.countdown.is_some() {
+            return match self.engine.tick(elapsed) {
+                Some(_) => self.finish_countdown(true),
+                None => Ok(()),
+            };
+        }
+        self.session_dirty = true;
+
+        let before = self.engine.time_remaining;
+        let was_over = self.engine.overtime.is_some();
+        self.engine.run_over = for_sessions(&self.config.overtime, self.engine.state);
+        while self.engine.state != PomodoroState::Work && elapsed > self.engine.time_remaining && self.is_idle() {
+            self.engine.extend(1);
+            self.idle_extended = true;
+        }
+        let Some(transition) = self.engine.tick(elapsed) else {
+            if self.config.ending_soon_minutes > 0 && self.engine.stopwatch.is_none() && reminder_due(self.config.ending_soon_minutes, before, self.engine.time_remaining) {
+                self.timer_event("ending_soon", None);
+            }
+            if !was_over && self.engine.overtime.is_some() {
+                self.timer_event("overtime", None);
+                self.alert_overtime()?;
+            }
+            match self.engine.state {
+                PomodoroState::Work => {
+                    self.ambient_cue(before, self.engine.time_remaining);
+                    self.warn(before, self.engine.time_remaining)?;
+                }
+                PomodoroState::LongBreak => self.remind(before, self.engine.time_remaining)?,
+                PomodoroState::ShortBreak => {}
+            }
+            return Ok(());
+        };
+        self.complete_session(transition)
+    }
+
+    // End a session that's running over, as one that ran to the end
+    pub fn end_overtime(&mut self) -> Result<()> {
+        let transition = self.engine.advance(false);
+        self.complete_session(transition)
+    }
+
+    // Record a session that ran to the end; only these count, skips don't
+    fn complete_session(&mut self, transition: Transition) -> Result<()> {
+        let finished = transition.finished;
+        if finished == PomodoroState::Work {
+            self.stats.record_completed(&self.config.profile);
+            if self.tasks.active.is_some() {
+                self.tasks.credit();
+                self.save_tasks()?;
+            }
+            if transition.extended_minutes > 0 {
+                self.stats.record_extension(transition.extended_minutes);
+            }
+        }
+        let idle_break = std::mem::take(&mut self.idle_extended);
+        if idle_break {
+            self.stats.record_idle_break((transition.counted.as_secs() / 60) as u32);
+        }
+        self.finish_session(transition);
+        if finished == PomodoroState::Work || idle_break {
+            self.save_stats()?;
+        }
+        // Silent unless routed
+        let done = self.stats.completed_today();
+        if finished == PomodoroState::Work && done == self.config.daily_goal {
+            self.alert("goal_reached", "Daily goal reached", &format!("{} pomodoros today", done), &[])?;
+        }
+        if finished != PomodoroState::Work && !self.offline && !self.config.focus_command.is_empty() {
+            self.health.track("focus command", focus_terminal(&self.config.focus_command))?;
+        }
+        Ok(())
+    }
+
+    // No key press or command for "break_idle_minutes"
+    fn is_idle(&self) -> bool {
+        self.config.break_idle_minutes > 0 && self.last_input.elapsed() >= Duration::from_secs(self.config.break_idle_minutes as u64 * 60)
+    }
+
+    // The "warning_minutes" mark of a work session, passed between two ticks
+    fn warn(&mut self, before: Duration, after: Duration) -> Result<()> {
+        let minutes = self.config.warning_minutes;
+        if minutes == 0 || !reminder_due(minutes, before, after) {
+            return Ok(());
+        }
+        let body = format!("{} minute{} left, time to wrap up", minutes, if minutes == 1 { "" } else { "s" });
+        self.alert("warning", "Work session", &body, &["notify", "sound"])
+    }
+
+    // Long break reminders whose mark was passed between two ticks
+    fn remind(&mut self, before: Duration, after: Duration) -> Result<()> {
+        let due: Vec<String> = self
+            .config
+            .long_break_reminders
+            .iter()
+            .filter(|(&minutes, _)| minutes > 0 && reminder_due(minutes, before, after))
+            .map(|(_, message)| message.clone())
+            .collect();
+        for message in due {
+            self.alert("reminder", "Long break", &message, &["notify"])?;
+        }
+        Ok(())
+    }
+
+    // Opens on the active profile's stats, or all of them without one
+    pub fn open_stats(&mut self) -> Result<()> {
+        let profile = Some(self.config.profile.clone()).filter(|profile| !profile.is_empty());
+        self.show_stats(profile)?;
+        self.stats_cursor = self.stats_view.as_ref().map_or(0, |view| view.days.len().saturating_sub(1));
+        Ok(())
+    }
+
+    // The stats screen's p: on to the next profile, all of them after the
+    // last
+    pub fn cycle_stats_profile(&mut self) -> Result<()> {
+        let Some(view) = &self.stats_view else {
+            return Ok(());
+        };
+        let names = self.config.profile_names();
+        let next = match &view.profile {
+            None => names.first(),
+            Some(profile) => names.iter().skip_while(|name| *name != profile).nth(1),
+        };
+        self.show_stats(next.map(|name| name.to_string()))
+    }
+
+    fn show_stats(&mut self, profile: Option<String>) -> Result<()> {
+        let entries = history::load(&self.history_path, self.passphrase.as_ref())?;
+        self.stats_view = Some(summarize(&entries, chrono::Local::now().date_naive(), profile.as_deref()));
+        Ok(())
+    }
+
+    pub fn notify(&mut self, title: &str, body: &str) -> Result<()> {
+        self.health.track("notifier", self.notifier.notify(title, body))
+    }
+
+    pub fn toggle_pause(&mut self) {
+        let starting = self.engine.paused && self.session_started.is_none();
+        if starting && !self.quota_allows_start() {
+            return;
+        }
+        self.engine.toggle_pause();
+        let event = if self.engine.paused {
+            "pause"
+        } else if self.session_started.is_some() {
+            "resume"
+        } else {
+            "start"
+        };
+        if !self.engine.paused {
+            (self.last_tick, self.last_wall_tick) = (Instant::now(), SystemTime::now());
+            self.session_started.get_or_insert_with(history::now);
+            // Resumed some other way than answering, or before the user
+            // was noticed back
+            (self.sleep_gap, self.idle_paused) = (None, false);
+        }
+        self.timer_event(event, None);
+    }
+
+    // POST `event` with where the timer now is to "event_webhook_url", if
+    // set. `finished` is the session that just ended, for transitions.
+    pub fn timer_event(&mut self, event: &str, finished: Option<PomodoroState>) {
+        if self.offline || self.config.event_webhook_url.is_empty() {
+            return;
+        }
+        let sent = webhook::post_event(&self.config.event_webhook_url, &self.event_payload(event, finished));
+        if let Err(e) = self.health.track("event webhook", sent) {
+            self.show_error(e);
+        }
+    }
+
+    pub fn event_payload(&self, event: &str, finished: Option<PomodoroState>) -> Value {
+        json!({
+            "event": event,
+            "state": self.engine.state.name(),
+            "finished": finished.map(PomodoroState::name),
+            "paused": self.engine.paused,
+            "remaining_secs": self.engine.time_remaining.as_secs(),
+            "stopwatch_secs": self.engine.stopwatch.map(|counted| counted.as_secs()),
+            "overtime_secs": self.engine.overtime.map(|over| over.as_secs()),
+            "cycle": self.engine.cycle_count,
+            "cycles_before_long": self.engine.cycles_before_long(),
+            "timestamp": chrono::Local::now().to_rfc3339(),
+        })
+    }
+
+    // A running session is paused while the config screen is open and
+    // picks up again on leaving, unless pause_in_config is off
+    pub fn enter_config(&mut self) {
+        self.config_mode = true;
+        // Back on the page it was left on, without the search
+        self.config_view = ConfigView { page: self.config_view.page, ..ConfigView::default() };
+        if self.config.pause_in_config && !self.engine.paused {
+            self.execute(Command::Pause);
+            self.resume_after_config = true;
+        }
+    }
+
+    // Save the edited settings and return to the timer in the state it was
+    // left in
+    pub fn leave_config(&mut self) -> Result<()> {
+        self.config_mode = false;
+        if std::mem::take(&mut self.resume_after_config) && self.engine.paused {
+            self.execute(Command::Resume);
+        }
+        self.save_config()?;
+        self.apply_config();
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        if let Some(countdown) = self.countdown.take() {
+            self.engine = countdown.pomodoro;
+        }
+        self.engine.reset();
+        self.session_started = None;
+        self.session_resumed = false;
+        self.laps.clear();
+        self.idle_extended = false;
+        self.clear_ambient_cue();
+        self.break_activity = None;
+        (self.session_dirty, self.transitioned) = (true, true);
+    }
+
+    // The configured lengths, with today's accepted flow length for work
+    pub fn lengths(&self) -> Preset {
+        let mut lengths = self.config.lengths();
+        match &self.work_override {
+            Some((day, minutes)) if *day == Stats::today() => lengths.work_duration = *minutes,
+            _ => {}
+        }
+        lengths
+    }
+
+    // Full length of the current session, extensions included
+    pub fn session_length(&self) -> Duration {
+        self.engine.session_length()
+    }
+
+    pub fn extend_session(&mut self) {
+        self.engine.extend(EXTEND_STEP);
+        self.session_dirty = true;
+    }
+
+    // Count the big clock up from zero, or back down, keeping the time
+    // already counted
+    pub fn toggle_stopwatch(&mut self) {
+        self.engine.set_stopwatch(self.engine.stopwatch.is_none());
+        self.session_dirty = true;
+    }
+
+    // A longer work length to offer, in minutes. Only suggested during breaks
+    // so it never interrupts the flow it's about.
+    pub fn flow_suggestion(&self) -> Option<u32> {
+        if self.engine.state == PomodoroState::Work || self.work_override.is_some() {
+            return None;
+        }
+        suggested_work_minutes(self.config.work_duration, self.stats.extensions_today())
+    }
+
+    pub fn accept_flow_suggestion(&mut self) {
+        if let Some(minutes) = self.flow_suggestion() {
+            self.work_override = Some((Stats::today(), minutes));
+            let lengths = self.lengths();
+            self.pomodoro_mut().set_lengths(lengths);
+        }
+    }
+
+    // Bring the running session in line with edited settings: never more time
+    // left than the new duration allows, never past the new cycle count
+    pub fn apply_config(&mut self) {
+        self.apply_theme();
+        self.keymap = Keymap::from_config(&self.config.keybindings);
+        self.badge_failed = false;
+        let (lengths, sequence) = (self.lengths(), self.config.sequence());
+        self.pomodoro_mut().set_lengths(lengths);
+        self.pomodoro_mut().set_sequence(sequence);
+    }
+
+    // The pomodoro timer, set aside while a countdown runs
+    pub fn pomodoro_mut(&mut self) -> &mut PomodoroEngine {
+        match &mut self.countdown {
+            Some(countdown) => &mut countdown.pomodoro,
+            None => &mut self.engine,
+        }
+    }
+
+    // Move on to the next session
+    pub fn advance_state(&mut self, skipped: bool) {
+        let transition = self.engine.advance(skipped);
+        self.finish_session(transition);
+    }
+
+    // Record the session that ended in the history and alert for the one
+    // that began
+    fn finish_session(&mut self, transition: Transition) {
+        let Transition { finished, skipped, .. } = transition;
+        let hooks = if self.offline { Ok(()) } else { self.run_hooks(&transition) };
+        let mut entry = HistoryEntry::new(finished, self.session_started.take(), transition.counted.as_secs(), skipped);
+        if self.auto_starts(transition.next) && self.quota_allows_start() {
+            self.engine.paused = false;
+            (self.last_tick, self.last_wall_tick) = (Instant::now(), SystemTime::now());
+            self.session_started = Some(history::now());
+        }
+        self.timer_event(if skipped { "skip" } else { "transition" }, Some(finished));
+        entry.laps = std::mem::take(&mut self.laps);
+        entry.resumed = std::mem::take(&mut self.session_resumed);
+        entry.overtime = Some(transition.overtime.as_secs()).filter(|&secs| secs > 0);
+        if finished == PomodoroState::Work {
+            entry.task = self.tasks.active_task().map(|task| task.title.clone());
+        }
+        entry.profile = Some(self.config.profile.clone()).filter(|profile| !profile.is_empty());
+        self.idle_extended = false;
+        self.clear_ambient_cue();
+        self.open_break_activity(transition.next);
+        let recorded = history::append(&self.history_path, &entry, self.encrypting());
+        (self.session_dirty, self.transitioned) = (true, true);
+        // A skip needs no alert, the user is at the keyboard
+        let alerted = if self.config.escalation.is_empty() {
+            self.alert_transition(finished)
+        } else {
+            self.escalation = Some(Escalation::new(finished)).filter(|_| !skipped);
+            self.escalate(Duration::ZERO)
+        };
+        let summary = self.push_weekly_summary();
+        for e in [recorded, alerted, hooks, summary].into_iter().filter_map(Result::err) {
+            self.show_error(e);
+        }
+    }
+
+    // Whether `next` runs as soon as it begins, per "auto_start"
+    fn auto_starts(&self, next: PomodoroState) -> bool {
+        for_sessions(&self.config.auto_start, next)
+    }
+
+    // The session-end hook, then the one for what began
+    fn run_hooks(&mut self, transition: &Transition) -> Result<()> {
+        let start = if transition.next == PomodoroState::Work { ("on_work_start", &self.config.on_work_start) } else { ("on_break_start", &self.config.on_break_start) };
+        let mut result = Ok(());
+        for (name, command) in [("on_session_end", &self.config.on_session_end), start].into_iter().filter(|(_, command)| !command.is_empty()) {
+            result = result.and(self.health.track(name, run_hook(name, command, transition)));
+        }
+        result
+    }
+
+    // Send `event`'s alerts: its "alert_routes" entry, or `defaults`. The
+    // webhook, the one that leaves the machine, is held off while offline.
+    pub fn alert(&mut self, event: &str, title: &str, body: &str, defaults: &[&str]) -> Result<()> {
+        let sinks = match self.config.alert_routes.get(event) {
+            Some(sinks) => sinks.clone(),
+            None => defaults.iter().map(|sink| sink.to_string()).collect(),
+        };
+        let mut result = Ok(());
+        for sink in sinks {
+            let sent = match sink.as_str() {
+                "sound" => self.health.track("sound", play(&self.sound_file(), self.config.sound.volume)),
+                "notify" => self.notify(title, body),
+                "webhook" if self.offline => Ok(()),
+                "webhook" => self.health.track("webhook", webhook::post(&self.config.webhook_url, event, title, body)),
+                "tts" => self.health.track("speech", speak(body)),
+                _ => {
+                    self.ring_bell = true;
+                    Ok(())
+                }
+            };
+            result = result.and(sent);
+        }
+        result
+    }
+
+    // Sound for the state that just began
+    pub fn sound_file(&self) -> PathBuf {
+        match self.config.sound.file_for(self.engine.state) {
+            "" => default_sound().to_path_buf(),
+            file => expand_home(file),
+        }
+    }
+
+    // Tell the desktop a session or break is over, so it isn't missed from
+    // another window. Without a route that's the notification and chime
+    // settings, and the return alarm after a long break.
+    fn alert_transition(&mut self, finished: PomodoroState) -> Result<()> {
+        let (event, defaults) = self.end_alert(finished);
+        let (title, body) = self.transition_message(finished);
+        self.alert(event, title, &body, &defaults)
+    }
+
+    // The same alert when the time is up but the session runs over
+    fn alert_overtime(&mut self) -> Result<()> {
+        let (event, defaults) = self.end_alert(self.engine.state);
+        let body = format!("Time's up, running over until ended with {}", self.config.keybindings.skip);
+        self.alert(event, "rpomodoro", &body, &defaults)
+    }
+
+    // The alert event for `finished` ending and its sinks when not routed
+    fn end_alert(&self, finished: PomodoroState) -> (&'static str, Vec<&'static str>) {
+        let return_alarm = finished == PomodoroState::LongBreak && self.config.return_alarm;
+        let defaults = [("notify", return_alarm || self.config.notifications.enabled), ("bell", return_alarm), ("sound", self.config.sound.enabled)];
+        let defaults = defaults.into_iter().filter(|&(_, on)| on).map(|(sink, _)| sink).collect();
+        (if finished == PomodoroState::Work { "work_end" } else { "break_end" }, defaults)
+    }
+
+    pub fn transition_message(&self, finished: PomodoroState) -> (&'static str, String) {
+        if finished == PomodoroState::LongBreak && self.config.return_alarm {
+            return ("Back to your desk", "The long break is over".to_string());
+        }
+        ("rpomodoro", self.config.notifications.text_for(self.engine.state).to_string())
+    }
+
+    // Within "ending_soon_minutes" of the end of a session or break
+    pub fn ending_soon(&self) -> bool {
+        let mark = Duration::from_secs(self.config.ending_soon_minutes as u64 * 60);
+        self.engine.stopwatch.is_none() && self.engine.overtime.is_none() && !mark.is_zero() && self.engine.time_remaining <= mark
+    }
+}
+
+// Whether an "off"/"breaks"/"work"/"both" setting covers `state`
+fn for_sessions(setting: &str, state: PomodoroState) -> bool {
+    match setting {
+        "both" => true,
+        "breaks" => state != PomodoroState::Work,
+        "work" => state == PomodoroState::Work,
+        _ => false,
+    }
+}
+
+// Whether the countdown crossed `minutes` left between two ticks
+pub fn reminder_due(minutes: u32, before: Duration, after: Duration) -> bool {
+    let mark = Duration::from_secs(minutes as u64 * 60);
+    before > mark && after <= mark
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use crate::config::Config;
+    use crossterm::event::{Event, KeyCode, KeyEvent};
+    use proptest::prelude::*;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Tick(u64),
+        Skip,
+        Reset,
+        TogglePause,
+        Configure { work: u32, short: u32, long: u32, cycles: u32 },
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            4 => (0u64..3600).prop_map(Op::Tick),
+            2 => Just(Op::Skip),
+            1 => Just(Op::Reset),
+            2 => Just(Op::TogglePause),
+            1 => (1u32..120, 1u32..60, 1u32..120, 1u32..10)
+                .prop_map(|(work, short, long, cycles)| Op::Configure { work, short, long, cycles }),
+        ]
+    }
+
+    fn apply(app: &mut App, op: &Op) {
+        match *op {
+            Op::Tick(secs) => app.tick(Duration::from_secs(secs)).unwrap(),
+            Op::Skip => app.advance_state(true),
+            Op::Reset => app.reset(),
+            Op::TogglePause => app.toggle_pause(),
+            Op::Configure { work, short, long, cycles } => {
+                app.config.work_duration = work;
+                app.config.short_break = short;
+                app.config.long_break = long;
+                app.config.cycles_before_long = cycles;
+                app.apply_config();
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn invariants_hold_for_any_sequence(ops in prop::collection::vec(op(), 0..200)) {
+            let mut app = App::for_test(Config::default());
+            for op in &ops {
+                apply(&mut app, op);
+                prop_assert!(app.engine.cycle_count < app.config.cycles_before_long.max(1));
+                prop_assert!(app.engine.time_remaining <= app.config.duration_for(app.engine.state));
+            }
+        }
+
+        #[test]
+        fn long_break_every_n_work_sessions(
+            cycles in 1u32..10,
+            ops in prop::collection::vec(prop_oneof![(0u64..3600).prop_map(Op::Tick), Just(Op::Skip), Just(Op::TogglePause)], 0..300),
+        ) {
+            let mut app = App::for_test(Config { cycles_before_long: cycles, ..Config::default() });
+            let mut works_finished = 0;
+            for op in &ops {
+                let before = app.engine.state;
+                apply(&mut app, op);
+                if before == PomodoroState::Work && app.engine.state != PomodoroState::Work {
+                    works_finished += 1;
+                    let expect_long = works_finished % cycles == 0;
+                    prop_assert_eq!(app.engine.state == PomodoroState::LongBreak, expect_long);
+                }
+            }
+        }
+
+        #[test]
+        fn redraw_deadline_reaches_next_second(ms in 1u64..3_600_000) {
+            let mut app = App::for_test(Config::default());
+            app.engine.paused = false;
+            app.engine.time_remaining = Duration::from_millis(ms);
+            let timeout = app.redraw_timeout();
+            prop_assert!(timeout <= Duration::from_secs(1));
+            let shown = app.engine.time_remaining.as_secs();
+            prop_assert!(app.engine.time_remaining.saturating_sub(timeout).as_secs() < shown || shown == 0);
+        }
+    }
+
+    #[test]
+    fn justified_break_skip_is_logged() {
+        let mut app = App::for_test(Config { break_skip: "justify".to_string(), ..Config::default() });
+        app.request_skip();
+        assert_eq!(app.engine.state, PomodoroState::ShortBreak, "work sessions skip freely");
+
+        app.request_skip();
+        assert_eq!(app.engine.state, PomodoroState::ShortBreak);
+        for c in "tired".chars() {
+            app.handle_skip_input(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        app.handle_skip_input(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!(app.engine.state, PomodoroState::Work);
+        assert_eq!(app.stats.skipped_breaks.len(), 1);
+        assert_eq!(app.stats.skipped_breaks[0].reason.as_deref(), Some("tired"));
+    }
+
+    #[test]
+    fn auto_start_runs_only_the_chosen_sessions() {
+        let mut app = App::for_test(Config { auto_start: "breaks".to_string(), ..Config::default() });
+        app.toggle_pause();
+        app.tick(Duration::from_secs(25 * 60 + 1)).unwrap();
+        assert_eq!((app.engine.state, app.engine.paused), (PomodoroState::ShortBreak, false));
+        assert!(app.session_started.is_some());
+        app.advance_state(true);
+        assert_eq!((app.engine.state, app.engine.paused), (PomodoroState::Work, true));
+        assert!(app.session_started.is_none());
+    }
+
+    #[test]
+    fn stopwatch_sessions_are_logged_with_their_length() {
+        let mut app = App::for_test(Config::default());
+        app.execute(Command::Stopwatch);
+        app.toggle_pause();
+        app.tick(Duration::from_secs(110 * 60 + 5)).unwrap();
+        assert_eq!((app.engine.state, app.clock_text().as_str()), (PomodoroState::Work, "01:50"));
+        app.execute(Command::Skip);
+        assert_eq!(app.engine.state, PomodoroState::ShortBreak);
+        assert_eq!(app.clock_text(), "00:00");
+
+        let entries = history::load(&app.history_path, None).unwrap();
+        let logged: Vec<(&str, u64, bool)> = entries.iter().map(|e| (e.state.as_str(), e.seconds, e.skipped)).collect();
+        assert_eq!(logged, [("work", 110 * 60 + 5, false)]);
+        assert_eq!(app.stats.completed_today(), 0);
+    }
+
+    #[test]
+    fn overtime_runs_until_skipped_and_is_logged() {
+        let mut app = App::for_test(Config { overtime: "work".to_string(), ..Config::default() });
+        app.toggle_pause();
+        app.tick(Duration::from_secs(25 * 60 + 90)).unwrap();
+        assert_eq!((app.engine.state, app.clock_text().as_str()), (PomodoroState::Work, "+01:30"));
+        assert_eq!(app.clock_color(), app.theme.urgent);
+        app.execute(Command::Skip);
+        assert_eq!((app.engine.state, app.stats.completed_today()), (PomodoroState::ShortBreak, 1));
+
+        let entries = history::load(&app.history_path, None).unwrap();
+        let logged: Vec<(u64, Option<u64>, bool)> = entries.iter().map(|e| (e.seconds, e.overtime, e.skipped)).collect();
+        assert_eq!(logged, [(25 * 60 + 90, Some(90), false)]);
+
+        app.toggle_pause();
+        app.tick(Duration::from_secs(5 * 60 + 1)).unwrap();
+        assert_eq!(app.engine.state, PomodoroState::Work, "breaks end on time");
+    }
+
+    #[test]
+    fn stats_are_kept_per_profile() {
+        let mut app = App::for_test(Config { profile: "study".to_string(), ..Config::default() });
+        app.toggle_pause();
+        app.tick(Duration::from_secs(25 * 60 + 1)).unwrap();
+        assert_eq!(app.stats.profiles["study"][&Stats::today()], 1);
+        let entries = history::load(&app.history_path, None).unwrap();
+        assert_eq!(entries[0].profile.as_deref(), Some("study"));
+
+        app.open_stats().unwrap();
+        let shown = |app: &App| app.stats_view.as_ref().map(|view| (view.profile.clone(), view.completed_today));
+        assert_eq!(shown(&app), Some((Some("study".to_string()), 1)));
+        app.cycle_stats_profile().unwrap();
+        assert_eq!(shown(&app), Some((None, 1)), "all profiles after the last");
+        app.cycle_stats_profile().unwrap();
+        assert_eq!(shown(&app), Some((Some("deep-work".to_string()), 0)));
+    }
+
+    #[test]
+    fn task_quotas_refuse_another_session_over_the_limit() {
+        let mut quotas = BTreeMap::new();
+        quotas.insert("email".to_string(), 1);
+        let mut app = App::for_test(Config { task_quotas: quotas, quota_action: "refuse".to_string(), ..Config::default() });
+        app.tasks.add("email");
+        app.tasks.select(0);
+        app.toggle_pause();
+        app.tick(Duration::from_secs(25 * 60 + 1)).unwrap();
+        app.advance_state(true);
+        assert_eq!(app.engine.state, PomodoroState::Work);
+
+        app.toggle_pause();
+        assert!(app.engine.paused, "email already had its one session today");
+        app.tasks.select(0);
+        app.toggle_pause();
+        assert!(!app.engine.paused);
+    }
+
+    #[test]
+    fn laps_are_logged_with_their_session() {
+        let mut app = App::for_test(Config::default());
+        app.start_lap();
+        assert!(app.lap_label.is_none(), "nothing to mark before the session starts");
+
+        app.toggle_pause();
+        app.tick(Duration::from_secs(90)).unwrap();
+        app.start_lap();
+        for c in "email".chars() {
+            app.handle_lap_input(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_lap_input(KeyEvent::from(KeyCode::Enter));
+        app.tick(Duration::from_secs(30)).unwrap();
+        app.start_lap();
+        app.handle_lap_input(KeyEvent::from(KeyCode::Enter));
+        app.advance_state(true);
+
+        let entries = history::load(&app.history_path, None).unwrap();
+        let laps = &entries.last().unwrap().laps;
+        assert_eq!(*laps, [Lap { at: 90, label: Some("email".to_string()) }, Lap { at: 120, label: None }]);
+        assert!(app.laps.is_empty());
+    }
+
+    #[test]
+    fn config_mode_pauses_and_restores() {
+        let mut app = App::for_test(Config::default());
+        app.toggle_pause();
+        app.enter_config();
+        assert!(app.engine.paused);
+        app.leave_config().unwrap();
+        assert!(!app.engine.paused, "a running session resumes");
+
+        app.toggle_pause();
+        app.enter_config();
+        app.leave_config().unwrap();
+        assert!(app.engine.paused, "a paused session stays paused");
+    }
+
+    #[test]
+    fn keybindings_replace_the_default_keys() {
+        let json = r#"{"keybindings": {"toggle": "enter", "quit": "X", "skip": "shift-s"}}"#;
+        let mut app = App::for_test(Config::from_json(json).unwrap());
+        assert!(app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char(' ')))));
+        assert!(app.engine.paused, "space is unbound");
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Enter)));
+        assert!(!app.engine.paused);
+        app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char('S'))));
+        assert_eq!(app.engine.state, PomodoroState::ShortBreak, "an unknown key keeps the default");
+        assert!(app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char('q')))));
+        assert!(!app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char('x')))));
+    }
+
+    #[test]
+    fn rebound_screens_close_with_their_own_key() {
+        let json = r#"{"keybindings": {"stats": "y", "integrations": "u"}}"#;
+        let mut app = App::for_test(Config::from_json(json).unwrap());
+        let press = |app: &mut App, key| app.handle_event(Event::Key(KeyEvent::from(KeyCode::Char(key))));
+        press(&mut app, 'y');
+        press(&mut app, 't');
+        assert!(app.stats_view.is_some(), "t isn't bound to stats any more");
+        press(&mut app, 'y');
+        assert!(app.stats_view.is_none());
+        press(&mut app, 'u');
+        press(&mut app, 'i');
+        assert!(app.show_integrations);
+        press(&mut app, 'u');
+        assert!(!app.show_integrations);
+    }
+
+    #[test]
+    fn breaks_run_on_while_idle() {
+        let mut app = App::for_test(Config { break_idle_minutes: 5, ..Config::default() });
+        app.advance_state(false);
+        app.toggle_pause();
+        app.last_input = Instant::now() - Duration::from_secs(10 * 60);
+
+        app.tick(Duration::from_secs(5 * 60 + 90)).unwrap();
+        assert_eq!((app.engine.state, app.engine.extended_minutes), (PomodoroState::ShortBreak, 2));
+        app.last_input = Instant::now();
+        app.tick(Duration::from_secs(60)).unwrap();
+        assert_eq!(app.engine.state, PomodoroState::Work);
+        assert_eq!(app.stats.idle_breaks.values().flatten().collect::<Vec<_>>(), [&7]);
+    }
+
+    #[test]
+    fn completed_work_counts_toward_the_active_task() {
+        let mut app = App::for_test(Config { work_duration: 1, ..Config::default() });
+        let keys = [KeyCode::Char('a'), KeyCode::Char('n'), KeyCode::Char('x'), KeyCode::Enter, KeyCode::Enter, KeyCode::Esc];
+        for code in keys {
+            app.handle_event(Event::Key(KeyEvent::from(code)));
+        }
+        assert_eq!(app.tasks.active_task().map(|task| task.title.as_str()), Some("x"));
+
+        app.toggle_pause();
+        app.tick(Duration::from_secs(61)).unwrap();
+        assert_eq!(Tasks::load(&app.tasks_path, None).unwrap().tasks[0].pomodoros, 1);
+        assert!(std::fs::read_to_string(&app.history_path).unwrap().contains("\"task\":\"x\""));
+    }
+
+    #[test]
+    fn event_payloads_say_where_the_timer_is() {
+        let mut app = App::for_test(Config::default());
+        app.advance_state(true);
+        let payload = app.event_payload("skip", Some(PomodoroState::Work));
+        assert_eq!(payload["state"], "short_break");
+        assert_eq!(payload["finished"], "work");
+        assert_eq!((payload["cycle"].as_u64(), payload["remaining_secs"].as_u64()), (Some(1), Some(300)));
+        assert!(app.event_payload("pause", None)["finished"].is_null());
+    }
+
+    #[test]
+    fn long_break_reminders_fire_once() {
+        let minute = Duration::from_secs(60);
+        assert!(reminder_due(10, 10 * minute + Duration::from_millis(500), 10 * minute));
+        assert!(!reminder_due(10, 10 * minute, 10 * minute - Duration::from_secs(1)));
+        assert!(!reminder_due(2, 10 * minute, 9 * minute));
+    }
+
+    // Keeps what it was asked to show
+    struct Recorder(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl Notifier for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn notify(&self, title: &str, _body: &str) -> Result<()> {
+            self.0.borrow_mut().push(title.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn routes_replace_the_default_alerts() {
+        let routes = [("work_end".to_string(), vec!["bell".to_string()]), ("break_end".to_string(), Vec::new())];
+        let mut app = App::for_test(Config { alert_routes: routes.into_iter().collect(), ..Config::default() });
+        let shown = std::rc::Rc::default();
+        app.notifier = Box::new(Recorder(std::rc::Rc::clone(&shown)));
+        app.advance_state(false);
+        assert!(std::mem::take(&mut app.ring_bell));
+        app.advance_state(false);
+        assert!(shown.borrow().is_empty() && !app.ring_bell);
+    }
+
+    #[test]
+    fn webhook_alerts_are_held_off_offline() {
+        let routes = [("work_end".to_string(), vec!["webhook".to_string()])];
+        let mut app = App::for_test(Config { alert_routes: routes.into_iter().collect(), ..Config::default() });
+        app.offline = true;
+        assert!(app.alert("work_end", "rpomodoro", "done", &[]).is_ok());
+        assert!(app.health.reports().is_empty());
+        app.offline = false;
+        assert!(app.alert("work_end", "rpomodoro", "done", &[]).is_err());
+    }
+
+    #[test]
+    fn warnings_come_before_the_work_session_ends() {
+        let routes = [("warning".to_string(), vec!["notify".to_string()])];
+        let mut app = App::for_test(Config { warning_minutes: 2, alert_routes: routes.into_iter().collect(), ..Config::default() });
+        let shown = std::rc::Rc::default();
+        app.notifier = Box::new(Recorder(std::rc::Rc::clone(&shown)));
+        app.toggle_pause();
+        app.tick(Duration::from_secs(22 * 60)).unwrap();
+        assert!(shown.borrow().is_empty());
+        assert_ne!(app.clock_color(), app.theme.urgent);
+
+        app.tick(Duration::from_secs(60)).unwrap();
+        assert_eq!(*shown.borrow(), ["Work session"]);
+        assert_eq!(app.clock_color(), app.theme.urgent);
+    }
+
+    #[test]
+    fn sleep_gaps_pause_or_count_as_configured() {
+        let mut app = App::for_test(Config { on_sleep: "ask".to_string(), ..Config::default() });
+        app.toggle_pause();
+        app.last_wall_tick -= Duration::from_secs(20 * 60);
+        app.update().unwrap();
+        assert!(app.engine.paused);
+        assert!(app.engine.time_remaining > Duration::from_secs(24 * 60), "the gap isn't counted before the answer");
+        app.answer_sleep_gap(Some(true)).unwrap();
+        assert!(!app.engine.paused && app.sleep_gap.is_none());
+        assert!(app.engine.time_remaining <= Duration::from_secs(5 * 60));
+
+        app.config.on_sleep = "pause".to_string();
+        app.last_wall_tick -= Duration::from_secs(60 * 60);
+        app.update().unwrap();
+        assert!(app.engine.paused && app.sleep_gap.is_none());
+        assert_eq!(app.active_toast(), Some("paused after 60 min asleep"));
+    }
+
+    #[test]
+    fn desktop_idle_pauses_work_and_gives_the_time_back() {
+        let mut app = App::for_test(Config { idle_pause_minutes: 5, ..Config::default() });
+        app.toggle_pause();
+        app.tick(Duration::from_secs(10 * 60)).unwrap();
+        app.desktop_idle(Duration::from_secs(4 * 60));
+        assert!(!app.engine.paused);
+        app.desktop_idle(Duration::from_secs(6 * 60));
+        assert!(app.engine.paused && app.idle_paused);
+        assert_eq!(app.engine.time_remaining, Duration::from_secs(21 * 60));
+        app.desktop_idle(Duration::from_secs(2));
+        assert!(!app.engine.paused && !app.idle_paused);
+
+        app.advance_state(false);
+        app.toggle_pause();
+        app.desktop_idle(Duration::from_secs(6 * 60));
+        assert!(!app.engine.paused, "breaks aren't paused");
+
+        app.advance_state(false);
+        app.config.overtime = "work".to_string();
+        app.engine.run_over = true;
+        app.toggle_pause();
+        app.tick(Duration::from_secs(25 * 60 + 8 * 60)).unwrap();
+        app.desktop_idle(Duration::from_secs(6 * 60));
+        assert_eq!((app.engine.time_remaining, app.engine.overtime), (Duration::ZERO, Some(Duration::from_secs(2 * 60))));
+        assert_eq!(app.clock_text(), "+02:00");
+        app.desktop_idle(Duration::from_secs(2));
+        app.tick(Duration::from_secs(60)).unwrap();
+        app.desktop_idle(Duration::from_secs(5 * 60));
+        assert_eq!((app.engine.time_remaining, app.engine.overtime), (Duration::from_secs(2 * 60), None));
+
+        app.desktop_idle(Duration::from_secs(2));
+        app.toggle_stopwatch();
+        app.tick(Duration::from_secs(10 * 60)).unwrap();
+        app.desktop_idle(Duration::from_secs(6 * 60));
+        assert_eq!(app.engine.stopwatch, Some(Duration::from_secs(27 * 60)));
+        app.desktop_idle(Duration::from_secs(2));
+        app.toggle_stopwatch();
+
+        app.start_countdown(Duration::from_secs(20 * 60)).unwrap();
+        app.tick(Duration::from_secs(10 * 60)).unwrap();
+        app.desktop_idle(Duration::from_secs(6 * 60));
+        assert!(!app.engine.paused, "countdowns aren't paused");
+        assert_eq!(app.clock_text(), "10:00");
+    }
+
+    #[test]
+    fn transitions_notify() {
+        let mut app = App::for_test(Config { cycles_before_long: 1, ..Config::default() });
+        let shown = std::rc::Rc::default();
+        app.notifier = Box::new(Recorder(std::rc::Rc::clone(&shown)));
+        app.advance_state(false);
+        app.advance_state(false);
+        assert_eq!(*shown.borrow(), ["rpomodoro", "Back to your desk"]);
+        assert!(app.ring_bell);
+
+        app.config.notifications.enabled = false;
+        app.advance_state(false);
+        assert_eq!(shown.borrow().len(), 2);
+    }
+}
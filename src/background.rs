@@ -0,0 +1,90 @@
+use crossterm::style::Color;
+
+// Works out the terminal's background color for the "background" setting:
+// asked of the terminal with OSC 11, else guessed from COLORFGBG. None when
+// neither says, which is treated as dark.
+pub fn detect(setting: &str) -> Option<Color> {
+    match setting {
+        "dark" => Some(Color::Rgb { r: 0, g: 0, b: 0 }),
+        "light" => Some(Color::Rgb { r: 255, g: 255, b: 255 }),
+        _ => query().or_else(colorfgbg),
+    }
+}
+
+// rxvt, Konsole and others export "fg;bg" as palette indexes
+fn colorfgbg() -> Option<Color> {
+    let value = std::env::var("COLORFGBG").ok()?;
+    let background: u8 = value.rsplit(';').next()?.parse().ok()?;
+    match background {
+        7 | 9..=15 => detect("light"),
+        _ => detect("dark"),
+    }
+}
+
+// Parse an OSC 11 reply, "\x1b]11;rgb:RRRR/GGGG/BBBB" ended by BEL or ST.
+// Channels have one to four hex digits.
+#[cfg_attr(not(unix), allow(dead_code))]
+pub fn parse_reply(reply: &str) -> Option<Color> {
+    let (_, rest) = reply.split_once("]11;rgb:")?;
+    let end = rest.find(['\x07', '\x1b']).unwrap_or(rest.len());
+    let mut channels = rest[..end].split('/').map(|channel| {
+        let value = u32::from_str_radix(channel, 16).ok().filter(|_| (1..=4).contains(&channel.len()))?;
+        let max = (1u32 << (4 * channel.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some(Color::Rgb { r, g, b })
+}
+
+// Ask the terminal, raw mode already on. OSC 11 is followed by a device
+// attributes request that every terminal answers, so one that doesn't know
+// OSC 11 is noticed without waiting out the timeout.
+#[cfg(unix)]
+fn query() -> Option<Color> {
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::fd::AsRawFd;
+    use std::time::{Duration, Instant};
+
+    let mut tty = OpenOptions::new().read(true).write(true).open("/dev/tty").ok()?;
+    tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").ok()?;
+    tty.flush().ok()?;
+
+    let deadline = Instant::now() + Duration::from_millis(500);
+    let mut reply = Vec::new();
+    // Read a byte at a time so no keypress after the replies is swallowed
+    while !(reply.ends_with(b"c") && reply.windows(3).any(|w| w == b"\x1b[?")) {
+        let left = deadline.saturating_duration_since(Instant::now()).as_millis() as i32;
+        let mut fd = libc::pollfd { fd: tty.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        if left == 0 || unsafe { libc::poll(&mut fd, 1, left) } <= 0 {
+            break;
+        }
+        let mut byte = [0u8];
+        if tty.read(&mut byte).ok()? == 0 {
+            break;
+        }
+        reply.push(byte[0]);
+    }
+    parse_reply(&String::from_utf8_lossy(&reply))
+}
+
+// The Windows console has no way to ask; COLORFGBG still applies
+#[cfg(not(unix))]
+fn query() -> Option<Color> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc11_replies_parse() {
+        let white = Some(Color::Rgb { r: 255, g: 255, b: 255 });
+        assert_eq!(parse_reply("\x1b]11;rgb:ffff/ffff/ffff\x07"), white);
+        assert_eq!(parse_reply("\x1b]11;rgb:ff/ff/ff\x1b\\\x1b[?62;c"), white);
+        assert_eq!(parse_reply("\x1b]11;rgb:2828/2c2c/3434\x1b\\"), Some(Color::Rgb { r: 40, g: 44, b: 52 }));
+        assert_eq!(parse_reply("\x1b[?62;22c"), None);
+        assert_eq!(parse_reply("\x1b]11;rgb:ffff/ffff\x07"), None);
+    }
+}
@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::storage::{create_dir, is_read_only, write_atomic, Dirs};
+
+// Bumped when the bundle layout changes; restore refuses newer formats
+pub const BACKUP_FORMAT: u32 = 1;
+
+// Everything in the config and data dirs in one JSON file, for moving to
+// another machine or keeping a copy before an upgrade. The state dir is
+// runtime only and left out.
+#[derive(Debug, Serialize, Deserialize)]
+struct Bundle {
+    format: u32,
+    // rpomodoro version that wrote it
+    version: String,
+    created: String,
+    files: Vec<BundledFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BundledFile {
+    // "config" or "data"
+    dir: String,
+    name: String,
+    // UTF-8 files as text, anything else (like encrypted stats) as bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bytes: Option<Vec<u8>>,
+}
+
+// `rpomodoro backup create|restore FILE`. With --read-only the files are
+// listed and nothing is written.
+pub fn run_backup(dirs: &Dirs, action: &str, file: &Path) -> Result<()> {
+    let mut dirs = dirs.clone();
+    dirs.apply_config(&Config::read(&dirs.config_file()).data_dir);
+    if is_read_only() {
+        println!("--read-only: listing the files only, nothing is written");
+    }
+    match action {
+        "create" => create(&dirs, file),
+        "restore" => restore(&mut dirs, file),
+        _ => Err(Error::Usage("usage: rpomodoro backup create|restore FILE".to_string())),
+    }
+}
+
+fn create(dirs: &Dirs, file: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    for (dir_name, dir) in [("config", &dirs.config), ("data", &dirs.data)] {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(Error::io(dir, e)),
+        };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| is_bundled(path)).collect();
+        paths.sort();
+        for path in paths {
+            let content = fs::read(&path).map_err(|e| Error::io(&path, e))?;
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let (text, bytes) = match String::from_utf8(content) {
+                Ok(text) => (Some(text), None),
+                Err(e) => (None, Some(e.into_bytes())),
+            };
+            println!("{}/{}", dir_name, name);
+            files.push(BundledFile { dir: dir_name.to_string(), name, text, bytes });
+        }
+    }
+
+    let bundle = Bundle {
+        format: BACKUP_FORMAT,
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        created: chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string(),
+        files,
+    };
+    let json = serde_json::to_string_pretty(&bundle).map_err(|e| Error::config(file, e))?;
+    write_atomic(file, json)
+}
+
+fn restore(dirs: &mut Dirs, file: &Path) -> Result<()> {
+    let json = fs::read_to_string(file).map_err(|e| Error::io(file, e))?;
+    let bundle: Bundle = serde_json::from_str(&json).map_err(|e| Error::config(file, format!("not an rpomodoro backup: {}", e)))?;
+    if bundle.format > BACKUP_FORMAT {
+        return Err(Error::config(file, format!("written by rpomodoro {}, which is newer than this one; upgrade first", bundle.version)));
+    }
+    if parse_version(&bundle.version) > parse_version(env!("CARGO_PKG_VERSION")) {
+        println!("note: backup is from rpomodoro {}, newer than this {}", bundle.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    // Config first, since a restored "data_dir" moves where the data goes
+    let (config_files, data_files): (Vec<_>, Vec<_>) = bundle.files.into_iter().partition(|f| f.dir == "config");
+    write_files(&dirs.config, "config", config_files, file)?;
+    dirs.apply_config(&Config::read(&dirs.config_file()).data_dir);
+    write_files(&dirs.data, "data", data_files, file)
+}
+
+fn write_files(dir: &Path, dir_name: &str, files: Vec<BundledFile>, bundle: &Path) -> Result<()> {
+    create_dir(dir)?;
+    for file in files {
+        // Only plain names, so a crafted bundle can't write outside the dir
+        if file.dir != dir_name || file.name.is_empty() || file.name.contains(['/', '\\']) || file.name.starts_with('.') {
+            return Err(Error::config(bundle, format!("unexpected file {}/{}", file.dir, file.name)));
+        }
+        let content = file.text.map(String::into_bytes).or(file.bytes).unwrap_or_default();
+        write_atomic(&dir.join(&file.name), content)?;
+        println!("{}/{}", dir_name, file.name);
+    }
+    Ok(())
+}
+
+// Files worth keeping: not temporaries, set-aside copies or sockets
+fn is_bundled(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.is_file() && !name.starts_with('.') && !name.ends_with(".tmp") && !name.ends_with(".bad")
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version.split('.').map(|part| part.parse().unwrap_or(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_round_trip() {
+        let root = std::env::temp_dir().join(format!("rpomodoro-test-backup-{}", std::process::id()));
+        let from = Dirs::resolve(Some(root.join("from/config")), Some(root.join("from/data")), false);
+        let to = Dirs::resolve(Some(root.join("to/config")), Some(root.join("to/data")), false);
+        fs::create_dir_all(&from.config).unwrap();
+        fs::create_dir_all(&from.data).unwrap();
+        fs::write(from.config.join("config.json"), "{\"work_duration\": 50}").unwrap();
+        fs::write(from.data.join("stats.json"), [0xff, 0x00, 0x42]).unwrap();
+        fs::write(from.data.join("stats.json.bad"), "junk").unwrap();
+
+        let bundle = root.join("backup.json");
+        run_backup(&from, "create", &bundle).unwrap();
+        run_backup(&to, "restore", &bundle).unwrap();
+
+        assert_eq!(fs::read_to_string(to.config.join("config.json")).unwrap(), "{\"work_duration\": 50}");
+        assert_eq!(fs::read(to.data.join("stats.json")).unwrap(), [0xff, 0x00, 0x42]);
+        assert!(!to.data.join("stats.json.bad").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,1184 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::engine::{PomodoroState, Step};
+use crate::error::{Error, Result};
+use crate::storage::{is_read_only, set_aside, write_atomic};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: String,
+    // Themes made in the theme editor or by hand, selected by name in
+    // "theme"
+    pub themes: BTreeMap<String, CustomTheme>,
+    // "auto" asks the terminal whether its background is light, or "dark"
+    // or "light" when it can't tell
+    pub background: String,
+    // "auto" goes by COLORTERM and TERM; "truecolor", "256" or "16" when
+    // those are wrong
+    pub color_mode: String,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub work_duration: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub short_break: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub long_break: u32,
+    pub cycles_before_long: u32,
+    // A custom plan walked instead of the cycle above, over and over, e.g.
+    // ["work:25", "break:5", "work:50", "long:20"]. Steps are "work",
+    // "break" (or "short") and "long" with their minutes. Empty for the
+    // usual cycle.
+    pub sequence: Vec<String>,
+    // Named sets of the four values above, besides the built-in PRESETS,
+    // which one of the same name replaces. p switches to previous_preset,
+    // which then becomes the one just left.
+    pub presets: BTreeMap<String, Preset>,
+    pub previous_preset: String,
+    // Named sets of the four values above and the theme, switched between
+    // with --profile or in the config screen. The active profile's values
+    // are the ones above; they're stored back into it on switching away.
+    pub profiles: BTreeMap<String, Profile>,
+    // The active profile, "" for none
+    pub profile: String,
+    // Minutes at the end of a work session during which the digits fade
+    // toward the theme's urgent color. 0 disables the effect.
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub urgency_minutes: u32,
+    // A notification and chime this many minutes before a work session
+    // ends, to wrap up before the break, with the digits turning the urgent
+    // color from then on. 0 turns it off.
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub warning_minutes: u32,
+    // During running breaks the clock's brightness rises and falls at this
+    // many breaths a minute, as a cue to slow down. 0 turns it off.
+    pub breathing_pace: u32,
+    // Once the timer has been paused this many seconds, the whole screen
+    // is drawn in the dim color until it runs again, so a timer left
+    // unstarted doesn't pass for a running one. 0 turns it off.
+    pub dim_paused_seconds: u32,
+    // "block" draws digits in the theme color, "inverted" cuts them out of a
+    // filled block (negative space)
+    pub digit_style: String,
+    // Digit font: "classic" (3x5 cells), "large" (6x10, when it fits) or
+    // "slim" (single width)
+    pub clock_style: String,
+    // "digital" for the big digits, "analog" for a dial with a sweeping hand
+    pub clock_face: String,
+    // A bar under the digital clock filling up as the session goes on
+    pub show_progress: bool,
+    // Show the current time of day in small digits under the timer
+    pub show_wall_clock: bool,
+    // Completed pomodoros aimed for each day, drawn as hollow tomatoes
+    pub daily_goal: u32,
+    pub show_tomatoes: bool,
+    pub statusline: StatuslineConfig,
+    // Keys for the main view's actions
+    pub keybindings: Keybindings,
+    // Locale for durations and times of day, e.g. "de" or "en-US". "auto"
+    // follows LC_ALL/LC_TIME/LANG.
+    pub time_locale: String,
+    // "auto" (locale default), "12h" or "24h"
+    pub clock_format: String,
+    // "auto" (right to left for Arabic, Hebrew, Persian and Urdu locales),
+    // "ltr" or "rtl". Right to left mirrors the statusline and prints
+    // right-to-left text in display order; "ltr" leaves that to terminals
+    // that reorder it themselves.
+    pub text_direction: String,
+    // What it takes to skip a break: "free", "justify" (type a reason) or
+    // "cooldown" (the skip happens after 10 seconds unless cancelled)
+    pub break_skip: String,
+    // Something to do in a panel during breaks, closed when the break ends:
+    // "off", "scramble" (unscramble words) or "2048"
+    pub break_activity: String,
+    // Pause a running session while the config screen is open, resuming it
+    // on leaving
+    pub pause_in_config: bool,
+    // Sessions that start running on their own when the one before ends:
+    // "off", "breaks", "work" or "both"
+    pub auto_start: String,
+    // Sessions that keep going past their end, counting the overtime in
+    // the warning color until ended with the skip key: "off", "work",
+    // "breaks" or "both"
+    pub overtime: String,
+    // Notifications during long breaks keyed by minutes left, e.g.
+    // {"10": "10 minutes left", "2": "2 minutes, wrap up lunch"}
+    #[serde(with = "minute_keys")]
+    pub long_break_reminders: BTreeMap<u32, String>,
+    // When a long break ends, a distinct "back to your desk" notification
+    // and a terminal bell instead of the usual one
+    pub return_alarm: bool,
+    // A break that ends after this many minutes without a key press runs on
+    // a minute at a time until there's one, instead of leaving work waiting
+    // paused. 0 turns it off.
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub break_idle_minutes: u32,
+    // Desktop notifications when a session or break ends
+    pub notifications: NotificationsConfig,
+    // A chime when a session or break ends
+    pub sound: SoundConfig,
+    // Which alerts each event gets, replacing the notification, chime and
+    // return alarm settings for the events listed. Events are "work_end",
+    // "break_end", "goal_reached" (daily_goal), "reminder" (long break
+    // reminders), "warning" (warning_minutes) and "countdown" (a one-off
+    // timer ran out); alerts are "sound", "notify", "webhook", "tts" and "bell",
+    // e.g. {"work_end": ["sound", "notify"], "break_end": []}
+    pub alert_routes: BTreeMap<String, Vec<String>>,
+    // Where the "webhook" alert POSTs
+    pub webhook_url: String,
+    // Where last week's report is pushed on the first run or transition of
+    // a new week
+    pub weekly_summary: WeeklySummaryConfig,
+    // Every start, pause, resume, skip and transition is POSTed here as
+    // JSON, for Home Assistant, n8n and the like. Empty turns it off.
+    pub event_webhook_url: String,
+    // This many minutes before any session or break ends, an "ending_soon"
+    // event goes to the event webhook and D-Bus, and status readers see
+    // "ending_soon": true, so lights and bars can change ahead of the
+    // transition. 0 turns it off.
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub ending_soon_minutes: u32,
+    // A quiet cue at these minutes left in a work session, for a terminal
+    // in view but out of focus: "urgent" (a bell and a marked window
+    // title), "cursor" (the cursor turns the urgent color) or "profile"
+    // (iTerm2 switches to ambient_profile). Undone when the session ends.
+    // No milestones or "off" turns it off.
+    pub ambient_milestones: Vec<u32>,
+    pub ambient_cue: String,
+    pub ambient_profile: String,
+    // Alerts that build up while a finished session waits to be
+    // acknowledged, replacing the notification and chime above. Empty
+    // keeps the single alert.
+    pub escalation: Vec<EscalationStep>,
+    // Run when a break ends to bring the terminal to the front: "wmctrl",
+    // "hyprctl", "osascript", or any shell command ({title} is the window
+    // title). Empty does nothing.
+    pub focus_command: String,
+    // Shell commands run, without waiting, when a work session begins, when
+    // a break begins, and when any session or break ends. They're told what
+    // happened in RPOMODORO_FINISHED, RPOMODORO_NEXT and RPOMODORO_SKIPPED.
+    // Empty does nothing.
+    pub on_work_start: String,
+    pub on_break_start: String,
+    pub on_session_end: String,
+    // Text file for OBS's "read from file" text source, rewritten as the
+    // timer changes. Empty turns it off.
+    pub badge_file: String,
+    // What goes in it: statusline segment names in braces, e.g.
+    // "{mode} {time}"
+    pub badge_template: String,
+    // TCP control port for Stream Deck plugins and other remote clients, in
+    // builds with the "remote" feature. 0 turns it off.
+    pub remote_port: u16,
+    // Address to listen on; "0.0.0.0" to allow other machines
+    pub remote_bind: String,
+    // When set, clients must send "auth <token>" first
+    pub remote_token: String,
+    // When the timer's position is saved for the next run: "interval"
+    // (every autosave_seconds while it changes), "transitions" or "exit".
+    // Less often means fewer writes on SD cards and network home dirs.
+    pub autosave: String,
+    pub autosave_seconds: u32,
+    // A session saved at most this long before rpomodoro starts again is
+    // carried on as the same one in the history; an older one is recorded
+    // as far as it got and starts over
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub resume_within_minutes: u32,
+    // With no key press or mouse move anywhere on the desktop for this
+    // many minutes, a running work session pauses, taking back the idle
+    // time. On return "resume" carries on by itself and "ask" leaves it
+    // paused with a reminder. Breaks are left alone. 0 turns it off.
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub idle_pause_minutes: u32,
+    pub idle_return: String,
+    // Most work sessions a day for a task, by title, e.g. {"email": 2}, to
+    // ration low-value work. Starting one more "warn"s, or "refuse"s to
+    // start until another task is picked.
+    pub task_quotas: BTreeMap<String, u32>,
+    pub quota_action: String,
+    // A gap of at least "sleep_gap_seconds" between two ticks of a running
+    // timer means the machine was suspended. "pause" stops the timer where
+    // it was before the gap, "ask" also asks whether to count the gap, and
+    // "count" counts it as if the timer had kept running.
+    pub on_sleep: String,
+    pub sleep_gap_seconds: u32,
+    // Start in offline mode: no control channel, remote port, hooks or
+    // exports, only the timer (also --offline, or o while running)
+    pub offline: bool,
+    // Desktop notification backend: "auto" picks one for this platform, or
+    // one of "notify-send", "osascript", "windows", "terminal", "none"
+    pub notifier: String,
+    // Where stats and other data are kept instead of the platform data dir;
+    // "~/" is expanded. Empty for the default.
+    pub data_dir: String,
+    // Encrypt stats with a passphrase (RPOMODORO_PASSPHRASE, or asked for
+    // at startup)
+    pub encrypt_data: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatuslineConfig {
+    // Drawn between segments of the same group in plain mode
+    pub separator: String,
+    // Draw segments as colored blocks joined by powerline glyphs (needs a
+    // powerline/nerd font)
+    pub powerline: bool,
+    pub powerline_left: String,
+    pub powerline_right: String,
+    // Segment names for each side: mode, status, preset, time, cycles, ends, flow,
+    // health, clock, battery, keys
+    pub left: Vec<String>,
+    pub center: Vec<String>,
+    pub right: Vec<String>,
+    // Segment name to "primary", "dim",
+    // "urgent" or a "#rrggbb" hex color
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for StatuslineConfig {
+    fn default() -> Self {
+        StatuslineConfig {
+            separator: "|".to_string(),
+            powerline: false,
+            powerline_left: "\u{e0b2}".to_string(),
+            powerline_right: "\u{e0b0}".to_string(),
+            left: vec!["mode".to_string(), "status".to_string()],
+            center: vec!["preset".to_string(), "cycles".to_string(), "ends".to_string(), "flow".to_string(), "health".to_string()],
+            right: vec!["keys".to_string()],
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    // Text shown when each state begins
+    pub work: String,
+    pub short_break: String,
+    pub long_break: String,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            enabled: true,
+            work: "Break's over, time to focus".to_string(),
+            short_break: "Work session done, take a short break".to_string(),
+            long_break: "Work session done, time for a long break".to_string(),
+        }
+    }
+}
+
+impl NotificationsConfig {
+    pub fn text_for(&self, state: PomodoroState) -> &str {
+        match state {
+            PomodoroState::Work => &self.work,
+            PomodoroState::ShortBreak => &self.short_break,
+            PomodoroState::LongBreak => &self.long_break,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    pub enabled: bool,
+    // 0 to 100
+    pub volume: u32,
+    // Sound file played when each state begins ("~/" is expanded); empty
+    // for the system chime
+    pub work: String,
+    pub short_break: String,
+    pub long_break: String,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        SoundConfig { enabled: false, volume: 80, work: String::new(), short_break: String::new(), long_break: String::new() }
+    }
+}
+
+impl SoundConfig {
+    pub fn file_for(&self, state: PomodoroState) -> &str {
+        match state {
+            PomodoroState::Work => &self.work,
+            PomodoroState::ShortBreak => &self.short_break,
+            PomodoroState::LongBreak => &self.long_break,
+        }
+    }
+}
+
+// A character ("r", "?"), "space", "enter", "tab", "backspace", or "f1" to
+// "f11" (F12 is the frame overlay). Letters match with or without shift. When
+// two actions share a key, the one listed first here wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keybindings {
+    pub toggle: String,
+    pub reset: String,
+    pub skip: String,
+    pub extend: String,
+    pub config: String,
+    pub quit: String,
+    // Take today's suggested longer work block
+    pub flow: String,
+    pub offline: String,
+    pub stats: String,
+    // Switch to the previous preset
+    pub preset: String,
+    pub lap: String,
+    // Count the clock up from zero instead of down
+    pub stopwatch: String,
+    // Type a length for a one-off countdown
+    pub timer: String,
+    pub integrations: String,
+    pub tasks: String,
+    // The command line, for `:set`
+    pub palette: String,
+}
+
+impl Default for Keybindings {
+    fn default() -> Self {
+        let key = |key: &str| key.to_string();
+        Keybindings {
+            toggle: key("space"),
+            reset: key("r"),
+            skip: key("s"),
+            extend: key("e"),
+            config: key("c"),
+            quit: key("q"),
+            flow: key("f"),
+            offline: key("o"),
+            stats: key("t"),
+            preset: key("p"),
+            lap: key("m"),
+            stopwatch: key("w"),
+            timer: key("d"),
+            integrations: key("i"),
+            tasks: key("a"),
+            palette: key(":"),
+        }
+    }
+}
+
+impl Keybindings {
+    // Every action's name, the same as the control channel's where there is
+    // one, and key
+    pub fn actions(&self) -> [(&'static str, &str); 16] {
+        [
+            ("toggle", &self.toggle),
+            ("reset", &self.reset),
+            ("skip", &self.skip),
+            ("extend", &self.extend),
+            ("config", &self.config),
+            ("quit", &self.quit),
+            ("flow", &self.flow),
+            ("offline", &self.offline),
+            ("stats", &self.stats),
+            ("preset", &self.preset),
+            ("lap", &self.lap),
+            ("stopwatch", &self.stopwatch),
+            ("timer", &self.timer),
+            ("integrations", &self.integrations),
+            ("tasks", &self.tasks),
+            ("palette", &self.palette),
+        ]
+    }
+
+    fn sanitize(&mut self) {
+        let defaults = Keybindings::default();
+        let fields = [
+            (&mut self.toggle, defaults.toggle),
+            (&mut self.reset, defaults.reset),
+            (&mut self.skip, defaults.skip),
+            (&mut self.extend, defaults.extend),
+            (&mut self.config, defaults.config),
+            (&mut self.quit, defaults.quit),
+            (&mut self.flow, defaults.flow),
+            (&mut self.offline, defaults.offline),
+            (&mut self.stats, defaults.stats),
+            (&mut self.preset, defaults.preset),
+            (&mut self.lap, defaults.lap),
+            (&mut self.stopwatch, defaults.stopwatch),
+            (&mut self.timer, defaults.timer),
+            (&mut self.integrations, defaults.integrations),
+            (&mut self.tasks, defaults.tasks),
+            (&mut self.palette, defaults.palette),
+        ];
+        for (key, default) in fields {
+            *key = key.to_lowercase();
+            if !is_key_name(key) {
+                *key = default;
+            }
+        }
+    }
+}
+
+pub fn is_key_name(key: &str) -> bool {
+    let f_key = key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()).is_some_and(|n| (1..=11).contains(&n));
+    key.chars().count() == 1 || KEY_NAMES.contains(&key) || f_key
+}
+
+// Colors are "#rrggbb"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomTheme {
+    pub primary: String,
+    pub dim: String,
+}
+
+impl Default for CustomTheme {
+    fn default() -> Self {
+        CustomTheme { primary: "#60a5fa".to_string(), dim: "#93c5fd".to_string() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub work_duration: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub short_break: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub long_break: u32,
+    pub cycles_before_long: u32,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Preset { work_duration: 25, short_break: 5, long_break: 15, cycles_before_long: 4 }
+    }
+}
+
+// Empty URLs leave a target out
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeeklySummaryConfig {
+    // An ntfy topic, e.g. "https://ntfy.sh/my-pomodoros"
+    pub ntfy_url: String,
+    // e.g. "smtps://smtp.example.com:465", with a login when it needs one
+    pub smtp_url: String,
+    pub smtp_user: String,
+    pub smtp_password: String,
+    pub mail_from: String,
+    pub mail_to: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub work_duration: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub short_break: u32,
+    #[serde(deserialize_with = "crate::duration::minutes")]
+    pub long_break: u32,
+    pub cycles_before_long: u32,
+    pub theme: String,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        let lengths = Preset::default();
+        Profile {
+            work_duration: lengths.work_duration,
+            short_break: lengths.short_break,
+            long_break: lengths.long_break,
+            cycles_before_long: lengths.cycles_before_long,
+            theme: "blue".to_string(),
+        }
+    }
+}
+
+// One stage of an escalation, `after` seconds into it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EscalationStep {
+    pub after: u32,
+    // "flash" (the statusline), "bell", "notify" or "sound"
+    pub alert: String,
+    // For "sound": play again every this many seconds (0 plays once), each
+    // time volume_step louder, up to 100
+    pub every: u32,
+    pub volume_step: u32,
+}
+
+impl Default for EscalationStep {
+    fn default() -> Self {
+        EscalationStep { after: 0, alert: "flash".to_string(), every: 0, volume_step: 10 }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            theme: "blue".to_string(),
+            themes: BTreeMap::new(),
+            background: "auto".to_string(),
+            color_mode: "auto".to_string(),
+            work_duration: 25,
+            short_break: 5,
+            long_break: 15,
+            cycles_before_long: 4,
+            sequence: Vec::new(),
+            presets: BTreeMap::new(),
+            previous_preset: "deep-work".to_string(),
+            profiles: BTreeMap::from([
+                ("deep-work".to_string(), Profile { work_duration: 50, short_break: 10, long_break: 30, cycles_before_long: 2, theme: "purple".to_string() }),
+                ("study".to_string(), Profile::default()),
+            ]),
+            profile: String::new(),
+            urgency_minutes: 2,
+            warning_minutes: 0,
+            breathing_pace: 0,
+            dim_paused_seconds: 0,
+            digit_style: "block".to_string(),
+            clock_style: "classic".to_string(),
+            clock_face: "digital".to_string(),
+            show_progress: false,
+            show_wall_clock: false,
+            daily_goal: 8,
+            show_tomatoes: true,
+            statusline: StatuslineConfig::default(),
+            keybindings: Keybindings::default(),
+            time_locale: "auto".to_string(),
+            clock_format: "auto".to_string(),
+            text_direction: "auto".to_string(),
+            break_skip: "free".to_string(),
+            break_activity: "off".to_string(),
+            pause_in_config: true,
+            auto_start: "off".to_string(),
+            overtime: "off".to_string(),
+            long_break_reminders: BTreeMap::new(),
+            return_alarm: true,
+            break_idle_minutes: 0,
+            notifications: NotificationsConfig::default(),
+            sound: SoundConfig::default(),
+            alert_routes: BTreeMap::new(),
+            webhook_url: String::new(),
+            event_webhook_url: String::new(),
+            ending_soon_minutes: 0,
+            weekly_summary: WeeklySummaryConfig::default(),
+            ambient_milestones: Vec::new(),
+            ambient_cue: "off".to_string(),
+            ambient_profile: "Pomodoro".to_string(),
+            escalation: Vec::new(),
+            focus_command: String::new(),
+            on_work_start: String::new(),
+            on_break_start: String::new(),
+            on_session_end: String::new(),
+            badge_file: String::new(),
+            badge_template: "{mode} {time}".to_string(),
+            remote_port: 0,
+            remote_bind: "127.0.0.1".to_string(),
+            remote_token: String::new(),
+            autosave: "transitions".to_string(),
+            autosave_seconds: 30,
+            resume_within_minutes: 30,
+            idle_pause_minutes: 0,
+            idle_return: "resume".to_string(),
+            task_quotas: BTreeMap::new(),
+            quota_action: "warn".to_string(),
+            on_sleep: "pause".to_string(),
+            sleep_gap_seconds: 60,
+            offline: false,
+            notifier: "auto".to_string(),
+            data_dir: String::new(),
+            encrypt_data: false,
+        }
+    }
+}
+
+impl Config {
+    // Parse and sanitize. Only malformed JSON is an error; missing fields take
+    // their defaults and out-of-range values are clamped.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        let mut config: Config = serde_json::from_str(json)?;
+        config.sanitize();
+        Ok(config)
+    }
+
+    pub fn from_toml(toml: &str) -> std::result::Result<Self, toml::de::Error> {
+        let mut config: Config = toml::from_str(toml)?;
+        config.sanitize();
+        Ok(config)
+    }
+
+    // A file's contents in the format its extension says: TOML for .toml,
+    // JSON otherwise
+    pub fn parse(path: &Path, content: &str) -> std::result::Result<Self, String> {
+        if is_toml(path) {
+            Config::from_toml(content).map_err(|e| e.message().to_string())
+        } else {
+            Config::from_json(content).map_err(|e| e.to_string())
+        }
+    }
+
+    // Load from `path`, writing the defaults if there's no file yet. A file
+    // that can't be parsed is set aside rather than overwritten on next save.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let default = Config::default();
+                default.save(path)?;
+                return Ok(default);
+            }
+            Err(e) => return Err(Error::io(path, e)),
+        };
+
+        match Config::parse(path, &content) {
+            Ok(config) => Ok(config),
+            Err(_) => {
+                set_aside(path)?;
+                Ok(Config::default())
+            }
+        }
+    }
+
+    // Whatever is usable at `path`, without writing anything
+    pub fn read(path: &Path) -> Self {
+        fs::read_to_string(path).ok().and_then(|content| Config::parse(path, &content).ok()).unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = if is_toml(path) {
+            toml::to_string_pretty(self).map_err(|e| Error::config(path, e))?
+        } else {
+            serde_json::to_string_pretty(self).map_err(|e| Error::config(path, e))?
+        };
+        write_atomic(path, &content)
+    }
+
+    // Keep values a hand-edited or damaged file may contain within what the
+    // timer and renderer can handle
+    pub fn sanitize(&mut self) {
+        const DAY_MINUTES: u32 = 24 * 60;
+        self.work_duration = self.work_duration.clamp(1, DAY_MINUTES);
+        self.short_break = self.short_break.clamp(1, DAY_MINUTES);
+        self.long_break = self.long_break.clamp(1, DAY_MINUTES);
+        self.cycles_before_long = self.cycles_before_long.clamp(1, 100);
+        for preset in self.presets.values_mut() {
+            preset.work_duration = preset.work_duration.clamp(1, DAY_MINUTES);
+            preset.short_break = preset.short_break.clamp(1, DAY_MINUTES);
+            preset.long_break = preset.long_break.clamp(1, DAY_MINUTES);
+            preset.cycles_before_long = preset.cycles_before_long.clamp(1, 100);
+        }
+        for profile in self.profiles.values_mut() {
+            profile.work_duration = profile.work_duration.clamp(1, DAY_MINUTES);
+            profile.short_break = profile.short_break.clamp(1, DAY_MINUTES);
+            profile.long_break = profile.long_break.clamp(1, DAY_MINUTES);
+            profile.cycles_before_long = profile.cycles_before_long.clamp(1, 100);
+        }
+        if !self.profiles.contains_key(&self.profile) {
+            self.profile.clear();
+        }
+        self.urgency_minutes = self.urgency_minutes.min(DAY_MINUTES);
+        self.warning_minutes = self.warning_minutes.min(DAY_MINUTES);
+        self.ending_soon_minutes = self.ending_soon_minutes.min(DAY_MINUTES);
+        self.breathing_pace = self.breathing_pace.min(20);
+        self.dim_paused_seconds = self.dim_paused_seconds.min(DAY_MINUTES * 60);
+        self.break_idle_minutes = self.break_idle_minutes.min(DAY_MINUTES);
+        self.daily_goal = self.daily_goal.min(100);
+
+        let defaults = Config::default();
+        if !DIGIT_STYLES.contains(&self.digit_style.as_str()) {
+            self.digit_style = defaults.digit_style;
+        }
+        if !CLOCK_STYLES.contains(&self.clock_style.as_str()) {
+            self.clock_style = defaults.clock_style;
+        }
+        if !CLOCK_FACES.contains(&self.clock_face.as_str()) {
+            self.clock_face = defaults.clock_face;
+        }
+        if !CLOCK_FORMATS.contains(&self.clock_format.as_str()) {
+            self.clock_format = defaults.clock_format;
+        }
+        if !TEXT_DIRECTIONS.contains(&self.text_direction.as_str()) {
+            self.text_direction = defaults.text_direction;
+        }
+        if !BREAK_SKIPS.contains(&self.break_skip.as_str()) {
+            self.break_skip = defaults.break_skip;
+        }
+        if !BREAK_ACTIVITIES.contains(&self.break_activity.as_str()) {
+            self.break_activity = defaults.break_activity;
+        }
+        if !AUTO_STARTS.contains(&self.auto_start.as_str()) {
+            self.auto_start = defaults.auto_start;
+        }
+        if !OVERTIMES.contains(&self.overtime.as_str()) {
+            self.overtime = defaults.overtime;
+        }
+        if !BACKGROUNDS.contains(&self.background.as_str()) {
+            self.background = defaults.background;
+        }
+        if !COLOR_MODES.contains(&self.color_mode.as_str()) {
+            self.color_mode = defaults.color_mode;
+        }
+        if !AMBIENT_CUES.contains(&self.ambient_cue.as_str()) {
+            self.ambient_cue = defaults.ambient_cue;
+        }
+        self.idle_pause_minutes = self.idle_pause_minutes.min(DAY_MINUTES);
+        if !IDLE_RETURNS.contains(&self.idle_return.as_str()) {
+            self.idle_return = defaults.idle_return;
+        }
+        if !QUOTA_ACTIONS.contains(&self.quota_action.as_str()) {
+            self.quota_action = defaults.quota_action;
+        }
+        if !SLEEP_ACTIONS.contains(&self.on_sleep.as_str()) {
+            self.on_sleep = defaults.on_sleep;
+        }
+        self.sleep_gap_seconds = self.sleep_gap_seconds.max(5);
+        if !AUTOSAVES.contains(&self.autosave.as_str()) {
+            self.autosave = defaults.autosave;
+        }
+        self.autosave_seconds = self.autosave_seconds.max(1);
+        self.keybindings.sanitize();
+        self.sequence.retain(|step| Step::parse(step).is_some());
+        self.escalation.retain(|step| ALERTS.contains(&step.alert.as_str()));
+        self.alert_routes.retain(|event, _| ALERT_EVENTS.contains(&event.as_str()));
+        for sinks in self.alert_routes.values_mut() {
+            sinks.retain(|sink| ALERT_SINKS.contains(&sink.as_str()));
+        }
+        self.escalation.sort_by_key(|step| step.after);
+        if !NOTIFIERS.contains(&self.notifier.as_str()) {
+            self.notifier = defaults.notifier;
+        }
+    }
+
+    // Built-in themes, then the config's own
+    pub fn theme_names(&self) -> Vec<&str> {
+        THEMES.iter().copied().chain(self.themes.keys().map(String::as_str)).collect()
+    }
+
+    // Session lengths and cycle count, as a preset would set them
+    pub fn lengths(&self) -> Preset {
+        Preset {
+            work_duration: self.work_duration,
+            short_break: self.short_break,
+            long_break: self.long_break,
+            cycles_before_long: self.cycles_before_long,
+        }
+    }
+
+    pub fn sequence(&self) -> Vec<Step> {
+        self.sequence.iter().filter_map(|step| Step::parse(step)).collect()
+    }
+
+    // The config's own presets, then the built-in ones it doesn't replace
+    pub fn preset_names(&self) -> Vec<&str> {
+        let builtin = PRESETS.iter().map(|(name, _)| *name).filter(|name| !self.presets.contains_key(*name));
+        self.presets.keys().map(String::as_str).chain(builtin).collect()
+    }
+
+    pub fn preset(&self, name: &str) -> Option<Preset> {
+        let builtin = || PRESETS.iter().find(|(builtin, _)| *builtin == name).map(|(_, preset)| preset.clone());
+        self.presets.get(name).cloned().or_else(builtin)
+    }
+
+    // The preset the current durations match, if any
+    pub fn active_preset(&self) -> Option<&str> {
+        let current = self.lengths();
+        self.preset_names().into_iter().find(|name| self.preset(name).as_ref() == Some(&current))
+    }
+
+    // Take on previous_preset's durations, remembering the preset left for
+    // the way back. False when there's no such preset.
+    pub fn switch_preset(&mut self) -> bool {
+        let name = self.previous_preset.clone();
+        self.apply_preset(&name)
+    }
+
+    // Take on `name`'s durations, remembering the preset left for p. False
+    // when there's no such preset.
+    pub fn apply_preset(&mut self, name: &str) -> bool {
+        let Some(preset) = self.preset(name) else {
+            return false;
+        };
+        if let Some(active) = self.active_preset().filter(|active| *active != name) {
+            self.previous_preset = active.to_string();
+        }
+        self.work_duration = preset.work_duration;
+        self.short_break = preset.short_break;
+        self.long_break = preset.long_break;
+        self.cycles_before_long = preset.cycles_before_long;
+        true
+    }
+
+    pub fn profile_names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    // Store the current durations and theme into the active profile, then
+    // take on `name`'s. False when there's no such profile.
+    pub fn switch_profile(&mut self, name: &str) -> bool {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            return false;
+        };
+        self.store_profile();
+        self.work_duration = profile.work_duration;
+        self.short_break = profile.short_break;
+        self.long_break = profile.long_break;
+        self.cycles_before_long = profile.cycles_before_long;
+        self.theme = profile.theme;
+        self.profile = name.to_string();
+        true
+    }
+
+    fn store_profile(&mut self) {
+        let theme = self.theme.clone();
+        let lengths = self.lengths();
+        if let Some(profile) = self.profiles.get_mut(&self.profile) {
+            *profile = Profile {
+                work_duration: lengths.work_duration,
+                short_break: lengths.short_break,
+                long_break: lengths.long_break,
+                cycles_before_long: lengths.cycles_before_long,
+                theme,
+            };
+        }
+    }
+
+    pub fn duration_for(&self, state: PomodoroState) -> Duration {
+        let minutes = match state {
+            PomodoroState::Work => self.work_duration,
+            PomodoroState::ShortBreak => self.short_break,
+            PomodoroState::LongBreak => self.long_break,
+        };
+        Duration::from_secs(minutes as u64 * 60)
+    }
+}
+
+// Settings given on the command line for this run only. They're applied
+// over the loaded config and kept out of config.json on save, unless
+// changed again in the config screen.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub work_duration: Option<u32>,
+    pub short_break: Option<u32>,
+    pub long_break: Option<u32>,
+    pub theme: Option<String>,
+    pub profile: Option<String>,
+    pub preset: Option<String>,
+}
+
+impl Overrides {
+    // The profile first, then the preset, so the other flags go over them
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(profile) = &self.profile {
+            config.switch_profile(profile);
+        }
+        if let Some(preset) = self.preset.as_ref().and_then(|name| config.preset(name)) {
+            config.work_duration = preset.work_duration;
+            config.short_break = preset.short_break;
+            config.long_break = preset.long_break;
+            config.cycles_before_long = preset.cycles_before_long;
+        }
+        if let Some(minutes) = self.work_duration {
+            config.work_duration = minutes;
+        }
+        if let Some(minutes) = self.short_break {
+            config.short_break = minutes;
+        }
+        if let Some(minutes) = self.long_break {
+            config.long_break = minutes;
+        }
+        if let Some(theme) = &self.theme {
+            config.theme = theme.clone();
+        }
+    }
+
+    // Put back `file`'s value for every setting still at its override
+    pub fn unapply(&self, config: &mut Config, file: &Config) {
+        // Changes made under the profile are kept in it
+        if self.profile.as_ref() == Some(&config.profile) {
+            config.store_profile();
+            config.work_duration = file.work_duration;
+            config.short_break = file.short_break;
+            config.long_break = file.long_break;
+            config.cycles_before_long = file.cycles_before_long;
+            config.theme = file.theme.clone();
+            config.profile = file.profile.clone();
+        }
+        if self.preset.as_ref().and_then(|name| config.preset(name)) == Some(config.lengths()) {
+            config.work_duration = file.work_duration;
+            config.short_break = file.short_break;
+            config.long_break = file.long_break;
+            config.cycles_before_long = file.cycles_before_long;
+        }
+        if self.work_duration == Some(config.work_duration) {
+            config.work_duration = file.work_duration;
+        }
+        if self.short_break == Some(config.short_break) {
+            config.short_break = file.short_break;
+        }
+        if self.long_break == Some(config.long_break) {
+            config.long_break = file.long_break;
+        }
+        if self.theme.as_ref() == Some(&config.theme) {
+            config.theme = file.theme.clone();
+        }
+    }
+}
+
+// Built-in presets: the classic pomodoro, 52/17 from DeskTime's study of its
+// most productive users, 45/15, 90-minute ultradian cycles and deep work
+pub const PRESETS: [(&str, Preset); 5] = [
+    ("classic", Preset { work_duration: 25, short_break: 5, long_break: 15, cycles_before_long: 4 }),
+    ("52-17", Preset { work_duration: 52, short_break: 17, long_break: 30, cycles_before_long: 3 }),
+    ("45-15", Preset { work_duration: 45, short_break: 15, long_break: 30, cycles_before_long: 3 }),
+    ("ultradian", Preset { work_duration: 90, short_break: 20, long_break: 30, cycles_before_long: 2 }),
+    ("deep-work", Preset { work_duration: 50, short_break: 10, long_break: 30, cycles_before_long: 2 }),
+];
+pub const THEMES: [&str; 6] = ["blue", "purple", "green", "red", "orange", "cyan"];
+pub const DIGIT_STYLES: [&str; 2] = ["block", "inverted"];
+pub const CLOCK_STYLES: [&str; 3] = ["classic", "large", "slim"];
+pub const CLOCK_FACES: [&str; 2] = ["digital", "analog"];
+pub const CLOCK_FORMATS: [&str; 3] = ["auto", "12h", "24h"];
+pub const TEXT_DIRECTIONS: [&str; 3] = ["auto", "ltr", "rtl"];
+pub const BREAK_SKIPS: [&str; 3] = ["free", "justify", "cooldown"];
+pub const AUTO_STARTS: [&str; 4] = ["off", "breaks", "work", "both"];
+pub const BREAK_ACTIVITIES: [&str; 3] = ["off", "scramble", "2048"];
+pub const OVERTIMES: [&str; 4] = ["off", "work", "breaks", "both"];
+pub const IDLE_RETURNS: [&str; 2] = ["resume", "ask"];
+pub const QUOTA_ACTIONS: [&str; 2] = ["warn", "refuse"];
+pub const SLEEP_ACTIONS: [&str; 3] = ["pause", "ask", "count"];
+pub const AUTOSAVES: [&str; 3] = ["interval", "transitions", "exit"];
+pub const BACKGROUNDS: [&str; 3] = ["auto", "dark", "light"];
+pub const COLOR_MODES: [&str; 4] = ["auto", "truecolor", "256", "16"];
+pub const ALERTS: [&str; 4] = ["flash", "bell", "notify", "sound"];
+pub const ALERT_EVENTS: [&str; 6] = ["work_end", "break_end", "goal_reached", "reminder", "warning", "countdown"];
+pub const ALERT_SINKS: [&str; 5] = ["sound", "notify", "webhook", "tts", "bell"];
+pub const AMBIENT_CUES: [&str; 4] = ["off", "urgent", "cursor", "profile"];
+pub const KEY_NAMES: [&str; 4] = ["space", "enter", "tab", "backspace"];
+// Backend names accepted by the `notifier` config setting
+pub const NOTIFIERS: [&str; 6] = ["auto", "notify-send", "osascript", "windows", "terminal", "none"];
+
+pub fn is_toml(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "toml")
+}
+
+// `rpomodoro migrate-config`: rewrite a config.json from before TOML as
+// config.toml beside it, keeping the old file as config.json.migrated.
+// Returns the new file.
+pub fn migrate_to_toml(json: &Path) -> Result<PathBuf> {
+    let toml = json.with_extension("toml");
+    if is_read_only() {
+        return Err(Error::Usage("nothing is written in read-only mode".to_string()));
+    }
+    if toml.exists() {
+        return Err(Error::config(&toml, "already exists; remove it or the JSON file"));
+    }
+    let content = fs::read_to_string(json).map_err(|e| Error::io(json, e))?;
+    let config = Config::from_json(&content).map_err(|e| Error::config(json, e))?;
+    config.save(&toml)?;
+    fs::rename(json, json.with_extension("json.migrated")).map_err(|e| Error::io(json, e))?;
+    Ok(toml)
+}
+
+// TOML keys are always strings, so long_break_reminders' minutes are written
+// as strings in both formats
+mod minute_keys {
+    use super::*;
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(reminders: &BTreeMap<u32, String>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(reminders.iter().map(|(minutes, text)| (minutes.to_string(), text)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<BTreeMap<u32, String>, D::Error> {
+        let reminders = BTreeMap::<String, String>::deserialize(deserializer)?;
+        reminders
+            .into_iter()
+            .map(|(minutes, text)| match minutes.trim().parse() {
+                Ok(minutes) => Ok((minutes, text)),
+                Err(_) => Err(D::Error::custom(format!("long_break_reminders: {:?} is not a number of minutes", minutes))),
+            })
+            .collect()
+    }
+}
+
+// Step to the next/previous entry of a fixed option list, wrapping around
+pub fn cycle_option(options: &[&str], current: &str, forward: bool) -> String {
+    let len = options.len();
+    let new_pos = match options.iter().position(|&o| o == current) {
+        Some(pos) if forward => (pos + 1) % len,
+        Some(pos) => (pos + len - 1) % len,
+        None => 0,
+    };
+    options[new_pos].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+    use proptest::prelude::*;
+
+    // Config-shaped JSON with arbitrary, often out-of-range, values
+    fn config_json() -> impl Strategy<Value = String> {
+        let value = prop_oneof![
+            any::<i64>().prop_map(|n| n.to_string()),
+            any::<u32>().prop_map(|n| n.to_string()),
+            "[a-z#0-9]{0,8}".prop_map(|s| format!("{:?}", s)),
+            Just("null".to_string()),
+            Just("true".to_string()),
+            Just("{}".to_string()),
+        ];
+        let key = prop::sample::select(vec![
+            "theme", "work_duration", "short_break", "long_break", "cycles_before_long",
+            "urgency_minutes", "digit_style", "clock_face", "daily_goal", "clock_format", "statusline",
+        ]);
+        prop::collection::vec((key, value), 0..12).prop_map(|fields| {
+            let body: Vec<String> = fields.iter().map(|(k, v)| format!("{:?}: {}", k, v)).collect();
+            format!("{{{}}}", body.join(", "))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn config_parsing_never_panics(input in any::<String>()) {
+            let _ = Config::from_json(&input);
+        }
+
+        #[test]
+        fn parsed_configs_are_usable(json in config_json()) {
+            if let Ok(config) = Config::from_json(&json) {
+                prop_assert!(config.cycles_before_long >= 1);
+                prop_assert!(!config.duration_for(PomodoroState::Work).is_zero());
+                prop_assert!(!config.duration_for(PomodoroState::ShortBreak).is_zero());
+                prop_assert!(!config.duration_for(PomodoroState::LongBreak).is_zero());
+                prop_assert!(DIGIT_STYLES.contains(&config.digit_style.as_str()));
+                prop_assert!(CLOCK_FACES.contains(&config.clock_face.as_str()));
+
+                let mut app = App::for_test(config);
+                app.toggle_pause();
+                for _ in 0..10 {
+                    app.tick(Duration::from_secs(3600)).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keybindings_are_lowercased_or_reset() {
+        let config = Config::from_json(r#"{"keybindings": {"stopwatch": "W", "lap": "not a key"}}"#).unwrap();
+        assert_eq!((config.keybindings.stopwatch.as_str(), config.keybindings.lap.as_str()), ("w", "m"));
+        // Every action, so one missed in sanitize shows up here
+        let defaults = Keybindings::default();
+        let bad: Vec<String> = defaults.actions().iter().map(|(action, _)| format!("{:?}: \"not a key\"", action)).collect();
+        let config = Config::from_json(&format!("{{\"keybindings\": {{{}}}}}", bad.join(", "))).unwrap();
+        assert_eq!(config.keybindings.actions(), defaults.actions());
+    }
+
+    #[test]
+    fn corrupt_config_is_set_aside() {
+        let dir = std::env::temp_dir().join(format!("rpomodoro-test-corrupt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.work_duration, Config::default().work_duration);
+        assert_eq!(fs::read_to_string(dir.join("config.json.bad")).unwrap(), "{ not json");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn json_config_migrates_to_toml() {
+        let dir = std::env::temp_dir().join(format!("rpomodoro-test-toml-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let json = dir.join("config.json");
+        fs::write(&json, r#"{"work_duration": 40, "long_break_reminders": {"10": "ten left"}}"#).unwrap();
+
+        let toml = migrate_to_toml(&json).unwrap();
+        assert!(!json.exists() && dir.join("config.json.migrated").exists());
+        assert!(fs::read_to_string(&toml).unwrap().contains("work_duration = 40"));
+        let config = Config::load(&toml).unwrap();
+        assert_eq!((config.work_duration, config.long_break_reminders[&10].as_str()), (40, "ten left"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn overrides_stay_out_of_the_file() {
+        let file = Config::default();
+        let overrides = Overrides { work_duration: Some(50), theme: Some("green".to_string()), ..Default::default() };
+        let mut config = file.clone();
+        overrides.apply(&mut config);
+        assert_eq!((config.work_duration, config.theme.as_str()), (50, "green"));
+
+        config.short_break = 9;
+        config.theme = "red".to_string();
+        overrides.unapply(&mut config, &file);
+        assert_eq!(config.work_duration, file.work_duration);
+        assert_eq!((config.short_break, config.theme.as_str()), (9, "red"));
+    }
+
+    #[test]
+    fn builtin_presets_are_there_unless_replaced() {
+        let mut config = Config::default();
+        assert!(config.apply_preset("52-17"));
+        assert_eq!((config.work_duration, config.short_break, config.active_preset()), (52, 17, Some("52-17")));
+        assert_eq!(config.previous_preset, "classic");
+
+        config.presets.insert("ultradian".to_string(), Preset { work_duration: 80, ..Preset::default() });
+        assert_eq!(config.preset("ultradian").map(|preset| preset.work_duration), Some(80));
+        assert_eq!(config.preset_names().iter().filter(|name| **name == "ultradian").count(), 1);
+
+        let overrides = Overrides { preset: Some("45-15".to_string()), work_duration: Some(40), ..Default::default() };
+        overrides.apply(&mut config);
+        assert_eq!((config.work_duration, config.short_break, config.long_break), (40, 15, 30));
+    }
+
+    #[test]
+    fn presets_switch_back_and_forth() {
+        let mut config = Config::default();
+        assert_eq!(config.active_preset(), Some("classic"));
+        assert!(config.switch_preset());
+        assert_eq!((config.active_preset(), config.work_duration), (Some("deep-work"), 50));
+        assert!(config.switch_preset());
+        assert_eq!((config.active_preset(), config.work_duration), (Some("classic"), 25));
+
+        config.short_break = 7;
+        assert_eq!(config.active_preset(), None);
+        config.previous_preset = "gone".to_string();
+        assert!(!config.switch_preset());
+    }
+
+    #[test]
+    fn profiles_keep_their_own_settings() {
+        let file = Config::default();
+        let overrides = Overrides { profile: Some("deep-work".to_string()), ..Default::default() };
+        let mut config = file.clone();
+        overrides.apply(&mut config);
+        assert_eq!((config.work_duration, config.theme.as_str()), (50, "purple"));
+
+        config.short_break = 12;
+        assert!(config.switch_profile("study"));
+        assert_eq!((config.work_duration, config.theme.as_str()), (25, "blue"));
+        assert!(config.switch_profile("deep-work"));
+        assert_eq!(config.short_break, 12);
+        assert!(!config.switch_profile("gone"));
+
+        overrides.unapply(&mut config, &file);
+        assert_eq!((config.profile.as_str(), config.work_duration, config.theme.as_str()), ("", 25, "blue"));
+        assert_eq!(config.profiles["deep-work"].short_break, 12);
+    }
+
+    #[test]
+    fn custom_themes_cycle_after_built_in_ones() {
+        let json = r##"{"theme": "mytheme", "themes": {"mytheme": {"primary": "#ff8800", "dim": "#ffd0a0"}}}"##;
+        let config = Config::from_json(json).unwrap();
+        assert_eq!(config.theme_names().last(), Some(&"mytheme"));
+        assert_eq!(cycle_option(&config.theme_names(), "mytheme", true), "blue");
+        assert_eq!(cycle_option(&config.theme_names(), "cyan", true), "mytheme");
+    }
+}
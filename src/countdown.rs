@@ -0,0 +1,153 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app::App;
+use crate::config::Preset;
+use crate::duration;
+use crate::engine::PomodoroEngine;
+use crate::error::{Error, Result};
+use crate::history;
+
+// A single countdown of any length, from `rpomodoro timer 17m` or typed
+// after the "timer" key, on the same big clock. It runs in place of the
+// pomodoro timer, which is set aside paused and comes back as it was when
+// the countdown runs out or is skipped. Nothing is counted in the stats or
+// history.
+pub struct Countdown {
+    pub length: Duration,
+    pub pomodoro: PomodoroEngine,
+    pub session_started: Option<String>,
+}
+
+// "17m", "1h30m", "25:30" or minutes alone, up to a day
+pub fn parse_length(text: &str) -> std::result::Result<Duration, String> {
+    let length = duration::parse(text)?;
+    if length.is_zero() || length > Duration::from_secs(24 * 60 * 60) {
+        return Err(format!("{:?} isn't between 1s and 24h", text.trim()));
+    }
+    Ok(length)
+}
+
+impl App {
+    pub fn open_countdown_prompt(&mut self) {
+        if self.attached {
+            self.show_error(Error::Usage("a countdown can't be started on an attached timer".to_string()));
+        } else {
+            self.countdown_input = Some(String::new());
+        }
+    }
+
+    // Keys while the countdown's length is typed. One that can't be read
+    // stays open.
+    pub fn handle_countdown_input(&mut self, key: KeyEvent) {
+        let Some(input) = &mut self.countdown_input else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.countdown_input = None,
+            KeyCode::Enter => {
+                if let Ok(length) = parse_length(input) {
+                    self.countdown_input = None;
+                    if let Err(e) = self.start_countdown(length) {
+                        self.show_error(e);
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    // Run `length` from now, setting the pomodoro timer aside paused. A
+    // countdown already running is replaced.
+    pub fn start_countdown(&mut self, length: Duration) -> Result<()> {
+        if self.attached {
+            return Err(Error::Usage("a countdown can't be started on an attached timer".to_string()));
+        }
+        let minutes = length.as_secs().div_ceil(60) as u32;
+        let lengths = Preset { work_duration: minutes, short_break: minutes, long_break: minutes, cycles_before_long: 1 };
+        let mut engine = PomodoroEngine::new(lengths);
+        engine.time_remaining = length;
+        engine.paused = false;
+        let mut pomodoro = std::mem::replace(&mut self.engine, engine);
+        match &mut self.countdown {
+            Some(countdown) => countdown.length = length,
+            None => {
+                pomodoro.paused = true;
+                let session_started = self.session_started.take();
+                self.countdown = Some(Countdown { length, pomodoro, session_started });
+            }
+        }
+        (self.last_tick, self.last_wall_tick) = (Instant::now(), SystemTime::now());
+        self.session_started = Some(history::now());
+        self.session_dirty = true;
+        Ok(())
+    }
+
+    // Back to the pomodoro timer, with the countdown's alert if it ran out
+    pub fn finish_countdown(&mut self, ran_out: bool) -> Result<()> {
+        let Some(countdown) = self.countdown.take() else {
+            return Ok(());
+        };
+        self.engine = countdown.pomodoro;
+        self.session_started = countdown.session_started;
+        self.session_dirty = true;
+        if !ran_out {
+            return Ok(());
+        }
+        let defaults = [("notify", self.config.notifications.enabled), ("sound", self.config.sound.enabled)];
+        let defaults: Vec<&str> = defaults.into_iter().filter(|&(_, on)| on).map(|(sink, _)| sink).collect();
+        let length = self.format_length(countdown.length);
+        self.alert("countdown", "Timer done", &format!("{} timer is up", length), &defaults)
+    }
+
+    // The prompt's hint: the length as it would run, or what's wrong
+    pub fn countdown_hint(&self) -> Option<(String, bool)> {
+        let input = self.countdown_input.as_ref()?;
+        Some(match parse_length(input) {
+            Ok(length) => (self.format_length(length), true),
+            Err(_) if input.is_empty() => ("e.g. 17m, 1h30m or 25:30".to_string(), true),
+            Err(e) => (e, false),
+        })
+    }
+
+    // In the locale's words for whole minutes, otherwise as it'd be typed
+    fn format_length(&self, length: Duration) -> String {
+        match length.as_secs() % 60 {
+            0 => self.locale.format_duration(length),
+            _ => duration::format(length),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::PomodoroState;
+    use crate::config::Config;
+
+    #[test]
+    fn lengths_are_up_to_a_day() {
+        let lengths = ["17m", "25:30", "24h", "0m", "25h", "h"].map(|text| parse_length(text).ok().map(|length| length.as_secs()));
+        assert_eq!(lengths, [Some(17 * 60), Some(25 * 60 + 30), Some(24 * 60 * 60), None, None, None]);
+    }
+
+    #[test]
+    fn a_countdown_sets_the_pomodoro_timer_aside() {
+        let mut app = App::for_test(Config::default());
+        app.toggle_pause();
+        app.tick(Duration::from_secs(60)).unwrap();
+        app.start_countdown(Duration::from_secs(3 * 60)).unwrap();
+        assert!(!app.engine.paused);
+        assert_eq!(app.clock_text(), "03:00");
+
+        app.tick(Duration::from_secs(3 * 60 + 1)).unwrap();
+        assert!(app.countdown.is_none());
+        assert_eq!((app.engine.state, app.engine.paused), (PomodoroState::Work, true));
+        assert_eq!(app.engine.time_remaining, Duration::from_secs(24 * 60));
+        assert_eq!(app.stats.completed_today(), 0);
+    }
+}
@@ -0,0 +1,143 @@
+use crossterm::{cursor, execute, terminal};
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::integrations::focus::FOCUS_PRESETS;
+use crate::theme::color_support;
+use crate::version::compiled_features;
+
+// Lines of the log kept for a crash report
+const LOG_LINES: usize = 50;
+
+// Errors shown while running, the newest last
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+pub fn log(line: &str) {
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    if log.len() == LOG_LINES {
+        log.pop_front();
+    }
+    log.push_back(format!("{} {}", chrono::Local::now().format("%H:%M:%S"), line));
+}
+
+// On a panic in any thread: restore the terminal, write a crash report to
+// `state_dir` for bug reports and say where it is, then exit. The config is
+// included as loaded, with secrets redacted.
+pub fn install(state_dir: PathBuf, config: &Config) {
+    let config = redacted(config);
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = terminal::disable_raw_mode();
+        let _ = execute!(io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+
+        let report = report(&info.to_string(), &Backtrace::force_capture().to_string(), &config);
+        eprintln!("rpomodoro crashed: {}", info);
+        match write_report(&state_dir, &report) {
+            Ok(path) => eprintln!("a crash report was written to {}; please attach it to a bug report", path.display()),
+            Err(e) => eprintln!("writing a crash report to {} failed: {}\n\n{}", state_dir.display(), e, report),
+        }
+        std::process::exit(101);
+    }));
+}
+
+// Tokens, URLs that work as one (webhooks, a private ntfy topic), the
+// weekly summary's login and addresses, and the hook and focus commands,
+// which can carry tokens of their own. A focus preset's name is kept.
+fn redacted(config: &Config) -> String {
+    let mut config = config.clone();
+    let focus_preset = Some(config.focus_command.clone()).filter(|command| FOCUS_PRESETS.contains(&command.as_str()));
+    let summary = &mut config.weekly_summary;
+    let secrets = [
+        &mut config.remote_token,
+        &mut config.on_work_start,
+        &mut config.on_break_start,
+        &mut config.on_session_end,
+        &mut config.focus_command,
+        &mut config.webhook_url,
+        &mut config.event_webhook_url,
+        &mut summary.ntfy_url,
+        &mut summary.smtp_url,
+        &mut summary.smtp_user,
+        &mut summary.smtp_password,
+        &mut summary.mail_from,
+        &mut summary.mail_to,
+    ];
+    for secret in secrets {
+        if !secret.is_empty() {
+            *secret = "<redacted>".to_string();
+        }
+    }
+    config.focus_command = focus_preset.unwrap_or(config.focus_command);
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+fn report(panic: &str, backtrace: &str, config: &str) -> String {
+    let env = |var: &str| std::env::var(var).unwrap_or_else(|_| "-".to_string());
+    let size = terminal::size().map(|(w, h)| format!("{}x{}", w, h)).unwrap_or_else(|_| "unknown".to_string());
+
+    let mut report = String::new();
+    let _ = writeln!(report, "rpomodoro {} crash report", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(report, "time: {}", chrono::Local::now().to_rfc3339());
+    let _ = writeln!(report, "os: {} ({})", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(report, "features: {}", compiled_features().join(", "));
+    let _ = writeln!(report, "terminal: {} TERM={} COLORTERM={} TERM_PROGRAM={} color support: {}", size, env("TERM"), env("COLORTERM"), env("TERM_PROGRAM"), color_support());
+    let _ = writeln!(report, "\n{}\n\nbacktrace:\n{}", panic, backtrace);
+    let _ = writeln!(report, "recent errors:");
+    for line in LOG.lock().map(|log| log.clone()).unwrap_or_default() {
+        let _ = writeln!(report, "{}", line);
+    }
+    let _ = writeln!(report, "\nconfig:\n{}", config);
+    report
+}
+
+// Written even in read-only mode: the user will want it
+fn write_report(state_dir: &Path, report: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(state_dir)?;
+    let path = state_dir.join(format!("crash-{}.txt", chrono::Local::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WeeklySummaryConfig;
+
+    #[test]
+    fn reports_redact_secrets_and_keep_the_log() {
+        let mut config = Config {
+            remote_token: "hunter2".to_string(),
+            webhook_url: "https://hooks.example.com/T0/B0/secret1".to_string(),
+            on_work_start: "curl -H 'Authorization: Bearer secret8' https://slack.com/api/dnd.setSnooze".to_string(),
+            on_session_end: "notify secret9".to_string(),
+            focus_command: "wmctrl -a rpomodoro --token secret10".to_string(),
+            ..Config::default()
+        };
+        config.weekly_summary = WeeklySummaryConfig {
+            ntfy_url: "https://ntfy.sh/private-topic-secret2".to_string(),
+            smtp_url: "smtps://secret3.example.com:465".to_string(),
+            smtp_user: "secret4-user".to_string(),
+            smtp_password: "secret5-password".to_string(),
+            mail_from: "secret6@example.com".to_string(),
+            mail_to: "secret7@example.com".to_string(),
+        };
+        log("notifier: notify-send not found");
+        let report = report("panicked at src/app.rs:1:1:\noops", "0: main", &redacted(&config));
+        for secret in ["hunter2", "secret1", "secret2", "secret3", "secret4", "secret5", "secret6", "secret7", "secret8", "secret9", "secret10"] {
+            assert!(!report.contains(secret), "{} in the report", secret);
+        }
+        assert!(report.contains("\"remote_token\": \"<redacted>\""));
+        assert!(report.contains("\"event_webhook_url\": \"\""), "empty ones stay empty");
+        config.focus_command = "hyprctl".to_string();
+        assert!(redacted(&config).contains("\"focus_command\": \"hyprctl\""), "presets are kept");
+        assert!(report.contains("notifier: notify-send not found"));
+        assert!(report.contains("oops"));
+    }
+}
@@ -0,0 +1,238 @@
+#[cfg(feature = "encryption")]
+use argon2::Argon2;
+#[cfg(feature = "encryption")]
+use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+#[cfg(feature = "encryption")]
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+#[cfg(feature = "encryption")]
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+#[cfg(feature = "encryption")]
+use crossterm::terminal;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File};
+#[cfg(feature = "encryption")]
+use std::io::Write;
+use std::io::{self, Read};
+use std::path::Path;
+#[cfg(feature = "encryption")]
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+use crate::storage::{set_aside, write_atomic};
+
+// Encrypted files start with this line, so they're recognised on load
+// whatever the config says
+const MAGIC: &[u8] = b"rpomodoro-encrypted-v1\n";
+#[cfg(feature = "encryption")]
+const SALT_LEN: usize = 16;
+#[cfg(feature = "encryption")]
+const NONCE_LEN: usize = 12;
+
+// A passphrase for encrypting data files. Keys are derived from it with a
+// random salt, stored in each header. Argon2 is slow on purpose, so the keys
+// derived are kept, and history lines share their file's salt.
+#[cfg(feature = "encryption")]
+pub struct Passphrase {
+    passphrase: String,
+    keys: Mutex<Vec<([u8; SALT_LEN], Key)>>,
+}
+
+// Without the "encryption" feature there's no way to get a passphrase, so
+// encrypted files and "encrypt_data" are reported instead of misread
+#[cfg(not(feature = "encryption"))]
+pub enum Passphrase {}
+
+#[cfg(not(feature = "encryption"))]
+impl Passphrase {
+    pub fn obtain(purpose: &Path) -> Result<Self> {
+        Err(Error::config(purpose, "encryption needs a build with the \"encryption\" feature"))
+    }
+
+    pub fn encrypt(&self, _plaintext: &[u8]) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn encrypt_like(&self, _plaintext: &[u8], _previous: Option<&[u8]>) -> Vec<u8> {
+        match *self {}
+    }
+
+    pub fn decrypt(&self, _data: &[u8]) -> Option<Vec<u8>> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl Passphrase {
+    // From RPOMODORO_PASSPHRASE, or asked for on the terminal
+    pub fn obtain(purpose: &Path) -> Result<Self> {
+        let passphrase = match std::env::var("RPOMODORO_PASSPHRASE") {
+            Ok(passphrase) => passphrase,
+            Err(_) => prompt(&format!("passphrase for {}: ", purpose.display()))?,
+        };
+        if passphrase.is_empty() {
+            return Err(Error::config(purpose, "an empty passphrase can't be used"));
+        }
+        Ok(Passphrase::new(passphrase))
+    }
+
+    fn new(passphrase: String) -> Self {
+        Passphrase { passphrase, keys: Mutex::new(Vec::new()) }
+    }
+
+    #[cfg(test)]
+    pub fn for_test(passphrase: &str) -> Self {
+        Passphrase::new(passphrase.to_string())
+    }
+
+    fn key(&self, salt: [u8; SALT_LEN]) -> Key {
+        let mut keys = self.keys.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, key)) = keys.iter().find(|(known, _)| *known == salt) {
+            return *key;
+        }
+        let mut key = Key::default();
+        Argon2::default()
+            .hash_password_into(self.passphrase.as_bytes(), &salt, &mut key)
+            .expect("salt and key lengths are fixed and valid");
+        keys.push((salt, key));
+        key
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.encrypt_like(plaintext, None)
+    }
+
+    // Under the same salt as `previous`, something encrypted before, so its
+    // key is reused and only the nonce is new; a fresh salt without one
+    pub fn encrypt_like(&self, plaintext: &[u8], previous: Option<&[u8]>) -> Vec<u8> {
+        let previous_salt = previous.and_then(|previous| previous.strip_prefix(MAGIC)?.get(..SALT_LEN)?.try_into().ok());
+        let salt = previous_salt.unwrap_or_else(|| {
+            let mut salt = [0u8; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            salt
+        });
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = ChaCha20Poly1305::new(&self.key(salt))
+            .encrypt(&nonce, plaintext)
+            .expect("encrypting into a Vec can't fail");
+
+        [MAGIC, &salt, &nonce, &ciphertext].concat()
+    }
+
+    // None for a wrong passphrase or a damaged file
+    pub fn decrypt(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let rest = data.strip_prefix(MAGIC)?;
+        if rest.len() < SALT_LEN + NONCE_LEN {
+            return None;
+        }
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        ChaCha20Poly1305::new(&self.key(salt.try_into().ok()?)).decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+    }
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+// A JSON data file, like the stats or the task list. Missing is the default;
+// one that can't be read is set aside so the next save doesn't destroy what
+// may still be recoverable by hand. Encrypted files need `passphrase`, and a
+// wrong one is an error rather than a reason to set the file aside.
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path, passphrase: Option<&Passphrase>) -> Result<T> {
+    let mut content = match fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    if is_encrypted(&content) {
+        let passphrase = passphrase.ok_or_else(|| Error::config(path, "encrypted, but no passphrase was given"))?;
+        content = passphrase
+            .decrypt(&content)
+            .ok_or_else(|| Error::config(path, "can't decrypt (wrong passphrase?)"))?;
+    }
+    match serde_json::from_slice(&content) {
+        Ok(value) => Ok(value),
+        Err(_) => {
+            set_aside(path)?;
+            Ok(T::default())
+        }
+    }
+}
+
+// Encrypted when a passphrase is given, plain JSON otherwise
+pub fn save_json<T: Serialize>(path: &Path, value: &T, passphrase: Option<&Passphrase>) -> Result<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| Error::config(path, e))?;
+    match passphrase {
+        Some(passphrase) => write_atomic(path, passphrase.encrypt(json.as_bytes())),
+        None => write_atomic(path, json),
+    }
+}
+
+// Peek at a file's header without reading all of it
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; MAGIC.len()];
+    File::open(path).and_then(|mut file| file.read_exact(&mut header)).is_ok() && is_encrypted(&header)
+}
+
+// Read a line without echoing it. Runs before the alternate screen is
+// entered, so it shows up as a normal prompt.
+#[cfg(feature = "encryption")]
+fn prompt(message: &str) -> Result<String> {
+    let mut stderr = io::stderr();
+    write!(stderr, "{}", message)?;
+    stderr.flush()?;
+
+    terminal::enable_raw_mode()?;
+    let mut line = String::new();
+    let result = loop {
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        match key.code {
+            KeyCode::Enter => break Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                break Err(io::Error::new(io::ErrorKind::Interrupted, "passphrase entry cancelled"))
+            }
+            KeyCode::Char(c) => line.push(c),
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            _ => {}
+        }
+    };
+    terminal::disable_raw_mode()?;
+    writeln!(stderr)?;
+    result?;
+    Ok(line)
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_wrong_passphrase() {
+        let passphrase = Passphrase::for_test("correct horse");
+        let encrypted = passphrase.encrypt(b"{\"completed\":{}}");
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(passphrase.decrypt(&encrypted).unwrap(), b"{\"completed\":{}}");
+        assert!(Passphrase::for_test("wrong").decrypt(&encrypted).is_none());
+        assert!(passphrase.decrypt(&encrypted[..encrypted.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn encrypting_like_another_shares_its_salt_and_key() {
+        let passphrase = Passphrase::for_test("correct horse");
+        let first = passphrase.encrypt(b"one");
+        let second = passphrase.encrypt_like(b"two", Some(&first));
+        let header = MAGIC.len() + SALT_LEN;
+        assert_eq!(first[..header], second[..header]);
+        assert_ne!(first[header..header + NONCE_LEN], second[header..header + NONCE_LEN]);
+        assert_eq!(passphrase.decrypt(&second).unwrap(), b"two");
+        assert_eq!(passphrase.keys.lock().unwrap().len(), 1);
+        assert_ne!(passphrase.encrypt(b"three")[..header], first[..header]);
+    }
+}
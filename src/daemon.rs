@@ -0,0 +1,71 @@
+#[cfg(unix)]
+use std::sync::{atomic::AtomicBool, Arc};
+
+use crate::app::{App, Attachment};
+use crate::config::Overrides;
+use crate::error::{Error, Result};
+use crate::events::Events;
+use crate::integrations;
+use crate::ipc;
+use crate::storage::Dirs;
+
+// `--daemon`: the timer without a terminal, driven through the control
+// channel. TUIs started while it runs attach to it, so closing them leaves
+// the timer running. Errors that the TUI would show as toasts go to stderr.
+pub fn run_daemon(dirs: &Dirs, overrides: Overrides) -> Result<()> {
+    let mut app = App::new(dirs, overrides)?;
+    // Offline would leave nothing to control it with
+    app.offline = false;
+
+    // Outlive the terminal that started it
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::new(AtomicBool::new(false)))
+        .map_err(|e| Error::integration("signals", e))?;
+
+    let events = Events::new();
+    let mut integrations = integrations::registry(dirs, &app);
+    for integration in integrations.iter_mut() {
+        let name = integration.name();
+        match app.health.track(name, integration.start(events.sender())) {
+            Err(e) if name == "control channel" => return Err(e),
+            Err(e) => eprintln!("rpomodoro: {}", e),
+            Ok(()) => {}
+        }
+    }
+
+    loop {
+        if let Err(e) = app.update().and_then(|_| app.export_badge()).and_then(|_| app.autosave(false)) {
+            app.show_error(e);
+        }
+        app.live.publish(app.snapshot());
+        app.ring_bell = false;
+        app.terminal_output.clear();
+        if let Some((message, _)) = app.toast.take() {
+            eprintln!("rpomodoro: {}", message);
+        }
+
+        for event in events.wait(app.redraw_timeout()) {
+            if !app.handle_app_event(event)? {
+                return app.autosave(true);
+            }
+        }
+    }
+}
+
+// Follow a timer already running in another process, if one answers.
+// Queued commands are sent to it and its state read back once a frame.
+pub fn attach(dirs: &Dirs) -> Option<Attachment> {
+    let transport = ipc::transport(dirs);
+    transport.request("status").ok().filter(|reply| reply.starts_with('{'))?;
+    let lost = |e| Error::integration("control channel", format!("lost the running timer ({})", e));
+    Some(Box::new(move |app: &mut App| {
+        for command in std::mem::take(&mut app.outbox) {
+            let reply = transport.request(command.name()).map_err(lost)?;
+            if let Some(reason) = reply.strip_prefix("error: ") {
+                return Err(Error::integration("control channel", reason));
+            }
+        }
+        let status = transport.request("status").map_err(lost)?;
+        app.mirror(&status)
+    }))
+}
@@ -0,0 +1,43 @@
+use crossterm::{
+    event::{self, Event, KeyCode, KeyModifiers},
+    terminal,
+};
+use std::io::{self, Write};
+use std::time::Instant;
+
+use crate::error::Result;
+use crate::theme::color_support;
+
+// Hidden `--debug-keys` mode: dumps what the terminal reports so users can
+// attach it to input/rendering bug reports. Stays on the main screen so the
+// log can be copied after exiting.
+pub fn run_debug_keys() -> Result<()> {
+    let (width, height) = terminal::size()?;
+    let env = |var: &str| std::env::var(var).unwrap_or_else(|_| "-".to_string());
+
+    println!("rpomodoro {} debug-keys", env!("CARGO_PKG_VERSION"));
+    println!("os: {} ({})", std::env::consts::OS, std::env::consts::ARCH);
+    println!("terminal size: {}x{}", width, height);
+    println!("TERM={} COLORTERM={} TERM_PROGRAM={}", env("TERM"), env("COLORTERM"), env("TERM_PROGRAM"));
+    println!("color support: {}", color_support());
+    println!("features: debug_assertions={}", cfg!(debug_assertions));
+    println!("press keys to see their events, ctrl-c to exit");
+
+    terminal::enable_raw_mode()?;
+    let start = Instant::now();
+    let result = (|| -> Result<()> {
+        loop {
+            let event = event::read()?;
+            print!("{:>8.3}s  {:?}\r\n", start.elapsed().as_secs_f32(), event);
+            io::stdout().flush()?;
+
+            if let Event::Key(key) = event {
+                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                    return Ok(());
+                }
+            }
+        }
+    })();
+    terminal::disable_raw_mode()?;
+    result
+}
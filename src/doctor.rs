@@ -0,0 +1,132 @@
+use crossterm::terminal;
+use std::fs;
+use std::io::{self, IsTerminal};
+
+use crate::config::{Config, THEMES};
+use crate::crypto::is_encrypted_file;
+use crate::integrations::focus::FOCUS_PRESETS;
+use crate::integrations::notify::{notifier_for, on_path};
+use crate::render::{MIN_HEIGHT, MIN_WIDTH};
+use crate::stats::Stats;
+use crate::storage::Dirs;
+use crate::theme::{color_support, parse_hex_color};
+
+// `rpomodoro doctor`: checks the environment and prints what to fix. Returns
+// whether everything needed to run is in place.
+pub fn run_doctor(dirs: &Dirs) -> bool {
+    let mut dirs = dirs.clone();
+    let mut healthy = true;
+    let mut report = |level: &str, what: String, hint: Option<&str>| {
+        println!("[{}] {}", level, what);
+        if let Some(hint) = hint {
+            println!("       {}", hint);
+        }
+        if level == "fail" {
+            healthy = false;
+        }
+    };
+
+    // Terminal
+    if io::stdout().is_terminal() {
+        match terminal::size() {
+            Ok((w, h)) if w >= MIN_WIDTH && h >= MIN_HEIGHT => report("ok", format!("terminal size {}x{}", w, h), None),
+            Ok((w, h)) => report("warn", format!("terminal size {}x{} is small", w, h), Some(&format!("the clock needs at least {}x{}", MIN_WIDTH, MIN_HEIGHT))),
+            Err(e) => report("fail", format!("cannot query terminal size: {}", e), None),
+        }
+    } else {
+        report("warn", "stdout is not a terminal".to_string(), Some("run rpomodoro from an interactive terminal"));
+    }
+
+    match color_support() {
+        "truecolor" => report("ok", "truecolor supported".to_string(), None),
+        other => report(
+            "warn",
+            format!("color support looks like {} (COLORTERM is not truecolor)", other),
+            Some("theme colors are mapped to the nearest ones available; set COLORTERM=truecolor or \"color_mode\" if your terminal has more"),
+        ),
+    }
+
+    // Config
+    let config_path = dirs.config_file();
+    match fs::read_to_string(&config_path) {
+        Ok(content) => match Config::parse(&config_path, &content) {
+            Ok(config) => {
+                report("ok", format!("config {} is valid", config_path.display()), None);
+                if !config.theme_names().contains(&config.theme.as_str()) {
+                    report("warn", format!("unknown theme \"{}\"", config.theme), Some("falls back to blue"));
+                }
+                for (name, custom) in &config.themes {
+                    if THEMES.contains(&name.as_str()) {
+                        report("warn", format!("custom theme \"{}\" has a built-in theme's name", name), Some("it replaces the built-in one"));
+                    }
+                    for hex in [&custom.primary, &custom.dim].into_iter().filter(|hex| parse_hex_color(hex).is_none()) {
+                        report("warn", format!("theme \"{}\" has an invalid color \"{}\"", name, hex), Some("use \"#rrggbb\"; blue's color is used instead"));
+                    }
+                }
+                if config.work_duration == 0 || config.short_break == 0 || config.long_break == 0 || config.cycles_before_long == 0 {
+                    report("warn", "a duration or cycle count is 0".to_string(), Some("sessions will end immediately; use values of at least 1"));
+                }
+            }
+            Err(e) => report(
+                "fail",
+                format!("config {} is invalid: {}", config_path.display(), e),
+                Some("fix the file, or rpomodoro will set it aside with a .bad extension and start from defaults"),
+            ),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            report("ok", format!("no config yet, defaults will be written to {}", config_path.display()), None)
+        }
+        Err(e) => report("fail", format!("cannot read {}: {}", config_path.display(), e), None),
+    }
+
+    if dirs.has_legacy_config() {
+        report("ok", "config is in JSON".to_string(), Some("`rpomodoro migrate-config` moves it to config.toml; both keep working"));
+    }
+
+    let config = fs::read_to_string(&config_path)
+        .ok()
+        .and_then(|content| Config::parse(&config_path, &content).ok())
+        .unwrap_or_default();
+    dirs.apply_config(&config.data_dir);
+
+    // Notifications
+    match notifier_for(&config.notifier).name() {
+        "terminal" if config.notifier == "auto" => report(
+            "warn",
+            "no desktop notifier found, using terminal escapes".to_string(),
+            Some("install notify-send (libnotify) for desktop notifications, or set \"notifier\" in the config"),
+        ),
+        name => report("ok", format!("notifications via {}", name), None),
+    }
+
+    // Bringing the terminal back after breaks
+    if FOCUS_PRESETS.contains(&config.focus_command.as_str()) && !on_path(&config.focus_command) {
+        report(
+            "warn",
+            format!("focus_command \"{}\" is not installed", config.focus_command),
+            Some("install it, or set \"focus_command\" to a command that works for your window manager"),
+        );
+    }
+
+    // Directories we write to
+    for (name, dir) in [("config", &dirs.config), ("data", &dirs.data), ("state", &dirs.state)] {
+        let probe = dir.join(".doctor-probe");
+        let writable = fs::create_dir_all(dir).and_then(|_| fs::write(&probe, b"")).and_then(|_| fs::remove_file(&probe));
+        match writable {
+            Ok(()) => report("ok", format!("{} dir {} is writable", name, dir.display()), None),
+            Err(e) => report("fail", format!("{} dir {} is not writable: {}", name, dir.display(), e), Some("check the directory's permissions")),
+        }
+    }
+
+    let stats_path = dirs.data.join("stats.json");
+    if is_encrypted_file(&stats_path) {
+        report("ok", format!("stats {} are encrypted", stats_path.display()), None);
+    } else if let Ok(content) = fs::read_to_string(&stats_path) {
+        match serde_json::from_str::<Stats>(&content) {
+            Ok(_) => report("ok", format!("stats {} are readable", stats_path.display()), None),
+            Err(e) => report("warn", format!("stats {} are unreadable: {}", stats_path.display(), e), Some("the tomato row will start from zero")),
+        }
+    }
+
+    healthy
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Deserializer};
+use std::time::Duration;
+
+// Lengths as people type them, wherever one is given: command-line flags,
+// config values, `:set` and the countdown prompt. "25m", "1h30m", "2h",
+// "90s" and combinations of them, "25:30" (minutes and seconds) or
+// "1:25:30", and a bare number, which is minutes.
+pub fn parse(text: &str) -> Result<Duration, String> {
+    let trimmed: String = text.split_whitespace().collect::<String>().to_lowercase();
+    let bad = || format!("{:?} isn't a length; use e.g. 25m, 1h30m, 90 or 25:30", text.trim());
+    let too_long = || format!("{:?} is too long", text.trim());
+    if trimmed.is_empty() {
+        return Err(bad());
+    }
+    if trimmed.contains(':') {
+        let parts: Vec<&str> = trimmed.split(':').collect();
+        let numbers: Option<Vec<u64>> = parts.iter().map(|part| part.parse().ok()).collect();
+        return match numbers.as_deref() {
+            Some(&[minutes, seconds]) if seconds < 60 => minutes.checked_mul(60).and_then(|secs| secs.checked_add(seconds)).map(Duration::from_secs).ok_or_else(too_long),
+            Some(&[hours, minutes, seconds]) if minutes < 60 && seconds < 60 => {
+                hours.checked_mul(3600).and_then(|secs| secs.checked_add(minutes * 60 + seconds)).map(Duration::from_secs).ok_or_else(too_long)
+            }
+            Some(_) if parts.len() <= 3 => Err(format!("{:?} has more than 59 minutes or seconds in a place", text.trim())),
+            _ => Err(bad()),
+        };
+    }
+    if let Ok(minutes) = trimmed.parse::<u64>() {
+        return minutes.checked_mul(60).map(Duration::from_secs).ok_or_else(too_long);
+    }
+    // Number and unit pairs, largest unit first. A number left at the end
+    // counts in the unit after the last one, so "1h30" is 1h30m.
+    let mut secs = 0;
+    let mut rest = trimmed.as_str();
+    let mut last_unit = u64::MAX;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let number: u64 = rest[..digits].parse().map_err(|_| bad())?;
+        rest = &rest[digits..];
+        let letters = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = match &rest[..letters] {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "" if last_unit == 3600 => 60,
+            "" if last_unit == 60 => 1,
+            _ => return Err(bad()),
+        };
+        if unit >= last_unit {
+            return Err(format!("{:?} repeats a unit or has them out of order", text.trim()));
+        }
+        rest = &rest[letters..];
+        secs = number.checked_mul(unit).and_then(|part| part.checked_add(secs)).ok_or_else(too_long)?;
+        last_unit = unit;
+    }
+    Ok(Duration::from_secs(secs))
+}
+
+// A length for a setting kept in minutes, from `min` to `max` of them
+pub fn parse_minutes(text: &str, min: u32, max: u32) -> Result<u32, String> {
+    let length = parse(text)?;
+    if length.as_secs() % 60 != 0 {
+        return Err(format!("{:?} has seconds; this takes whole minutes", text.trim()));
+    }
+    let minutes = length.as_secs() / 60;
+    if minutes < min as u64 || minutes > max as u64 {
+        return Err(format!("{:?} isn't between {} and {}", text.trim(), format(Duration::from_secs(min as u64 * 60)), format(Duration::from_secs(max as u64 * 60))));
+    }
+    Ok(minutes as u32)
+}
+
+// The shortest way of writing `length` that parse reads back, e.g. "1h30m"
+// or "25m30s"
+pub fn format(length: Duration) -> String {
+    let secs = length.as_secs();
+    let parts = [(secs / 3600, "h"), (secs / 60 % 60, "m"), (secs % 60, "s")];
+    let text: String = parts.iter().filter(|(n, _)| *n > 0).map(|(n, unit)| format!("{}{}", n, unit)).collect();
+    if text.is_empty() {
+        "0m".to_string()
+    } else {
+        text
+    }
+}
+
+// A minutes setting in config, written as a number or as a length in any
+// of parse's forms
+pub fn minutes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Minutes {
+        Number(u32),
+        Text(String),
+    }
+    match Minutes::deserialize(deserializer)? {
+        Minutes::Number(minutes) => Ok(minutes),
+        Minutes::Text(text) => parse_minutes(&text, 0, u32::MAX).map_err(serde::de::Error::custom),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_are_read_in_every_form() {
+        let secs = |text| parse(text).map(|length| length.as_secs()).ok();
+        let read = ["25m", "25", "1h30m", "1h30", "2h", "45min", " 5M ", "90s", "25:30", "1:25:30", "1h 30m", "25m30s"].map(secs);
+        assert_eq!(read, [Some(1500), Some(1500), Some(5400), Some(5400), Some(7200), Some(2700), Some(300), Some(90), Some(1530), Some(5130), Some(5400), Some(1530)]);
+        for bad in ["", "h", "5x", "30m1h", "5m5m", "25:75", "1:2:3:4", "-5", "99999999999999999999h", "307445734561825860:59", "5124095576030431:00:59"] {
+            assert!(parse(bad).is_err(), "{:?}", bad);
+        }
+        assert_eq!(format(Duration::from_secs(5430)), "1h30m30s");
+    }
+
+    #[test]
+    fn minute_settings_take_whole_minutes_in_range() {
+        assert_eq!(parse_minutes("1h30m", 1, 1440), Ok(90));
+        assert_eq!(parse_minutes("25:30", 1, 1440), Err("\"25:30\" has seconds; this takes whole minutes".to_string()));
+        assert_eq!(parse_minutes("25h", 1, 1440), Err("\"25h\" isn't between 1m and 24h".to_string()));
+    }
+}
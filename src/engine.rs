@@ -0,0 +1,498 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::Preset;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroState {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroState {
+    // Name used in files and output meant for other programs
+    pub fn name(self) -> &'static str {
+        match self {
+            PomodoroState::Work => "work",
+            PomodoroState::ShortBreak => "short_break",
+            PomodoroState::LongBreak => "long_break",
+        }
+    }
+}
+
+// One session of a custom sequence, written "work:25", "break:5" (or
+// "short:5") and "long:20" in config, with the length in any form
+// crate::duration reads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Step {
+    pub state: PomodoroState,
+    pub minutes: u32,
+}
+
+impl Step {
+    pub fn parse(text: &str) -> Option<Step> {
+        let (state, minutes) = text.trim().split_once(':')?;
+        let state = match state.trim().to_lowercase().as_str() {
+            "work" => PomodoroState::Work,
+            "break" | "short" | "short_break" => PomodoroState::ShortBreak,
+            "long" | "long_break" => PomodoroState::LongBreak,
+            _ => return None,
+        };
+        let minutes = crate::duration::parse_minutes(minutes, 1, 1440).ok()?;
+        Some(Step { state, minutes })
+    }
+}
+
+// The work/break sequence and nothing else: no clock, files or alerts. Time
+// only moves by what tick() is given, and a session that ends comes back as
+// a Transition, so the sequencing can be stepped through deterministically.
+// App drives one with the wall clock and does the rest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroEngine {
+    // Session lengths in minutes and the number of work sessions before a
+    // long break
+    pub lengths: Preset,
+    pub state: PomodoroState,
+    // Work sessions finished since the last long break
+    pub cycle_count: u32,
+    pub time_remaining: Duration,
+    pub paused: bool,
+    // Minutes added to the current session with extend
+    pub extended_minutes: u32,
+    // Time counted up in the current session, in stopwatch mode. Sessions
+    // don't end on their own then; time_remaining is left alone.
+    pub stopwatch: Option<Duration>,
+    // Whether the current session runs over instead of ending when its time
+    // is up, and how far it has, until it's ended by hand
+    pub run_over: bool,
+    pub overtime: Option<Duration>,
+    // A custom plan walked in place of the work/short/long cycle, from the
+    // first step again after the last, and the step the timer is on. Empty
+    // for the usual cycle.
+    pub sequence: Vec<Step>,
+    pub step: usize,
+}
+
+// A session that ended, by running out or by a skip, and the one that began
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub finished: PomodoroState,
+    pub next: PomodoroState,
+    pub skipped: bool,
+    // Time actually counted down, less than the full length when skipped
+    // and with any overtime
+    pub counted: Duration,
+    pub extended_minutes: u32,
+    pub overtime: Duration,
+}
+
+impl PomodoroEngine {
+    // Paused at the start of the first work session
+    pub fn new(lengths: Preset) -> Self {
+        let mut engine = PomodoroEngine {
+            lengths,
+            state: PomodoroState::Work,
+            cycle_count: 0,
+            time_remaining: Duration::ZERO,
+            paused: true,
+            extended_minutes: 0,
+            stopwatch: None,
+            run_over: false,
+            overtime: None,
+            sequence: Vec::new(),
+            step: 0,
+        };
+        engine.time_remaining = engine.session_length();
+        engine
+    }
+
+    pub fn duration_for(&self, state: PomodoroState) -> Duration {
+        let minutes = match state {
+            PomodoroState::Work => self.lengths.work_duration,
+            PomodoroState::ShortBreak => self.lengths.short_break,
+            PomodoroState::LongBreak => self.lengths.long_break,
+        };
+        Duration::from_secs(minutes as u64 * 60)
+    }
+
+    // Full length of the current session, extensions included
+    pub fn session_length(&self) -> Duration {
+        let length = match self.sequence.get(self.step) {
+            Some(step) => Duration::from_secs(step.minutes as u64 * 60),
+            None => self.duration_for(self.state),
+        };
+        length + Duration::from_secs(self.extended_minutes as u64 * 60)
+    }
+
+    // Time the current session has run, counted down or up, overtime
+    // included
+    pub fn counted(&self) -> Duration {
+        self.stopwatch.unwrap_or_else(|| self.session_length().saturating_sub(self.time_remaining).saturating_add(self.overtime.unwrap_or_default()))
+    }
+
+    // Switch between counting down and up, keeping the time already counted
+    pub fn set_stopwatch(&mut self, on: bool) {
+        if on == self.stopwatch.is_some() {
+            return;
+        }
+        if on {
+            self.stopwatch = Some(self.counted());
+            self.overtime = None;
+        } else {
+            let counted = self.stopwatch.take().unwrap_or_default();
+            self.time_remaining = self.session_length().saturating_sub(counted);
+        }
+    }
+
+    // Run the current session forward by `elapsed`, if it isn't paused. Time
+    // past the end of a session isn't carried into the next, which starts
+    // paused; with run_over it's overtime instead, and the session goes on.
+    pub fn tick(&mut self, elapsed: Duration) -> Option<Transition> {
+        if self.paused {
+            return None;
+        }
+        if let Some(counted) = self.stopwatch.as_mut().or(self.overtime.as_mut()) {
+            *counted = counted.saturating_add(elapsed);
+            return None;
+        }
+        match self.time_remaining.checked_sub(elapsed) {
+            Some(remaining) => {
+                self.time_remaining = remaining;
+                None
+            }
+            None if self.run_over => {
+                self.overtime = Some(elapsed - self.time_remaining);
+                self.time_remaining = Duration::ZERO;
+                None
+            }
+            None => {
+                self.time_remaining = Duration::ZERO;
+                Some(self.advance(false))
+            }
+        }
+    }
+
+    // Move on to the next session: the sequence's next step if there is
+    // one, otherwise a short break after work, a long one after every
+    // `cycles_before_long` work sessions, and work after a break
+    pub fn advance(&mut self, skipped: bool) -> Transition {
+        let finished = self.state;
+        let counted = self.counted();
+        let extended_minutes = self.extended_minutes;
+        let overtime = self.overtime.take().unwrap_or_default();
+        if !self.sequence.is_empty() {
+            self.step = (self.step + 1) % self.sequence.len();
+            self.state = self.sequence[self.step].state;
+            // Counted over the sequence's work steps, from 0 again once it
+            // loops back to the start
+            self.cycle_count = match (finished, self.state) {
+                _ if self.step == 0 => 0,
+                (_, PomodoroState::LongBreak) => 0,
+                (PomodoroState::Work, _) => (self.cycle_count + 1) % self.cycles_before_long(),
+                _ => self.cycle_count,
+            };
+        } else {
+            self.advance_cycle();
+        }
+        self.extended_minutes = 0;
+        self.time_remaining = self.session_length();
+        self.stopwatch = self.stopwatch.map(|_| Duration::ZERO);
+        self.paused = true;
+        Transition { finished, next: self.state, skipped, counted, extended_minutes, overtime }
+    }
+
+    fn advance_cycle(&mut self) {
+        match self.state {
+            PomodoroState::Work => {
+                self.cycle_count += 1;
+                if self.cycle_count >= self.lengths.cycles_before_long {
+                    self.state = PomodoroState::LongBreak;
+                    self.cycle_count = 0;
+                } else {
+                    self.state = PomodoroState::ShortBreak;
+                }
+            }
+            PomodoroState::ShortBreak | PomodoroState::LongBreak => {
+                self.state = PomodoroState::Work;
+            }
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn reset(&mut self) {
+        self.paused = true;
+        self.cycle_count = 0;
+        self.step = 0;
+        self.state = self.sequence.first().map_or(PomodoroState::Work, |step| step.state);
+        self.extended_minutes = 0;
+        self.time_remaining = self.session_length();
+        self.stopwatch = self.stopwatch.map(|_| Duration::ZERO);
+        self.overtime = None;
+    }
+
+    // More time for the current session. Overtime is paid back first.
+    pub fn extend(&mut self, minutes: u32) {
+        self.extended_minutes += minutes;
+        let extra = Duration::from_secs(minutes as u64 * 60);
+        match self.overtime.take() {
+            Some(over) if over > extra => self.overtime = Some(over - extra),
+            Some(over) => self.time_remaining = extra - over,
+            None => self.time_remaining += extra,
+        }
+    }
+
+    // Take on new lengths: never more time left than the new length allows,
+    // never past the new cycle count
+    pub fn set_lengths(&mut self, lengths: Preset) {
+        self.lengths = lengths;
+        self.time_remaining = self.time_remaining.min(self.session_length());
+        self.cycle_count = self.cycle_count.min(self.cycles_before_long().saturating_sub(1));
+    }
+
+    // Work sessions in a round: `cycles_before_long`, or the work steps of a
+    // custom sequence
+    pub fn cycles_before_long(&self) -> u32 {
+        cycles_in(&self.sequence, self.lengths.cycles_before_long)
+    }
+
+    // Take on a new sequence, staying on the step of the same number if it's
+    // the same kind of session, or else the first such step. A timer whose
+    // kind of session isn't in the sequence starts it from the top.
+    pub fn set_sequence(&mut self, sequence: Vec<Step>) {
+        if sequence == self.sequence {
+            return;
+        }
+        self.sequence = sequence;
+        let same = |step: &Step| step.state == self.state;
+        match self.sequence.get(self.step).filter(|step| same(step)).map(|_| self.step).or_else(|| self.sequence.iter().position(same)) {
+            Some(step) => self.step = step,
+            None if self.sequence.is_empty() => self.step = 0,
+            None => {
+                let paused = self.paused;
+                self.reset();
+                self.paused = paused;
+                return;
+            }
+        }
+        self.time_remaining = self.time_remaining.min(self.session_length());
+        self.cycle_count = self.cycle_count.min(self.cycles_before_long().saturating_sub(1));
+    }
+}
+
+// PomodoroEngine::cycles_before_long for a sequence and cycle count not yet
+// in an engine
+pub fn cycles_in(sequence: &[Step], cycles_before_long: u32) -> u32 {
+    match sequence.iter().filter(|step| step.state == PomodoroState::Work).count() {
+        _ if sequence.is_empty() => cycles_before_long,
+        works => (works as u32).max(1),
+    }
+}
+
+// A monotonic reading, as time since any fixed point. std::time::Instant
+// panics on wasm32-unknown-unknown, so there the frontend brings its own,
+// e.g. performance.now().
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct SystemClock(std::time::Instant);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock(std::time::Instant::now())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+// An engine that keeps time by a Clock instead of being handed elapsed time,
+// the way App drives one by the wall clock
+pub struct ClockedEngine<C: Clock> {
+    pub engine: PomodoroEngine,
+    clock: C,
+    last_tick: Duration,
+}
+
+impl<C: Clock> ClockedEngine<C> {
+    pub fn new(engine: PomodoroEngine, clock: C) -> Self {
+        let last_tick = clock.now();
+        ClockedEngine { engine, clock, last_tick }
+    }
+
+    // Run the timer up to the clock's time
+    pub fn update(&mut self) -> Option<Transition> {
+        let now = self.clock.now();
+        let elapsed = now.saturating_sub(self.last_tick);
+        self.last_tick = now;
+        self.engine.tick(elapsed)
+    }
+
+    // Pausing and resuming go through here so time spent paused isn't
+    // counted on resume
+    pub fn set_paused(&mut self, paused: bool) {
+        if !paused && self.engine.paused {
+            self.last_tick = self.clock.now();
+        }
+        self.engine.paused = paused;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.engine.paused);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    #[test]
+    fn sequence_runs_work_breaks_and_long_break() {
+        let mut engine = PomodoroEngine::new(Preset { work_duration: 25, short_break: 5, long_break: 15, cycles_before_long: 2 });
+        assert_eq!(engine.tick(minutes(60)), None, "paused until started");
+
+        let mut states = Vec::new();
+        for _ in 0..4 {
+            engine.toggle_pause();
+            assert_eq!(engine.tick(engine.time_remaining - Duration::from_secs(1)), None);
+            let transition = engine.tick(minutes(1)).unwrap();
+            assert!(!transition.skipped && engine.paused);
+            assert_eq!(transition.counted, engine.duration_for(transition.finished));
+            states.push(transition.next);
+        }
+        use PomodoroState::*;
+        assert_eq!(states, [ShortBreak, Work, LongBreak, Work]);
+        assert_eq!(engine.cycle_count, 0);
+    }
+
+    #[test]
+    fn skips_and_extensions_are_reported() {
+        let mut engine = PomodoroEngine::new(Preset::default());
+        engine.toggle_pause();
+        engine.tick(minutes(10));
+        engine.extend(5);
+        assert_eq!(engine.time_remaining, minutes(20));
+
+        let transition = engine.advance(true);
+        assert_eq!(transition, Transition { finished: PomodoroState::Work, next: PomodoroState::ShortBreak, skipped: true, counted: minutes(10), extended_minutes: 5, overtime: Duration::ZERO });
+        assert_eq!(engine.time_remaining, minutes(5));
+
+        engine.set_lengths(Preset { short_break: 3, ..Preset::default() });
+        assert_eq!(engine.time_remaining, minutes(3));
+        engine.reset();
+        assert_eq!((engine.state, engine.cycle_count, engine.time_remaining), (PomodoroState::Work, 0, minutes(25)));
+    }
+
+    #[test]
+    fn stopwatch_counts_up_until_ended() {
+        let mut engine = PomodoroEngine::new(Preset::default());
+        engine.toggle_pause();
+        engine.tick(minutes(5));
+        engine.set_stopwatch(true);
+        assert_eq!(engine.tick(minutes(60)), None, "no end to run out");
+        assert_eq!(engine.stopwatch, Some(minutes(65)));
+
+        let transition = engine.advance(false);
+        assert_eq!(transition.counted, minutes(65));
+        assert_eq!(engine.stopwatch, Some(Duration::ZERO));
+        engine.toggle_pause();
+        engine.tick(minutes(2));
+        engine.set_stopwatch(false);
+        assert_eq!(engine.time_remaining, minutes(3));
+    }
+
+    #[test]
+    fn sequence_replaces_the_cycle() {
+        let plan = ["work:25", "break:5", "work:50", "long:20"].map(|step| Step::parse(step).unwrap());
+        let mut engine = PomodoroEngine::new(Preset::default());
+        engine.set_sequence(plan.to_vec());
+        let mut sessions = Vec::new();
+        for _ in 0..5 {
+            sessions.push((engine.state, engine.time_remaining.as_secs() / 60));
+            engine.advance(true);
+        }
+        use PomodoroState::*;
+        assert_eq!(sessions, [(Work, 25), (ShortBreak, 5), (Work, 50), (LongBreak, 20), (Work, 25)]);
+
+        engine.advance(true);
+        engine.set_sequence(vec![plan[0], plan[3]]);
+        assert_eq!((engine.step, engine.state), (0, Work), "no short break left to be on");
+        engine.set_sequence(Vec::new());
+        assert_eq!(engine.time_remaining, minutes(25));
+        assert_eq!(["rest:5", "work:0", "work", "Long: 15", "work:1h30m"].map(Step::parse), [None, None, None, Some(Step { state: LongBreak, minutes: 15 }), Some(Step { state: Work, minutes: 90 })]);
+    }
+
+    #[test]
+    fn sequences_without_a_long_break_still_wrap_the_cycle() {
+        let mut engine = PomodoroEngine::new(Preset { cycles_before_long: 4, ..Preset::default() });
+        engine.set_sequence(["work:25", "break:5", "work:25", "break:5", "work:25", "break:5"].map(|step| Step::parse(step).unwrap()).to_vec());
+        assert_eq!(engine.cycles_before_long(), 3);
+        let cycles: Vec<u32> = (0..8)
+            .map(|_| {
+                engine.advance(true);
+                engine.cycle_count
+            })
+            .collect();
+        assert_eq!(cycles, [1, 1, 2, 2, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn overtime_runs_until_ended() {
+        let mut engine = PomodoroEngine::new(Preset::default());
+        engine.run_over = true;
+        engine.toggle_pause();
+        assert_eq!(engine.tick(minutes(26)), None, "no end to run out");
+        assert_eq!((engine.time_remaining, engine.overtime), (Duration::ZERO, Some(minutes(1))));
+        engine.tick(minutes(2));
+        engine.extend(1);
+        assert_eq!(engine.overtime, Some(minutes(2)), "an extension pays overtime back");
+
+        let transition = engine.advance(false);
+        assert_eq!((transition.counted, transition.overtime), (minutes(28), minutes(2)));
+        assert_eq!((engine.state, engine.overtime), (PomodoroState::ShortBreak, None));
+    }
+
+    struct ManualClock(Rc<Cell<Duration>>);
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    #[test]
+    fn clocked_engine_skips_time_spent_paused() {
+        let time = Rc::new(Cell::new(minutes(100)));
+        let mut clocked = ClockedEngine::new(PomodoroEngine::new(Preset::default()), ManualClock(time.clone()));
+        time.set(minutes(110));
+        assert_eq!(clocked.update(), None);
+        clocked.toggle_pause();
+        time.set(minutes(120));
+        clocked.update();
+        clocked.toggle_pause();
+        time.set(minutes(200));
+        clocked.toggle_pause();
+        time.set(minutes(210));
+        assert_eq!(clocked.update(), None);
+        assert_eq!(clocked.engine.time_remaining, minutes(5));
+        time.set(minutes(216));
+        assert_eq!(clocked.update().map(|transition| transition.next), Some(PomodoroState::ShortBreak));
+    }
+}
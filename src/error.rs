@@ -0,0 +1,38 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    // A settings file that exists but can't be used as-is
+    #[error("{}: {reason}", path.display())]
+    Config { path: PathBuf, reason: String },
+    // Reading or writing one of our files
+    #[error("{}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    // A notifier or other outside program we hand work to
+    #[error("{name}: {reason}")]
+    Integration { name: String, reason: String },
+    // Bad command-line arguments
+    #[error("{0}")]
+    Usage(String),
+    // Talking to the terminal
+    #[error("terminal: {0}")]
+    Render(#[from] io::Error),
+}
+
+impl Error {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Error::Io { path: path.to_path_buf(), source }
+    }
+
+    pub fn config(path: &Path, reason: impl ToString) -> Self {
+        Error::Config { path: path.to_path_buf(), reason: reason.to_string() }
+    }
+
+    pub fn integration(name: &str, reason: impl ToString) -> Self {
+        Error::Integration { name: name.to_string(), reason: reason.to_string() }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
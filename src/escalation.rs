@@ -0,0 +1,111 @@
+use std::time::{Duration, Instant};
+
+use crate::app::{App, PomodoroState};
+use crate::error::Result;
+use crate::integrations::sound::play;
+
+// Progress through the configured escalation steps since a session ended
+pub struct Escalation {
+    pub finished: PomodoroState,
+    pub since: Instant,
+    // Steps already fired
+    pub fired: usize,
+    // The statusline flashes from a "flash" step on
+    pub flashing: bool,
+    pub sound: Option<RepeatingSound>,
+}
+
+// A "sound" step still playing: when next (time into the escalation) and
+// how loud
+pub struct RepeatingSound {
+    pub at: Duration,
+    pub volume: u32,
+    pub every: u32,
+    pub volume_step: u32,
+}
+
+impl Escalation {
+    pub fn new(finished: PomodoroState) -> Self {
+        Escalation { finished, since: Instant::now(), fired: 0, flashing: false, sound: None }
+    }
+
+    // Whether a flashing statusline is lit this second
+    pub fn lit(&self) -> bool {
+        self.flashing && self.since.elapsed().as_secs().is_multiple_of(2)
+    }
+}
+
+impl App {
+    // Fire the steps due `elapsed` into the escalation, and the next play
+    // of a repeating sound
+    pub fn escalate(&mut self, elapsed: Duration) -> Result<()> {
+        let Some(escalation) = &mut self.escalation else {
+            return Ok(());
+        };
+        let mut notify = false;
+        while let Some(step) = self.config.escalation.get(escalation.fired).filter(|step| Duration::from_secs(step.after as u64) <= elapsed) {
+            escalation.fired += 1;
+            match step.alert.as_str() {
+                "flash" => escalation.flashing = true,
+                "bell" => self.ring_bell = true,
+                "notify" => notify = true,
+                _ => {
+                    let (every, volume_step) = (step.every, step.volume_step);
+                    escalation.sound = Some(RepeatingSound { at: elapsed, volume: self.config.sound.volume, every, volume_step });
+                }
+            }
+        }
+
+        let mut volume = None;
+        if let Some(sound) = escalation.sound.as_mut().filter(|sound| sound.at <= elapsed) {
+            volume = Some(sound.volume);
+            sound.at = elapsed + Duration::from_secs(sound.every as u64);
+            sound.volume = (sound.volume + sound.volume_step).min(100);
+            if sound.every == 0 {
+                escalation.sound = None;
+            }
+        }
+
+        let finished = escalation.finished;
+        if notify {
+            let (title, body) = self.transition_message(finished);
+            self.notify(title, &body)?;
+        }
+        if let Some(volume) = volume {
+            self.health.track("sound", play(&self.sound_file(), volume))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, EscalationStep};
+
+    fn step(after: u32, alert: &str) -> EscalationStep {
+        EscalationStep { after, alert: alert.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn steps_fire_in_turn_until_acknowledged() {
+        let config = Config { escalation: vec![step(0, "flash"), step(30, "bell"), step(60, "notify")], ..Default::default() };
+        let mut app = App::for_test(config);
+        app.advance_state(false);
+        let fired = |app: &App| app.escalation.as_ref().map(|e| (e.fired, e.flashing));
+        assert_eq!(fired(&app), Some((1, true)));
+        assert!(!app.ring_bell);
+
+        app.escalate(Duration::from_secs(45)).unwrap();
+        assert_eq!(fired(&app), Some((2, true)));
+        assert!(app.ring_bell);
+        app.escalate(Duration::from_secs(90)).unwrap();
+        assert_eq!(fired(&app), Some((3, true)));
+
+        app.toggle_pause();
+        app.update().unwrap();
+        assert!(app.escalation.is_none());
+        app.advance_state(true);
+        assert!(app.escalation.is_none(), "skips aren't alerted");
+    }
+}
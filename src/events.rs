@@ -0,0 +1,121 @@
+use crossterm::event::{self, Event};
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Actions that can come from any source, not only the keyboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    TogglePause,
+    Pause,
+    Resume,
+    Reset,
+    Skip,
+    Extend,
+    // Count the clock up or down
+    Stopwatch,
+    Quit,
+}
+
+impl Command {
+    // Names used on the command line and the control channel
+    pub const NAMES: [(&'static str, Command); 8] = [
+        ("toggle", Command::TogglePause),
+        ("pause", Command::Pause),
+        ("resume", Command::Resume),
+        ("reset", Command::Reset),
+        ("skip", Command::Skip),
+        ("extend", Command::Extend),
+        ("stopwatch", Command::Stopwatch),
+        ("quit", Command::Quit),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Command> {
+        Command::NAMES.iter().find(|(n, _)| *n == name).map(|&(_, command)| command)
+    }
+
+    #[cfg_attr(not(feature = "control"), allow(dead_code))]
+    pub fn name(self) -> &'static str {
+        Command::NAMES.iter().find(|&&(_, c)| c == self).map(|&(name, _)| name).unwrap_or_default()
+    }
+}
+
+// Everything the main loop reacts to. Each source runs on its own thread and
+// only sends into the channel, so the app state and stdout are touched from
+// the main loop alone.
+pub enum AppEvent {
+    Terminal(Event),
+    // Sent by sources other than the keyboard
+    #[cfg_attr(not(feature = "control"), allow(dead_code))]
+    Command(Command),
+    // The desktop's idle time, polled when "idle_pause_minutes" is set
+    Idle(Duration),
+    // Reading terminal input failed; there's no way to carry on
+    InputFailed(io::Error),
+}
+
+pub struct Events {
+    tx: Sender<AppEvent>,
+    rx: Receiver<AppEvent>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Events {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Events { tx, rx }
+    }
+
+    // Handle for other event sources to send through
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.tx.clone()
+    }
+
+    // Forward terminal input (keys, resizes) from a background thread
+    pub fn spawn_terminal(&self) {
+        let tx = self.tx.clone();
+        thread::spawn(move || loop {
+            let event = match event::read() {
+                Ok(event) => AppEvent::Terminal(event),
+                Err(e) => {
+                    let _ = tx.send(AppEvent::InputFailed(e));
+                    return;
+                }
+            };
+            if tx.send(event).is_err() {
+                return;
+            }
+        });
+    }
+
+    // Send recorded terminal input, each event once its time since now has
+    // passed (--replay)
+    pub fn spawn_replay(&self, events: Vec<(Duration, Event)>) {
+        let tx = self.tx.clone();
+        let started = Instant::now();
+        thread::spawn(move || {
+            for (at, event) in events {
+                thread::sleep(at.saturating_sub(started.elapsed()));
+                if tx.send(AppEvent::Terminal(event)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    // Wait up to `timeout` for an event, then take whatever else is already
+    // queued so a burst of input is handled before the next frame
+    pub fn wait(&self, timeout: Duration) -> Vec<AppEvent> {
+        let first = match self.rx.recv_timeout(timeout) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => return Vec::new(),
+        };
+        std::iter::once(first).chain(self.rx.try_iter()).collect()
+    }
+}
@@ -0,0 +1,155 @@
+// C interface to PomodoroEngine, for frontends in other languages (include/
+// rpomodoro.h). Build a shared library with
+// `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//
+// Engines come from rpomodoro_engine_new and are freed with
+// rpomodoro_engine_free; every other function takes a pointer from
+// rpomodoro_engine_new that hasn't been freed, used from one thread at a
+// time. Null pointers are refused rather than dereferenced.
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, c_int, CStr};
+use std::time::Duration;
+
+use crate::config::Preset;
+use crate::engine::{PomodoroEngine, PomodoroState};
+use crate::events::Command;
+use crate::flow::EXTEND_STEP;
+
+// Bumped whenever a signature or RpomodoroStatus changes
+pub const ABI_VERSION: u32 = 2;
+
+#[repr(C)]
+#[derive(Debug, Default, PartialEq)]
+pub struct RpomodoroStatus {
+    // 0 work, 1 short break, 2 long break
+    pub state: u32,
+    pub paused: bool,
+    pub remaining_ms: u64,
+    pub cycle: u32,
+    pub cycles_before_long: u32,
+    pub extended_minutes: u32,
+    // Counting up after the "stopwatch" command, with remaining_ms left as
+    // it was
+    pub stopwatch: bool,
+    // Time the session has run, counted down or up, overtime included
+    pub counted_ms: u64,
+}
+
+#[no_mangle]
+pub extern "C" fn rpomodoro_abi_version() -> u32 {
+    ABI_VERSION
+}
+
+// Paused at the start of the first work session. Lengths are minutes; 0
+// takes the default.
+#[no_mangle]
+pub extern "C" fn rpomodoro_engine_new(work: u32, short_break: u32, long_break: u32, cycles_before_long: u32) -> *mut PomodoroEngine {
+    let defaults = Preset::default();
+    let or_default = |value: u32, default: u32| if value == 0 { default } else { value.min(24 * 60) };
+    let lengths = Preset {
+        work_duration: or_default(work, defaults.work_duration),
+        short_break: or_default(short_break, defaults.short_break),
+        long_break: or_default(long_break, defaults.long_break),
+        cycles_before_long: or_default(cycles_before_long, defaults.cycles_before_long),
+    };
+    Box::into_raw(Box::new(PomodoroEngine::new(lengths)))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn rpomodoro_engine_free(engine: *mut PomodoroEngine) {
+    if !engine.is_null() {
+        drop(Box::from_raw(engine));
+    }
+}
+
+// Run the timer forward by `elapsed_ms`. Returns 1 when a session ended
+// (the next one starts paused), 0 otherwise, -1 for a null engine.
+#[no_mangle]
+pub unsafe extern "C" fn rpomodoro_engine_tick(engine: *mut PomodoroEngine, elapsed_ms: u64) -> c_int {
+    let Some(engine) = engine.as_mut() else {
+        return -1;
+    };
+    engine.tick(Duration::from_millis(elapsed_ms)).is_some() as c_int
+}
+
+// "toggle", "pause", "resume", "skip", "extend", "stopwatch" or "reset", as
+// on the command line. Returns 0, or -1 for a null pointer or another name.
+#[no_mangle]
+pub unsafe extern "C" fn rpomodoro_engine_command(engine: *mut PomodoroEngine, command: *const c_char) -> c_int {
+    let (Some(engine), false) = (engine.as_mut(), command.is_null()) else {
+        return -1;
+    };
+    let command = CStr::from_ptr(command).to_str().ok().and_then(Command::from_name);
+    match command {
+        Some(Command::TogglePause) => engine.toggle_pause(),
+        Some(Command::Pause) => engine.paused = true,
+        Some(Command::Resume) => engine.paused = false,
+        Some(Command::Skip) => {
+            engine.advance(true);
+        }
+        Some(Command::Extend) => engine.extend(EXTEND_STEP),
+        Some(Command::Reset) => engine.reset(),
+        Some(Command::Stopwatch) => engine.set_stopwatch(engine.stopwatch.is_none()),
+        Some(Command::Quit) | None => return -1,
+    }
+    0
+}
+
+// Fill `status` with where the timer is. Returns 0, or -1 for a null
+// pointer.
+#[no_mangle]
+pub unsafe extern "C" fn rpomodoro_engine_status(engine: *const PomodoroEngine, status: *mut RpomodoroStatus) -> c_int {
+    let (Some(engine), Some(status)) = (engine.as_ref(), status.as_mut()) else {
+        return -1;
+    };
+    *status = RpomodoroStatus {
+        state: match engine.state {
+            PomodoroState::Work => 0,
+            PomodoroState::ShortBreak => 1,
+            PomodoroState::LongBreak => 2,
+        },
+        paused: engine.paused,
+        remaining_ms: engine.time_remaining.as_millis() as u64,
+        cycle: engine.cycle_count,
+        cycles_before_long: engine.lengths.cycles_before_long,
+        extended_minutes: engine.extended_minutes,
+        stopwatch: engine.stopwatch.is_some(),
+        counted_ms: engine.counted().as_millis() as u64,
+    };
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn engine_runs_through_the_c_interface() {
+        let (toggle, quit) = (CString::new("toggle").unwrap(), CString::new("quit").unwrap());
+        unsafe {
+            let engine = rpomodoro_engine_new(1, 0, 0, 2);
+            let mut status = RpomodoroStatus::default();
+            assert_eq!(rpomodoro_engine_command(engine, toggle.as_ptr()), 0);
+            assert_eq!(rpomodoro_engine_tick(engine, 59_500), 0);
+            rpomodoro_engine_status(engine, &mut status);
+            assert_eq!(status, RpomodoroStatus { state: 0, paused: false, remaining_ms: 500, cycle: 0, cycles_before_long: 2, extended_minutes: 0, stopwatch: false, counted_ms: 59_500 });
+
+            assert_eq!(rpomodoro_engine_tick(engine, 1_000), 1);
+            rpomodoro_engine_status(engine, &mut status);
+            assert_eq!((status.state, status.paused, status.remaining_ms), (1, true, 5 * 60_000));
+
+            let stopwatch = CString::new("stopwatch").unwrap();
+            assert_eq!(rpomodoro_engine_command(engine, stopwatch.as_ptr()), 0);
+            assert_eq!(rpomodoro_engine_command(engine, toggle.as_ptr()), 0);
+            assert_eq!(rpomodoro_engine_tick(engine, 2_000), 0);
+            rpomodoro_engine_status(engine, &mut status);
+            assert_eq!((status.stopwatch, status.counted_ms, status.remaining_ms), (true, 2_000, 5 * 60_000));
+
+            assert_eq!(rpomodoro_engine_command(engine, quit.as_ptr()), -1);
+            assert_eq!(rpomodoro_engine_tick(std::ptr::null_mut(), 1), -1);
+            rpomodoro_engine_free(engine);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Heuristics for noticing flow. Work sessions that keep getting extended
+// suggest the configured length is too short for the kind of work being
+// done today, so a longer block is offered for the rest of the day.
+
+// Minutes added by each extend
+pub const EXTEND_STEP: u32 = 5;
+// Extended work sessions in a day before a longer block is suggested
+pub const EXTENDED_SESSIONS: usize = 2;
+
+// Work length to offer, in minutes: the configured length plus the average
+// extension of today's extended sessions, rounded up to the extend step.
+// None until there's a pattern to go on.
+pub fn suggested_work_minutes(work_minutes: u32, extensions_today: &[u32]) -> Option<u32> {
+    if extensions_today.len() < EXTENDED_SESSIONS {
+        return None;
+    }
+    let total: u32 = extensions_today.iter().sum();
+    let mean = total.div_ceil(extensions_today.len() as u32);
+    let minutes = (work_minutes + mean).div_ceil(EXTEND_STEP) * EXTEND_STEP;
+    Some(minutes.min(24 * 60)).filter(|&minutes| minutes > work_minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_after_repeated_extensions() {
+        assert_eq!(suggested_work_minutes(25, &[]), None);
+        assert_eq!(suggested_work_minutes(25, &[10]), None);
+        assert_eq!(suggested_work_minutes(25, &[10, 15]), Some(40));
+        assert_eq!(suggested_work_minutes(25, &[5, 5, 5]), Some(30));
+        assert_eq!(suggested_work_minutes(24 * 60, &[5, 5]), None);
+    }
+}
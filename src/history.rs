@@ -0,0 +1,230 @@
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::app::PomodoroState;
+use crate::crypto::{is_encrypted, Passphrase};
+use crate::error::{Error, Result};
+use crate::storage::append_line;
+
+// Every finished or skipped session and break, one JSON object per line in
+// history.jsonl next to the stats. Appended on each transition rather than
+// rewritten, so it stays cheap however long it gets. With encryption on each
+// line is encrypted on its own and stored as hex, under the salt of the line
+// before it, so loading derives the key once rather than once per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    // "work", "short_break" or "long_break"
+    pub state: String,
+    // Local times, YYYY-MM-DDTHH:MM:SS. No start for a session skipped
+    // before it was ever started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    pub end: String,
+    // Time actually counted down, less than the full length when skipped
+    #[serde(default)]
+    pub seconds: u64,
+    pub skipped: bool,
+    // Markers dropped during the session with m, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub laps: Vec<Lap>,
+    // Carried on after a restart of rpomodoro (session.json)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub resumed: bool,
+    // The active task, for work sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    // Seconds run past the end, for a session in overtime; they're part of
+    // `seconds` too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtime: Option<u64>,
+    // The active profile, if any, so reports can keep them apart
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lap {
+    // Seconds into the session
+    pub at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl HistoryEntry {
+    pub fn new(state: PomodoroState, start: Option<String>, seconds: u64, skipped: bool) -> Self {
+        HistoryEntry { state: state.name().to_string(), start, end: now(), seconds, skipped, laps: Vec::new(), resumed: false, task: None, overtime: None, profile: None }
+    }
+}
+
+pub fn now() -> String {
+    chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+pub fn append(path: &Path, entry: &HistoryEntry, passphrase: Option<&Passphrase>) -> Result<()> {
+    let json = serde_json::to_string(entry).map_err(|e| Error::config(path, e))?;
+    let line = match passphrase {
+        Some(passphrase) => to_hex(&passphrase.encrypt_like(json.as_bytes(), last_encrypted(path).as_deref())),
+        None => json,
+    };
+    append_line(path, &line)
+}
+
+// Whether any line is encrypted, so a passphrase is needed to read it even
+// with encryption since switched off
+pub fn has_encrypted_lines(path: &Path) -> bool {
+    last_encrypted(path).is_some_and(|line| is_encrypted(&line))
+}
+
+// The last encrypted line, decoded from hex
+fn last_encrypted(path: &Path) -> Option<Vec<u8>> {
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().rev().filter(|line| !line.starts_with('{')).find_map(from_hex)
+}
+
+// All entries, oldest first. Lines that can't be read, like a half-written
+// last line after a crash, are skipped, but encrypted ones without a
+// passphrase are an error rather than quietly left out.
+pub fn load(path: &Path, passphrase: Option<&Passphrase>) -> Result<Vec<HistoryEntry>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io(path, e)),
+    };
+    let mut entries = Vec::new();
+    for line in content.lines() {
+        let entry = match (line.starts_with('{'), from_hex(line)) {
+            (true, _) => serde_json::from_str(line).ok(),
+            (false, Some(data)) if is_encrypted(&data) => {
+                let passphrase = passphrase.ok_or_else(|| Error::config(path, "has encrypted entries, which need the passphrase"))?;
+                passphrase.decrypt(&data).and_then(|plain| serde_json::from_slice(&plain).ok())
+            }
+            _ => None,
+        };
+        entries.extend(entry);
+    }
+    Ok(entries)
+}
+
+// What the stats screen shows: today's totals and a week of completed
+// pomodoros per day, oldest first, for one profile or all of them
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsView {
+    // None for all profiles together
+    pub profile: Option<String>,
+    pub completed_today: u32,
+    pub focus_minutes_today: u64,
+    // Weekday label and pomodoros completed that day
+    pub days: Vec<(String, u32)>,
+}
+
+pub fn summarize(entries: &[HistoryEntry], today: NaiveDate, profile: Option<&str>) -> StatsView {
+    let work_on = |date: String| entries.iter().filter(move |e| e.state == "work" && e.end.starts_with(&date) && in_profile(e, profile));
+    let completed_on = |date: String| work_on(date).filter(|e| !e.skipped).count() as u32;
+    let key = |day: NaiveDate| day.format("%Y-%m-%d").to_string();
+    let days = (0..7)
+        .rev()
+        .filter_map(|ago| today.checked_sub_days(Days::new(ago)))
+        .map(|day| (day.format("%a").to_string(), completed_on(key(day))))
+        .collect();
+    StatsView {
+        profile: profile.map(str::to_string),
+        completed_today: completed_on(key(today)),
+        focus_minutes_today: work_on(key(today)).map(|e| e.seconds).sum::<u64>() / 60,
+        days,
+    }
+}
+
+// Whether `entry` belongs to `profile`; every entry does for None
+pub fn in_profile(entry: &HistoryEntry, profile: Option<&str>) -> bool {
+    profile.is_none_or(|profile| entry.profile.as_deref() == Some(profile))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_round_trip_past_a_torn_line() {
+        let path = std::env::temp_dir().join(format!("rpomodoro-test-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let first = HistoryEntry::new(PomodoroState::Work, Some("2026-01-05T09:00:00".to_string()), 1500, false);
+        let second = HistoryEntry::new(PomodoroState::ShortBreak, None, 0, true);
+        append(&path, &first, None).unwrap();
+        append_line(&path, "{\"state\": \"wo").unwrap();
+        append(&path, &second, None).unwrap();
+
+        assert_eq!(load(&path, None).unwrap(), [first, second]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_lines_share_the_first_salt() {
+        let path = std::env::temp_dir().join(format!("rpomodoro-test-history-encrypted-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let entries: Vec<HistoryEntry> = (0..3).map(|i| HistoryEntry::new(PomodoroState::Work, None, i, false)).collect();
+        for entry in &entries {
+            append(&path, entry, Some(&Passphrase::for_test("correct horse"))).unwrap();
+        }
+        let content = fs::read_to_string(&path).unwrap();
+        let salts: Vec<&str> = content.lines().map(|line| &line[..78]).collect();
+        assert!(salts.iter().all(|salt| *salt == salts[0]), "one salt, so one key to derive");
+        assert_eq!(load(&path, Some(&Passphrase::for_test("correct horse"))).unwrap(), entries);
+        assert!(has_encrypted_lines(&path));
+        assert!(load(&path, None).is_err(), "encrypted entries aren't dropped without a passphrase");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(from_hex(&to_hex(&[0, 0x7f, 0xff])).unwrap(), [0, 0x7f, 0xff]);
+        assert_eq!(from_hex("abc"), None);
+    }
+
+    #[test]
+    fn summary_counts_completed_work_per_day() {
+        let entry = |end: &str, state: &str, seconds, skipped| HistoryEntry {
+            state: state.to_string(),
+            start: None,
+            end: end.to_string(),
+            seconds,
+            skipped,
+            laps: Vec::new(),
+            resumed: false,
+            task: None,
+            overtime: None,
+            profile: None,
+        };
+        let entries = [
+            entry("2026-01-01T10:00:00", "work", 1500, false),
+            entry("2026-01-05T09:25:00", "work", 1500, false),
+            entry("2026-01-05T09:30:00", "short_break", 300, false),
+            entry("2026-01-05T10:00:00", "work", 600, true),
+            HistoryEntry { profile: Some("study".to_string()), ..entry("2026-01-04T10:00:00", "work", 1500, false) },
+        ];
+        let view = summarize(&entries, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(), None);
+        assert_eq!(view.completed_today, 1);
+        assert_eq!(view.focus_minutes_today, 35);
+        assert_eq!(view.days.len(), 7);
+        assert_eq!(view.days[6], ("Mon".to_string(), 1));
+        assert_eq!(view.days[2], ("Thu".to_string(), 1));
+        assert_eq!(view.days[5], ("Sun".to_string(), 1));
+
+        let study = summarize(&entries, NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(), Some("study"));
+        assert_eq!((study.completed_today, study.days[2].1, study.days[5].1), (0, 0, 1));
+    }
+}
@@ -0,0 +1,425 @@
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::time::Instant;
+
+use crate::app::{App, SkipRequest, MIN_JUSTIFICATION};
+use crate::config::{Keybindings, THEMES};
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::navigation::Axis;
+use crate::settings::Setting;
+use crate::tasks::TaskPane;
+use crate::theme::{Theme, ThemeEditor};
+
+// What a key does in the main view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Command(Command),
+    Flow,
+    Offline,
+    Stats,
+    Preset,
+    Lap,
+    Integrations,
+    Tasks,
+    Palette,
+    Timer,
+    Config,
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Action> {
+        let action = match name {
+            "flow" => Action::Flow,
+            "offline" => Action::Offline,
+            "stats" => Action::Stats,
+            "preset" => Action::Preset,
+            "lap" => Action::Lap,
+            "integrations" => Action::Integrations,
+            "tasks" => Action::Tasks,
+            "palette" => Action::Palette,
+            "timer" => Action::Timer,
+            "config" => Action::Config,
+            name => Action::Command(Command::from_name(name)?),
+        };
+        Some(action)
+    }
+}
+
+// Keybindings parsed once, when the config is applied
+#[derive(Debug, Clone)]
+pub struct Keymap(Vec<(KeyCode, Action)>);
+
+impl Keymap {
+    pub fn from_config(bindings: &Keybindings) -> Self {
+        let keys = bindings.actions().into_iter().filter_map(|(name, key)| Some((parse_key(key)?, Action::from_name(name)?)));
+        Keymap(keys.collect())
+    }
+
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        let code = match code {
+            KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+            code => code,
+        };
+        self.0.iter().find(|&&(key, _)| key == code).map(|&(_, action)| action)
+    }
+}
+
+// A key name as accepted in "keybindings", already lowercased
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    let code = match (name, chars.next(), chars.next()) {
+        ("space", ..) => KeyCode::Char(' '),
+        ("enter", ..) => KeyCode::Enter,
+        ("tab", ..) => KeyCode::Tab,
+        ("backspace", ..) => KeyCode::Backspace,
+        (_, Some(c), None) => KeyCode::Char(c),
+        _ => KeyCode::F(name.strip_prefix('f')?.parse().ok().filter(|n| (1..=11).contains(n))?),
+    };
+    Some(code)
+}
+
+impl App {
+    // Apply one event from the main loop's channel. Returns false once
+    // something asked to quit.
+    pub fn handle_app_event(&mut self, event: AppEvent) -> Result<bool> {
+        match event {
+            AppEvent::Terminal(event) => Ok(self.handle_event(event)),
+            // Other processes and machines are ignored while offline
+            AppEvent::Command(_) if self.offline => Ok(true),
+            AppEvent::Command(command) => {
+                self.last_input = Instant::now();
+                Ok(self.execute(command))
+            }
+            AppEvent::Idle(idle) => {
+                self.desktop_idle(idle);
+                Ok(true)
+            }
+            AppEvent::InputFailed(e) => Err(Error::Render(e)),
+        }
+    }
+
+    // Run a command regardless of where it came from. Returns false for Quit.
+    pub fn execute(&mut self, command: Command) -> bool {
+        if self.attached && command != Command::Quit {
+            self.outbox.push(command);
+            return true;
+        }
+        match command {
+            Command::TogglePause => self.toggle_pause(),
+            Command::Pause => {
+                if !self.engine.paused {
+                    self.toggle_pause();
+                }
+            }
+            Command::Resume => {
+                if self.engine.paused {
+                    self.toggle_pause();
+                }
+            }
+            Command::Reset => self.reset(),
+            Command::Skip => self.request_skip(),
+            Command::Extend => self.extend_session(),
+            Command::Stopwatch => self.toggle_stopwatch(),
+            Command::Quit => return false,
+        }
+        true
+    }
+
+    // Apply one terminal event. Returns false once the user asked to quit.
+    pub fn handle_event(&mut self, event: Event) -> bool {
+        match event {
+            Event::Key(key) => {
+                self.last_input = Instant::now();
+                if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                    return self.execute(Command::Quit);
+                }
+                // Any key acknowledges a finished session's alerts
+                self.escalation = None;
+
+                if key.code == KeyCode::F(12) {
+                    self.show_overlay = !self.show_overlay;
+                } else if self.sleep_gap.is_some() {
+                    let count = match key.code {
+                        KeyCode::Char('y') => Some(true),
+                        KeyCode::Char('n') => Some(false),
+                        _ => None,
+                    };
+                    if let Err(e) = self.answer_sleep_gap(count) {
+                        self.show_error(e);
+                    }
+                } else if self.skip_request.is_some() {
+                    if let Err(e) = self.handle_skip_input(key) {
+                        self.show_error(e);
+                    }
+                } else if self.lap_label.is_some() {
+                    self.handle_lap_input(key);
+                } else if self.countdown_input.is_some() {
+                    self.handle_countdown_input(key);
+                } else if self.palette.is_some() {
+                    if let Err(e) = self.handle_palette_input(key) {
+                        self.show_error(e);
+                    }
+                } else if let Some(view) = &self.stats_view {
+                    if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || self.keymap.action(key.code) == Some(Action::Stats) {
+                        self.stats_view = None;
+                    } else if key.code == KeyCode::Char('p') {
+                        if let Err(e) = self.cycle_stats_profile() {
+                            self.show_error(e);
+                        }
+                    } else if let Some(motion) = self.navigation.motion(key, Axis::Horizontal) {
+                        self.stats_cursor = motion.apply(self.stats_cursor, view.days.len(), view.days.len());
+                    }
+                } else if self.task_pane.is_some() {
+                    if let Err(e) = self.handle_task_input(key) {
+                        self.show_error(e);
+                    }
+                } else if self.show_integrations {
+                    if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) || self.keymap.action(key.code) == Some(Action::Integrations) {
+                        self.show_integrations = false;
+                    }
+                } else if self.config_mode {
+                    if let Err(e) = self.handle_config_input(key) {
+                        self.show_error(e);
+                    }
+                } else if !self.handle_activity_input(key) {
+                    match self.keymap.action(key.code) {
+                        Some(Action::Command(command)) => return self.execute(command),
+                        Some(Action::Flow) => self.accept_flow_suggestion(),
+                        Some(Action::Offline) => self.offline = !self.offline,
+                        Some(Action::Stats) => {
+                            if let Err(e) = self.open_stats() {
+                                self.show_error(e);
+                            }
+                        }
+                        Some(Action::Preset) => {
+                            if let Err(e) = self.switch_preset() {
+                                self.show_error(e);
+                            }
+                        }
+                        Some(Action::Lap) => self.start_lap(),
+                        Some(Action::Integrations) => self.show_integrations = true,
+                        Some(Action::Tasks) => self.task_pane = Some(TaskPane::default()),
+                        Some(Action::Palette) => self.open_palette(),
+                        Some(Action::Timer) => self.open_countdown_prompt(),
+                        Some(Action::Config) => self.enter_config(),
+                        None => {}
+                    }
+                }
+            }
+            Event::Resize(w, h) => {
+                self.width = w;
+                self.height = h;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    // Keys while a break skip is pending: typing a reason, or cancelling the
+    // cooldown with any key
+    pub fn handle_skip_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(SkipRequest::Justify(reason)) = &mut self.skip_request else {
+            self.skip_request = None;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => self.skip_request = None,
+            KeyCode::Enter if reason.trim().chars().count() >= MIN_JUSTIFICATION => {
+                let reason = reason.trim().to_string();
+                self.confirm_skip(Some(reason))?;
+            }
+            KeyCode::Backspace => {
+                reason.pop();
+            }
+            KeyCode::Char(c) => reason.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Keys in the task pane: j/k move, enter makes the highlighted task the
+    // active one (or none, if it already is), n types a new one's title and
+    // d deletes. Every change is saved straight away.
+    pub fn handle_task_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(pane) = &mut self.task_pane else {
+            return Ok(());
+        };
+        if let Some(title) = &mut pane.new_title {
+            match key.code {
+                KeyCode::Esc => pane.new_title = None,
+                KeyCode::Enter if !title.trim().is_empty() => {
+                    let title = title.trim().to_string();
+                    pane.new_title = None;
+                    pane.cursor = self.tasks.tasks.len();
+                    self.tasks.add(&title);
+                    return self.save_tasks();
+                }
+                KeyCode::Backspace => {
+                    title.pop();
+                }
+                KeyCode::Char(c) => title.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+        let count = self.tasks.tasks.len();
+        if let Some(motion) = self.navigation.motion(key, Axis::Vertical) {
+            pane.cursor = motion.apply(pane.cursor, count, self.height as usize);
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.task_pane = None,
+            code if self.keymap.action(code) == Some(Action::Tasks) => self.task_pane = None,
+            KeyCode::Char('n') => pane.new_title = Some(String::new()),
+            KeyCode::Enter if pane.cursor < count => {
+                self.tasks.select(pane.cursor);
+                return self.save_tasks();
+            }
+            KeyCode::Char('d') if pane.cursor < count => {
+                self.tasks.remove(pane.cursor);
+                pane.cursor = pane.cursor.min(count.saturating_sub(2));
+                return self.save_tasks();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Keys on the command line: tab completes, enter runs it
+    pub fn handle_palette_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(line) = &mut self.palette else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Enter => return self.run_palette(),
+            KeyCode::Tab => self.complete_palette(),
+            // Backspace on an empty line closes it, as in vim
+            KeyCode::Backspace if line.is_empty() => self.palette = None,
+            KeyCode::Backspace => {
+                line.pop();
+            }
+            KeyCode::Char(c) => line.push(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Keys while a lap's label is being typed; it may be left empty
+    pub fn handle_lap_input(&mut self, key: KeyEvent) {
+        let Some(label) = &mut self.lap_label else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.lap_label = None,
+            KeyCode::Enter => {
+                let label = Some(label.trim().to_string()).filter(|label| !label.is_empty());
+                self.mark_lap(label);
+            }
+            KeyCode::Backspace => {
+                label.pop();
+            }
+            KeyCode::Char(c) => label.push(c),
+            _ => {}
+        }
+    }
+
+    // Keys on the config screen: h/l change the highlighted setting, tab and
+    // shift-tab turn the page and / searches every page by name
+    pub fn handle_config_input(&mut self, key: KeyEvent) -> Result<()> {
+        if self.theme_editor.is_some() {
+            self.handle_theme_editor_input(key);
+            return Ok(());
+        }
+        let view = &mut self.config_view;
+        if view.typing {
+            match key.code {
+                KeyCode::Esc => (view.search, view.typing) = (None, false),
+                KeyCode::Enter => view.typing = false,
+                KeyCode::Backspace => {
+                    view.search.get_or_insert_with(String::new).pop();
+                }
+                KeyCode::Char(c) => view.search.get_or_insert_with(String::new).push(c),
+                _ => {}
+            }
+            view.cursor = 0;
+            return Ok(());
+        }
+        if let Some(motion) = self.navigation.motion(key, Axis::Vertical) {
+            view.cursor = motion.apply(view.cursor, view.rows().len(), self.height as usize);
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Tab if view.search.is_none() => view.turn_page(true),
+            KeyCode::BackTab if view.search.is_none() => view.turn_page(false),
+            KeyCode::Char('/') => (view.search, view.typing, view.cursor) = (Some(String::new()), true, 0),
+            // Out of the search first, then out of the config screen
+            KeyCode::Esc if view.search.is_some() => (view.search, view.cursor) = (None, 0),
+            KeyCode::Char('q') | KeyCode::Esc => self.leave_config()?,
+            KeyCode::Enter if view.selected() == Some(Setting::Theme) => {
+                self.theme_editor = Some(ThemeEditor::new(&Theme::from_config(&self.config)));
+            }
+            KeyCode::Char('h') | KeyCode::Left => {
+                if let Some(setting) = view.selected() {
+                    setting.change(self, false);
+                }
+            }
+            KeyCode::Char('l') | KeyCode::Right => {
+                if let Some(setting) = view.selected() {
+                    setting.change(self, true);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // Keys in the theme editor: channels change with a live preview, then
+    // the result is saved under a typed name. Built-in names are taken.
+    pub fn handle_theme_editor_input(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.theme_editor else {
+            return;
+        };
+        if let Some(name) = &mut editor.name {
+            match key.code {
+                KeyCode::Esc => editor.name = None,
+                KeyCode::Enter if !name.trim().is_empty() && !THEMES.contains(&name.trim()) => {
+                    let name = name.trim().to_string();
+                    self.config.themes.insert(name.clone(), editor.custom());
+                    self.config.theme = name;
+                    self.theme_editor = None;
+                    self.apply_theme();
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) => name.push(c),
+                _ => {}
+            }
+            return;
+        }
+
+        if let Some(motion) = self.navigation.motion(key, Axis::Vertical) {
+            editor.row = motion.apply(editor.row, 6, self.height as usize);
+            return;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.theme_editor = None;
+                self.apply_theme();
+                return;
+            }
+            KeyCode::Char('h') | KeyCode::Left => editor.adjust(-1),
+            KeyCode::Char('l') | KeyCode::Right => editor.adjust(1),
+            KeyCode::Char('H') => editor.adjust(-16),
+            KeyCode::Char('L') => editor.adjust(16),
+            KeyCode::Enter => {
+                let editing_custom = self.config.themes.contains_key(&self.config.theme);
+                editor.name = Some(if editing_custom { self.config.theme.clone() } else { String::new() });
+            }
+            _ => {}
+        }
+        let preview = Theme { primary: editor.primary(), dim: editor.dim(), ..self.theme };
+        self.theme = preview.on_background(self.background);
+    }
+}
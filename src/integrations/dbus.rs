@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use zbus::blocking::{connection, Connection};
+use zbus::interface;
+use zbus::zvariant::Value;
+
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::integrations::Integration;
+use crate::live::Live;
+
+// org.rpomodoro.Timer on the session bus, for GNOME extensions and scripts:
+//
+//   busctl --user call org.rpomodoro.Timer /org/rpomodoro/Timer org.rpomodoro.Timer Skip
+//   busctl --user get-property org.rpomodoro.Timer /org/rpomodoro/Timer org.rpomodoro.Timer Remaining
+//
+// Properties change without their new values in PropertiesChanged
+// (invalidated), so listeners read them again when told. EndingSoon(state,
+// remaining_secs) is signalled once "ending_soon_minutes" before a session
+// or break ends.
+pub const BUS_NAME: &str = "org.rpomodoro.Timer";
+pub const OBJECT_PATH: &str = "/org/rpomodoro/Timer";
+const PROPERTIES: [&str; 8] = ["State", "Paused", "Remaining", "RemainingSecs", "Cycle", "CyclesBeforeLong", "CompletedToday", "EndingSoon"];
+
+pub struct DBus {
+    pub live: Live,
+    // Owns the bus name for as long as the integration is kept alive
+    connection: Option<Connection>,
+}
+
+impl DBus {
+    pub fn new(live: Live) -> Self {
+        DBus { live, connection: None }
+    }
+}
+
+impl Integration for DBus {
+    fn name(&self) -> &'static str {
+        "dbus"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        let timer = Timer { live: self.live.clone(), events: Mutex::new(events) };
+        let connection = connection::Builder::session()
+            .and_then(|builder| builder.name(BUS_NAME))
+            .and_then(|builder| builder.serve_at(OBJECT_PATH, timer))
+            .and_then(|builder| builder.build())
+            .map_err(|e| Error::integration(self.name(), e))?;
+
+        let (signals, live) = (connection.clone(), self.live.clone());
+        thread::spawn(move || {
+            let mut seen = live.get();
+            loop {
+                let snapshot = live.wait_change(&seen, Duration::from_secs(60));
+                if snapshot == seen {
+                    continue;
+                }
+                let ending_soon = snapshot.ending_soon && !seen.ending_soon;
+                seen = snapshot;
+                let changed: HashMap<&str, Value> = HashMap::new();
+                let body = (BUS_NAME, changed, PROPERTIES.to_vec());
+                if signals.emit_signal(None::<&str>, OBJECT_PATH, "org.freedesktop.DBus.Properties", "PropertiesChanged", &body).is_err() {
+                    return;
+                }
+                let body = (seen.state, seen.remaining_secs);
+                if ending_soon && signals.emit_signal(None::<&str>, OBJECT_PATH, BUS_NAME, "EndingSoon", &body).is_err() {
+                    return;
+                }
+            }
+        });
+        self.connection = Some(connection);
+        Ok(())
+    }
+}
+
+struct Timer {
+    live: Live,
+    events: Mutex<Sender<AppEvent>>,
+}
+
+impl Timer {
+    fn send(&self, command: Command) -> zbus::fdo::Result<()> {
+        let events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        events.send(AppEvent::Command(command)).map_err(|_| zbus::fdo::Error::Failed("shutting down".to_string()))
+    }
+}
+
+#[interface(name = "org.rpomodoro.Timer")]
+impl Timer {
+    // Resume; does nothing while running
+    fn start(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Resume)
+    }
+
+    fn pause(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Pause)
+    }
+
+    fn toggle(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::TogglePause)
+    }
+
+    fn skip(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Skip)
+    }
+
+    fn extend(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Extend)
+    }
+
+    fn reset(&self) -> zbus::fdo::Result<()> {
+        self.send(Command::Reset)
+    }
+
+    // "work", "short_break" or "long_break"
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn state(&self) -> String {
+        self.live.get().state.to_string()
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn paused(&self) -> bool {
+        self.live.get().paused
+    }
+
+    // mm:ss, as the clock shows it
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn remaining(&self) -> String {
+        self.live.get().remaining
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn remaining_secs(&self) -> u64 {
+        self.live.get().remaining_secs
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn cycle(&self) -> u32 {
+        self.live.get().cycle
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn cycles_before_long(&self) -> u32 {
+        self.live.get().cycles_before_long
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn completed_today(&self) -> u32 {
+        self.live.get().completed_today
+    }
+
+    #[zbus(property(emits_changed_signal = "invalidates"))]
+    fn ending_soon(&self) -> bool {
+        self.live.get().ending_soon
+    }
+}
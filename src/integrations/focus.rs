@@ -0,0 +1,51 @@
+use super::{shell, spawn};
+use crate::error::Result;
+
+// Title rpomodoro gives its terminal window, so window managers can find it
+pub const WINDOW_TITLE: &str = "rpomodoro";
+
+// Built-in "focus_command" templates; anything else is run as a shell
+// command
+pub const FOCUS_PRESETS: [&str; 3] = ["wmctrl", "hyprctl", "osascript"];
+
+// Shell command for a "focus_command" setting, with {title} replaced by the
+// window title. None when the setting is empty.
+pub fn focus_command(setting: &str) -> Option<String> {
+    let template = match setting {
+        "" => return None,
+        "wmctrl" => "wmctrl -a {title}".to_string(),
+        "hyprctl" => "hyprctl dispatch focuswindow title:{title}".to_string(),
+        "osascript" => format!("osascript -e 'tell application \"{}\" to activate'", mac_terminal_app()),
+        custom => custom.to_string(),
+    };
+    Some(template.replace("{title}", WINDOW_TITLE))
+}
+
+// The app to activate on macOS, from what the terminal reports about itself
+fn mac_terminal_app() -> String {
+    match std::env::var("TERM_PROGRAM").as_deref() {
+        Ok("Apple_Terminal") | Err(_) => "Terminal".to_string(),
+        Ok("iTerm.app") => "iTerm".to_string(),
+        Ok(other) => other.trim_end_matches(".app").to_string(),
+    }
+}
+
+// Run the configured focus command, if any, without waiting for it
+pub fn focus_terminal(setting: &str) -> Result<()> {
+    let Some(command) = focus_command(setting) else {
+        return Ok(());
+    };
+    spawn("focus_command", &mut shell(&command))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_and_custom_commands_get_the_title() {
+        assert_eq!(focus_command(""), None);
+        assert_eq!(focus_command("wmctrl").as_deref(), Some("wmctrl -a rpomodoro"));
+        assert_eq!(focus_command("swaymsg '[title={title}] focus'").as_deref(), Some("swaymsg '[title=rpomodoro] focus'"));
+    }
+}
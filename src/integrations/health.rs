@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::{Arc, Mutex};
+
+use crate::error::Result;
+
+// Whether each integration is working, for the statusline and the
+// integrations screen. Cloned into integration threads so failures after
+// start-up are reported too, instead of going silent.
+#[derive(Clone, Default)]
+pub struct Health(Arc<Mutex<BTreeMap<&'static str, Report>>>);
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Report {
+    pub healthy: bool,
+    // Local time (HH:MM:SS) and reason of the most recent failure, kept
+    // after the integration recovers
+    pub last_error: Option<(String, String)>,
+}
+
+impl Health {
+    pub fn ok(&self, name: &'static str) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).entry(name).or_default().healthy = true;
+    }
+
+    pub fn failed(&self, name: &'static str, reason: impl Display) {
+        self.failed_at(name, chrono::Local::now().format("%H:%M:%S").to_string(), reason);
+    }
+
+    // `failed` with a fixed time, for deterministic snapshots
+    pub fn failed_at(&self, name: &'static str, at: String, reason: impl Display) {
+        let mut reports = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let report = reports.entry(name).or_default();
+        report.healthy = false;
+        report.last_error = Some((at, reason.to_string()));
+    }
+
+    // Record the outcome of one attempt and pass it on
+    pub fn track<T>(&self, name: &'static str, result: Result<T>) -> Result<T> {
+        match &result {
+            Ok(_) => self.ok(name),
+            Err(e) => self.failed(name, e),
+        }
+        result
+    }
+
+    // Everything that has reported so far, by name
+    pub fn reports(&self) -> Vec<(&'static str, Report)> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).iter().map(|(&name, report)| (name, report.clone())).collect()
+    }
+
+    pub fn failing(&self) -> Vec<&'static str> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).iter().filter(|(_, report)| !report.healthy).map(|(&name, _)| name).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recovery_keeps_the_last_error() {
+        let health = Health::default();
+        health.failed("remote", "address in use");
+        assert_eq!(health.failing(), ["remote"]);
+        health.ok("remote");
+        assert!(health.failing().is_empty());
+        let (_, report) = &health.reports()[0];
+        assert_eq!(report.last_error.as_ref().map(|(_, reason)| reason.as_str()), Some("address in use"));
+    }
+}
@@ -0,0 +1,38 @@
+use super::{shell, spawn};
+use crate::engine::Transition;
+use crate::error::Result;
+
+// Run a transition hook ("on_work_start", "on_break_start" or
+// "on_session_end") without waiting for it. The command gets what happened
+// in RPOMODORO_FINISHED, RPOMODORO_NEXT ("work", "short_break" or
+// "long_break") and RPOMODORO_SKIPPED ("true" or "false").
+pub fn run_hook(name: &str, command: &str, transition: &Transition) -> Result<()> {
+    let mut shell = shell(command);
+    shell
+        .env("RPOMODORO_FINISHED", transition.finished.name())
+        .env("RPOMODORO_NEXT", transition.next.name())
+        .env("RPOMODORO_SKIPPED", transition.skipped.to_string());
+    spawn(name, &mut shell)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::engine::PomodoroState;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn hooks_are_told_what_happened() {
+        let path = std::env::temp_dir().join(format!("rpomodoro-test-hook-{}", std::process::id()));
+        let transition = Transition { finished: PomodoroState::Work, next: PomodoroState::ShortBreak, skipped: true, counted: Duration::ZERO, extended_minutes: 0, overtime: Duration::ZERO };
+        let command = format!("echo $RPOMODORO_FINISHED $RPOMODORO_NEXT $RPOMODORO_SKIPPED > {}.tmp && mv {0}.tmp {0}", path.display());
+        run_hook("on_session_end", &command, &transition).unwrap();
+
+        let started = Instant::now();
+        while !path.exists() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "work short_break true\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::events::AppEvent;
+use crate::integrations::Integration;
+
+// How often the desktop is asked for its idle time
+const POLL: Duration = Duration::from_secs(10);
+
+// Time since the last key press or mouse move anywhere on the desktop, not
+// only in the terminal, for "idle_pause_minutes". Read from xprintidle on
+// X11 or GNOME's idle monitor (X11 and Wayland) on Linux, ioreg on macOS
+// and GetLastInputInfo on Windows.
+pub struct IdleWatch;
+
+impl Integration for IdleWatch {
+    fn name(&self) -> &'static str {
+        "idle"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        // Asked once up front, so a desktop without a source says so
+        if system_idle().is_none() {
+            return Err(Error::integration(self.name(), "no idle time available: install xprintidle, or use GNOME"));
+        }
+        thread::spawn(move || loop {
+            if let Some(idle) = system_idle() {
+                if events.send(AppEvent::Idle(idle)).is_err() {
+                    return;
+                }
+            }
+            thread::sleep(POLL);
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn system_idle() -> Option<Duration> {
+    if let Some(ms) = output("xprintidle", &[]).and_then(|out| out.trim().parse().ok()) {
+        return Some(Duration::from_millis(ms));
+    }
+    let monitor = ["call", "--session", "--dest", "org.gnome.Mutter.IdleMonitor", "--object-path", "/org/gnome/Mutter/IdleMonitor/Core", "--method", "org.gnome.Mutter.IdleMonitor.GetIdletime"];
+    output("gdbus", &monitor).and_then(|out| parse_gdbus(&out))
+}
+
+#[cfg(target_os = "macos")]
+fn system_idle() -> Option<Duration> {
+    output("ioreg", &["-c", "IOHIDSystem", "-d", "4"]).and_then(|out| parse_ioreg(&out))
+}
+
+#[cfg(all(windows, feature = "control"))]
+fn system_idle() -> Option<Duration> {
+    use windows_sys::Win32::System::SystemInformation::GetTickCount;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+    // SAFETY: `info` is a valid LASTINPUTINFO with its size filled in
+    if unsafe { GetLastInputInfo(&mut info) } == 0 {
+        return None;
+    }
+    // Tick counts wrap every 49 days; the difference doesn't
+    let ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+    Some(Duration::from_millis(ms as u64))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", all(windows, feature = "control"))))]
+fn system_idle() -> Option<Duration> {
+    None
+}
+
+// A helper's stdout, when it ran and succeeded
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos")), allow(dead_code))]
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).stdin(Stdio::null()).stderr(Stdio::null()).output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// "(uint64 12345,)", in milliseconds
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_gdbus(out: &str) -> Option<Duration> {
+    let ms = out.trim().strip_prefix("(uint64 ")?.strip_suffix(",)")?;
+    Some(Duration::from_millis(ms.parse().ok()?))
+}
+
+// The `"HIDIdleTime" = 1234567890` line, in nanoseconds
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_ioreg(out: &str) -> Option<Duration> {
+    let line = out.lines().find(|line| line.contains("\"HIDIdleTime\""))?;
+    Some(Duration::from_nanos(line.rsplit('=').next()?.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_times_are_read_from_each_tool() {
+        assert_eq!(parse_gdbus("(uint64 90500,)\n"), Some(Duration::from_millis(90500)));
+        let ioreg = "    | |   \"HIDIdleCount\" = 0\n    | |   \"HIDIdleTime\" = 2500000000\n";
+        assert_eq!(parse_ioreg(ioreg), Some(Duration::from_millis(2500)));
+        assert_eq!(parse_gdbus("Error: GDBus.Error"), None);
+    }
+}
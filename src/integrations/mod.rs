@@ -0,0 +1,87 @@
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use crate::app::App;
+use crate::error::{Error, Result};
+use crate::events::AppEvent;
+use crate::storage::Dirs;
+
+// Talking to things outside the terminal: desktop notifications and other
+// programs. Integrations with their own dependencies sit behind cargo
+// features and only appear in the registry when compiled in.
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+pub mod dbus;
+pub mod focus;
+pub mod health;
+pub mod hooks;
+pub mod idle;
+pub mod notify;
+pub mod obs;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod sound;
+pub mod speech;
+pub mod summary;
+#[cfg(all(windows, feature = "tray"))]
+pub mod tray;
+pub mod webhook;
+
+// Something that runs alongside the timer and feeds the main loop's event
+// channel. Kept alive until the app exits.
+pub trait Integration {
+    fn name(&self) -> &'static str;
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()>;
+}
+
+// Every integration compiled into this build. Pushed one by one since each
+// entry is feature-gated.
+#[allow(clippy::vec_init_then_push)]
+pub fn registry(dirs: &Dirs, app: &App) -> Vec<Box<dyn Integration>> {
+    #[allow(unused_mut)]
+    let mut integrations: Vec<Box<dyn Integration>> = Vec::new();
+    #[cfg(feature = "control")]
+    integrations.push(Box::new(crate::ipc::ControlChannel(crate::ipc::transport(dirs), app.live.clone())));
+    #[cfg(all(unix, feature = "control"))]
+    integrations.push(Box::new(crate::ipc::signals::Signals));
+    #[cfg(feature = "remote")]
+    if app.config.remote_port != 0 {
+        integrations.push(Box::new(remote::Remote {
+            bind: app.config.remote_bind.clone(),
+            port: app.config.remote_port,
+            token: app.config.remote_token.clone(),
+            live: app.live.clone(),
+            health: app.health.clone(),
+        }));
+    }
+    #[cfg(all(target_os = "linux", feature = "dbus"))]
+    integrations.push(Box::new(dbus::DBus::new(app.live.clone())));
+    #[cfg(all(windows, feature = "tray"))]
+    integrations.push(Box::new(tray::Tray { live: app.live.clone() }));
+    if app.config.idle_pause_minutes > 0 {
+        integrations.push(Box::new(idle::IdleWatch));
+    }
+    #[cfg(not(feature = "control"))]
+    let _ = dirs;
+    integrations
+}
+
+// `command` as the platform's shell would run it
+pub fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+    shell.args([if cfg!(windows) { "/C" } else { "-c" }, command]);
+    shell
+}
+
+// Start a helper program without blocking the main loop. The child is
+// reaped on a throwaway thread.
+pub fn spawn(name: &str, command: &mut Command) -> Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::integration(name, e))?;
+    thread::spawn(move || child.wait());
+    Ok(())
+}
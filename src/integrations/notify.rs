@@ -0,0 +1,155 @@
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+use super::spawn;
+use crate::error::Result;
+
+// Shows a desktop notification. Implementations return once the
+// notification is handed off, never waiting for the user to see it.
+pub trait Notifier {
+    fn name(&self) -> &'static str;
+    fn notify(&self, title: &str, body: &str) -> Result<()>;
+}
+
+// The backend for a `notifier` setting. "auto" picks the native one for
+// this platform when its tool is installed, else the terminal fallback.
+pub fn notifier_for(setting: &str) -> Box<dyn Notifier> {
+    match setting {
+        "notify-send" => Box::new(NotifySend),
+        "osascript" => Box::new(Osascript),
+        "windows" => Box::new(WindowsToast),
+        "terminal" => Box::new(TerminalEscape),
+        "none" => Box::new(Silent),
+        _ => {
+            if cfg!(target_os = "macos") && on_path("osascript") {
+                Box::new(Osascript)
+            } else if cfg!(windows) && on_path("powershell.exe") {
+                Box::new(WindowsToast)
+            } else if cfg!(unix) && on_path("notify-send") {
+                Box::new(NotifySend)
+            } else {
+                Box::new(TerminalEscape)
+            }
+        }
+    }
+}
+
+pub fn on_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| env::split_paths(&paths).any(|dir| is_file(&dir.join(program))))
+}
+
+fn is_file(path: &Path) -> bool {
+    path.metadata().map(|m| m.is_file()).unwrap_or(false)
+}
+
+// Linux and BSD desktops, through libnotify's CLI (talks to DBus for us)
+pub struct NotifySend;
+
+impl Notifier for NotifySend {
+    fn name(&self) -> &'static str {
+        "notify-send"
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        spawn(self.name(), Command::new("notify-send").args(["--app-name=rpomodoro", title, body]))
+    }
+}
+
+// macOS Notification Center
+pub struct Osascript;
+
+impl Notifier for Osascript {
+    fn name(&self) -> &'static str {
+        "osascript"
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        let script = format!("display notification {} with title {}", applescript_string(body), applescript_string(title));
+        spawn(self.name(), Command::new("osascript").args(["-e", &script]))
+    }
+}
+
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Windows 10+ toasts via the WinRT API from PowerShell, which needs no
+// extra modules
+pub struct WindowsToast;
+
+impl Notifier for WindowsToast {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        let script = format!(
+            "[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+             $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+             $text = $xml.GetElementsByTagName('text'); \
+             $text.Item(0).AppendChild($xml.CreateTextNode({})) > $null; \
+             $text.Item(1).AppendChild($xml.CreateTextNode({})) > $null; \
+             [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('rpomodoro').Show([Windows.UI.Notifications.ToastNotification]::new($xml))",
+            powershell_string(title),
+            powershell_string(body),
+        );
+        spawn(self.name(), Command::new("powershell.exe").args(["-NoProfile", "-NonInteractive", "-Command", &script]))
+    }
+}
+
+pub fn powershell_string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+// OSC 777 (urxvt, foot, VTE terminals) and OSC 9 (iTerm2, Windows Terminal,
+// kitty). Terminals that know neither ignore both.
+pub struct TerminalEscape;
+
+impl Notifier for TerminalEscape {
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+
+    fn notify(&self, title: &str, body: &str) -> Result<()> {
+        let clean = |text: &str| text.chars().filter(|c| !c.is_control() && *c != ';').collect::<String>();
+        let (title, body) = (clean(title), clean(body));
+        let mut stdout = io::stdout();
+        write!(stdout, "\x1b]777;notify;{};{}\x07\x1b]9;{}: {}\x07", title, body, title, body)?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+// `"notifier": "none"`
+pub struct Silent;
+
+impl Notifier for Silent {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn notify(&self, _title: &str, _body: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NOTIFIERS;
+
+    #[test]
+    fn quotes_survive_script_strings() {
+        assert_eq!(applescript_string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+        assert_eq!(powershell_string("it's done"), "'it''s done'");
+    }
+
+    #[test]
+    fn explicit_setting_wins() {
+        for name in NOTIFIERS.iter().filter(|&&n| n != "auto") {
+            assert_eq!(notifier_for(name).name(), *name);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use crate::app::App;
+use crate::error::Result;
+use crate::render::segments::find;
+use crate::storage::write_export;
+
+impl App {
+    // Keep the OBS badge file in step with the timer. It's only rewritten
+    // when its text changes, i.e. about once a second while running. After a
+    // failed write it stays off until the settings are saved again, so a bad
+    // path doesn't flood the statusline with errors.
+    pub fn export_badge(&mut self) -> Result<()> {
+        if self.config.badge_file.is_empty() || self.badge_failed || self.offline {
+            return Ok(());
+        }
+        let text = render_badge(&self.config.badge_template, self);
+        if text == self.last_badge {
+            return Ok(());
+        }
+        if let Err(e) = self.health.track("obs badge", write_export(Path::new(&self.config.badge_file), &text)) {
+            self.badge_failed = true;
+            return Err(e);
+        }
+        self.last_badge = text;
+        Ok(())
+    }
+}
+
+// Fill `{name}` placeholders with the statusline segment of that name,
+// e.g. "{mode} {time}". Unknown names are left as typed.
+pub fn render_badge(template: &str, app: &App) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let name = &after[..end];
+        match find(name) {
+            Some(segment) => out.push_str(&segment.text(app).unwrap_or_default()),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn badge_template_fills_segments() {
+        let app = App::for_test(Config::default());
+        assert_eq!(render_badge("{mode} {time} {nope} {", &app), "work 25:00 {nope} {");
+        assert_eq!(render_badge("🍅 {cycles}", &app), "🍅 cycles: 0/4");
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use super::query_param;
+
+const PAGE: &str = include_str!("dashboard.html");
+
+// Plain HTTP on the remote port: the dashboard page at /, which talks to
+// the WebSocket endpoint for everything else
+pub fn serve(stream: TcpStream, target: &str, token: &str) -> io::Result<()> {
+    // Drain the request so closing the socket doesn't reset it under the
+    // response
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, content_type, body) = if path != "/" {
+        ("404 Not Found", "text/plain", "not found")
+    } else if !token.is_empty() && query_param(query, "token") != Some(token) {
+        ("401 Unauthorized", "text/plain", "open this page with ?token=<remote_token>")
+    } else {
+        ("200 OK", "text/html; charset=utf-8", PAGE)
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
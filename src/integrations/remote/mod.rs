@@ -0,0 +1,133 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::integrations::health::Health;
+use crate::integrations::Integration;
+use crate::live::Live;
+
+mod dashboard;
+mod websocket;
+
+// Line-based control over TCP for Stream Deck plugins, macro pads and
+// scripts on other machines. Each request line gets one reply line:
+//
+//   auth <token>  required first when "remote_token" is set
+//   status        the current state as a JSON object
+//   subscribe     a JSON object now and on every change, until disconnect
+//   toggle, pause, resume, skip, extend, reset, quit
+//                 "ok" or "error: <reason>"
+//
+// The same port speaks HTTP for browsers: a dashboard page at / and a
+// WebSocket at /ws.
+pub struct Remote {
+    pub bind: String,
+    pub port: u16,
+    pub token: String,
+    pub live: Live,
+    pub health: Health,
+}
+
+impl Integration for Remote {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        let listener = TcpListener::bind((self.bind.as_str(), self.port)).map_err(|e| Error::integration(self.name(), e))?;
+        let (name, token, live, health) = (self.name(), self.token.clone(), self.live.clone(), self.health.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        health.ok(name);
+                        let (token, live, events) = (token.clone(), live.clone(), events.clone());
+                        thread::spawn(move || handle(stream, &token, &live, &events));
+                    }
+                    Err(e) => health.failed(name, format!("accepting a connection: {}", e)),
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+fn handle(stream: TcpStream, token: &str, live: &Live, events: &Sender<AppEvent>) -> io::Result<()> {
+    // HTTP clients are told apart by their request line
+    let mut start = [0u8; 512];
+    let peeked = stream.peek(&mut start)?;
+    if let Some(request) = start[..peeked].strip_prefix(b"GET ") {
+        let target = request.split(|&b| b == b' ').next().unwrap_or_default();
+        let target = String::from_utf8_lossy(target).into_owned();
+        return match target.split('?').next() {
+            Some("/ws") => websocket::serve(stream, token, live, events),
+            _ => dashboard::serve(stream, &target, token),
+        };
+    }
+    // Any other HTTP request, like a form a web page posts here, is turned
+    // away before its body could be read as commands
+    let first_line = String::from_utf8_lossy(start[..peeked].split(|&b| b == b'\n').next().unwrap_or_default()).into_owned();
+    if first_line.trim_end().rsplit(' ').next().is_some_and(|version| version.starts_with("HTTP/")) {
+        let mut stream = stream;
+        return write!(stream, "HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+    }
+
+    let reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut authenticated = token.is_empty();
+
+    for line in reader.lines() {
+        let line = line?;
+        let request = line.trim();
+        if request.is_empty() {
+            continue;
+        }
+        if !authenticated {
+            if request.strip_prefix("auth ") != Some(token) {
+                return writeln!(writer, "error: auth required");
+            }
+            authenticated = true;
+            writeln!(writer, "ok")?;
+            continue;
+        }
+
+        let reply = match request {
+            "status" => snapshot_json(live),
+            "subscribe" => return subscribe(writer, live),
+            name => run_command(name, events),
+        };
+        writeln!(writer, "{}", reply)?;
+    }
+    Ok(())
+}
+
+// "ok" or "error: <reason>"
+fn run_command(name: &str, events: &Sender<AppEvent>) -> String {
+    match Command::from_name(name) {
+        Some(command) if events.send(AppEvent::Command(command)).is_ok() => "ok".to_string(),
+        Some(_) => "error: shutting down".to_string(),
+        None => format!("error: unknown command {:?}", name),
+    }
+}
+
+fn snapshot_json(live: &Live) -> String {
+    serde_json::to_string(&live.get()).unwrap_or_default()
+}
+
+// Push every change. An unchanged state is resent every half minute, which
+// is also how a vanished client gets noticed.
+fn subscribe(mut writer: TcpStream, live: &Live) -> io::Result<()> {
+    let mut seen = live.get();
+    loop {
+        writeln!(writer, "{}", serde_json::to_string(&seen).unwrap_or_default())?;
+        seen = live.wait_change(&seen, Duration::from_secs(30));
+    }
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+}
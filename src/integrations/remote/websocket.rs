@@ -0,0 +1,129 @@
+use serde_json::json;
+use std::io;
+use std::net::TcpStream;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::{accept_hdr, Message, WebSocket};
+
+use super::{query_param, run_command};
+use crate::events::AppEvent;
+use crate::live::{Live, Snapshot};
+
+// WebSocket endpoint at /ws, with the token as `?token=` when one is set.
+// Pushes {"event": "state" | "tick", "state": {...}} on every change:
+// "tick" when only the clock moved, "state" for anything else.
+// Text messages are control commands, answered with
+// {"event": "reply", "reply": "ok" | "error: ..."}.
+// The handshake callback's error type is tungstenite's
+#[allow(clippy::result_large_err)]
+pub fn serve(stream: TcpStream, token: &str, live: &Live, events: &Sender<AppEvent>) -> io::Result<()> {
+    // Generous for the handshake, then short so pushes aren't held up by a
+    // quiet client
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let check = |request: &Request, response: Response| {
+        let uri = request.uri();
+        if uri.path() != "/ws" {
+            return Err(error_response(StatusCode::NOT_FOUND));
+        }
+        if !token.is_empty() && query_param(uri.query().unwrap_or(""), "token") != Some(token) {
+            return Err(error_response(StatusCode::UNAUTHORIZED));
+        }
+        if !same_origin(request) {
+            return Err(error_response(StatusCode::FORBIDDEN));
+        }
+        Ok(response)
+    };
+    let mut socket = accept_hdr(stream, check).map_err(|e| io::Error::other(e.to_string()))?;
+    socket.get_mut().set_read_timeout(Some(Duration::from_millis(100)))?;
+
+    let mut seen = live.get();
+    push(&mut socket, "state", &seen)?;
+    loop {
+        let next = live.wait_change(&seen, Duration::from_millis(100));
+        if next != seen {
+            push(&mut socket, if only_time(&seen, &next) { "tick" } else { "state" }, &next)?;
+            seen = next;
+        }
+
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let reply = json!({ "event": "reply", "reply": run_command(text.trim(), events) });
+                socket.send(Message::Text(reply.to_string())).map_err(io::Error::other)?;
+            }
+            Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e)) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(io::Error::other(e)),
+        }
+    }
+}
+
+// Browsers say which page opened the socket. Only the dashboard, served
+// from this same host and port, is let in, so other web pages can't drive
+// the timer; clients that aren't browsers send no origin.
+fn same_origin(request: &Request) -> bool {
+    let header = |name| request.headers().get(name).and_then(|value| value.to_str().ok());
+    let Some(origin) = header("origin") else {
+        return true;
+    };
+    let authority = origin.split_once("://").map(|(_, authority)| authority);
+    authority.is_some_and(|authority| header("host").is_some_and(|host| authority.eq_ignore_ascii_case(host)))
+}
+
+// Whether only the clock moved: the time left, or the time counted up by a
+// stopwatch or overtime that was already running
+fn only_time(seen: &Snapshot, next: &Snapshot) -> bool {
+    let counted = |seen: Option<u64>, next: Option<u64>| if seen.is_some() == next.is_some() { seen } else { next };
+    let moved = Snapshot {
+        remaining_secs: seen.remaining_secs,
+        remaining: seen.remaining.clone(),
+        stopwatch_secs: counted(seen.stopwatch_secs, next.stopwatch_secs),
+        overtime_secs: counted(seen.overtime_secs, next.overtime_secs),
+        ..next.clone()
+    };
+    moved == *seen
+}
+
+fn push(socket: &mut WebSocket<TcpStream>, event: &str, snapshot: &Snapshot) -> io::Result<()> {
+    let message = json!({ "event": event, "state": snapshot });
+    socket.send(Message::Text(message.to_string())).map_err(io::Error::other)
+}
+
+fn error_response(status: StatusCode) -> ErrorResponse {
+    let mut response = ErrorResponse::new(status.canonical_reason().map(str::to_string));
+    *response.status_mut() = status;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn other_pages_are_turned_away() {
+        let request = |origin: Option<&str>| {
+            let mut builder = Request::builder().uri("/ws").header("Host", "127.0.0.1:47123");
+            if let Some(origin) = origin {
+                builder = builder.header("Origin", origin);
+            }
+            builder.body(()).unwrap()
+        };
+        assert!(same_origin(&request(None)));
+        assert!(same_origin(&request(Some("http://127.0.0.1:47123"))));
+        assert!(!same_origin(&request(Some("https://example.com"))));
+        assert!(!same_origin(&request(Some("null"))));
+    }
+
+    #[test]
+    fn counting_up_is_a_tick() {
+        let seen = Snapshot { remaining_secs: 600, remaining: "10:00".to_string(), stopwatch_secs: Some(60), ..Snapshot::default() };
+        let counted = Snapshot { stopwatch_secs: Some(61), remaining: "01:01".to_string(), ..seen.clone() };
+        let stopped = Snapshot { stopwatch_secs: None, ..seen.clone() };
+        let paused = Snapshot { paused: true, ..counted.clone() };
+        assert!(only_time(&seen, &counted));
+        assert!(!only_time(&seen, &stopped));
+        assert!(!only_time(&seen, &paused));
+    }
+}
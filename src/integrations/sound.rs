@@ -0,0 +1,64 @@
+use std::path::Path;
+use std::process::Command;
+
+use super::notify::on_path;
+use super::spawn;
+use crate::error::{Error, Result};
+
+// Chimes on state changes, played by the platform's own command-line player
+// like notifications are, so there's no audio library to link and nothing to
+// fail at startup on machines without sound
+pub fn play(path: &Path, volume: u32) -> Result<()> {
+    if !path.is_file() {
+        return Err(Error::integration("sound", format!("{}: no such file", path.display())));
+    }
+    let volume = volume.min(100);
+    let file = path.as_os_str();
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("afplay");
+        command.arg("-v").arg(format!("{:.2}", volume as f32 / 100.0)).arg(file);
+        command
+    } else if cfg!(windows) {
+        let script = format!("(New-Object Media.SoundPlayer '{}').PlaySync()", path.display().to_string().replace('\'', "''"));
+        let mut command = Command::new("powershell.exe");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    } else if on_path("paplay") {
+        let mut command = Command::new("paplay");
+        command.arg(format!("--volume={}", volume * 65536 / 100)).arg(file);
+        command
+    } else if on_path("pw-play") {
+        let mut command = Command::new("pw-play");
+        command.arg(format!("--volume={:.2}", volume as f32 / 100.0)).arg(file);
+        command
+    } else if on_path("aplay") {
+        // No volume control, and only WAV files
+        let mut command = Command::new("aplay");
+        command.arg("-q").arg(file);
+        command
+    } else {
+        return Err(Error::integration("sound", "no audio player found (paplay, pw-play or aplay)"));
+    };
+    spawn("sound", &mut command)
+}
+
+// Chime used when no sound file is configured for a transition
+pub fn default_sound() -> &'static Path {
+    Path::new(if cfg!(target_os = "macos") {
+        "/System/Library/Sounds/Glass.aiff"
+    } else if cfg!(windows) {
+        r"C:\Windows\Media\chimes.wav"
+    } else {
+        "/usr/share/sounds/freedesktop/stereo/complete.oga"
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_files_are_an_error() {
+        assert!(play(Path::new("/nonexistent/chime.oga"), 80).is_err());
+    }
+}
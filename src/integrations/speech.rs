@@ -0,0 +1,31 @@
+use std::process::Command;
+
+use super::notify::{on_path, powershell_string};
+use super::spawn;
+use crate::error::{Error, Result};
+
+// Reads an alert aloud (the "tts" alert sink) with the platform's speech
+// command, like sounds and notifications
+pub fn speak(text: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("say");
+        command.arg(text);
+        command
+    } else if cfg!(windows) {
+        let script = format!("Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak({})", powershell_string(text));
+        let mut command = Command::new("powershell.exe");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    } else if on_path("spd-say") {
+        let mut command = Command::new("spd-say");
+        command.arg("--").arg(text);
+        command
+    } else if on_path("espeak") {
+        let mut command = Command::new("espeak");
+        command.arg("--").arg(text);
+        command
+    } else {
+        return Err(Error::integration("speech", "no speech command found (spd-say or espeak)"));
+    };
+    spawn("speech", &mut command)
+}
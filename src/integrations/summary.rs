@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::spawn;
+use crate::config::WeeklySummaryConfig;
+use crate::error::{Error, Result};
+
+// The weekly summary's targets, reached with curl like the webhooks
+
+// A message to an ntfy topic URL, e.g. "https://ntfy.sh/my-pomodoros"
+pub fn ntfy(url: &str, title: &str, body: &str) -> Result<()> {
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--max-time", "10", "--header", &format!("Title: {}", title), "--data", body, "--", url]);
+    spawn("weekly summary", &mut command)
+}
+
+// A plain text email through "smtp_url", e.g. "smtps://smtp.example.com:465"
+pub fn email(config: &WeeklySummaryConfig, subject: &str, body: &str) -> Result<()> {
+    if config.mail_from.is_empty() || config.mail_to.is_empty() {
+        return Err(Error::integration("weekly summary", "email needs \"mail_from\" and \"mail_to\""));
+    }
+    let message = format!("From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n{}", config.mail_from, config.mail_to, subject, body.replace('\n', "\r\n"));
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--max-time", "30", "--url", &config.smtp_url, "--mail-from", &config.mail_from, "--mail-rcpt", &config.mail_to, "--upload-file", "-"]);
+    let credentials = match config.smtp_user.is_empty() {
+        true => None,
+        false => Some(credentials_file(&config.smtp_user, &config.smtp_password)?),
+    };
+    if let Some(path) = &credentials {
+        command.arg("--config").arg(path);
+    }
+    // The message goes in on stdin, written and waited for on a throwaway
+    // thread, which also removes the credentials once curl is done
+    let spawned = command.stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::null()).spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => {
+            if let Some(path) = &credentials {
+                let _ = fs::remove_file(path);
+            }
+            return Err(Error::integration("weekly summary", e));
+        }
+    };
+    let mut stdin = child.stdin.take();
+    thread::spawn(move || {
+        if let Some(stdin) = &mut stdin {
+            let _ = stdin.write_all(message.as_bytes());
+        }
+        drop(stdin);
+        let _ = child.wait();
+        if let Some(path) = &credentials {
+            let _ = fs::remove_file(path);
+        }
+    });
+    Ok(())
+}
+
+// A curl config file with the SMTP login, readable only by us. On curl's
+// command line the password would show in ps and /proc/*/cmdline.
+fn credentials_file(user: &str, password: &str) -> Result<PathBuf> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_nanos());
+    let path = std::env::temp_dir().join(format!("rpomodoro-smtp-{}-{}", std::process::id(), nanos));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "user = \"{}\"", quoted(&format!("{}:{}", user, password))))
+        .map_err(|e| Error::io(&path, e))?;
+    Ok(path)
+}
+
+// Escaped for a double-quoted value in a curl config file
+fn quoted(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n").replace('\r', "\\r")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credentials_stay_off_the_command_line() {
+        let path = credentials_file("me@example.com", "p\"ss\\word").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "user = \"me@example.com:p\\\"ss\\\\word\"\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use std::ptr;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+use windows_sys::Win32::UI::WindowsAndMessaging::{DispatchMessageW, PeekMessageW, TranslateMessage, MSG, PM_REMOVE};
+
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::integrations::Integration;
+use crate::live::{Live, Snapshot};
+
+// An icon in the notification area, for a terminal that's minimized: the
+// state and time left in its tooltip, and Pause/Skip in its menu. The icon
+// belongs to the thread that made it, which also runs its messages, so
+// everything happens on one thread that polls for changes in between.
+pub struct Tray {
+    pub live: Live,
+}
+
+const POLL: Duration = Duration::from_millis(200);
+
+impl Integration for Tray {
+    fn name(&self) -> &'static str {
+        "tray"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        let (ready, built) = mpsc::channel();
+        let live = self.live.clone();
+        thread::spawn(move || {
+            let pause = MenuItem::new("Pause", true, None);
+            let skip = MenuItem::new("Skip", true, None);
+            let mut seen = live.get();
+            let tray = match build(&seen, &pause, &skip) {
+                Ok(tray) => tray,
+                Err(e) => return ready.send(Err(e)).unwrap_or(()),
+            };
+            let _ = ready.send(Ok(()));
+            loop {
+                pump_messages();
+                while let Ok(event) = MenuEvent::receiver().try_recv() {
+                    let command = if event.id == *pause.id() { Command::TogglePause } else { Command::Skip };
+                    if events.send(AppEvent::Command(command)).is_err() {
+                        return;
+                    }
+                }
+                let snapshot = live.wait_change(&seen, POLL);
+                if snapshot != seen {
+                    if snapshot.state != seen.state {
+                        let _ = tray.set_icon(Some(icon(snapshot.state)));
+                    }
+                    let _ = tray.set_tooltip(Some(tooltip(&snapshot)));
+                    pause.set_text(if snapshot.paused { "Resume" } else { "Pause" });
+                    seen = snapshot;
+                }
+            }
+        });
+        built.recv().unwrap_or_else(|_| Err(Error::integration(self.name(), "the tray thread stopped")))
+    }
+}
+
+fn build(snapshot: &Snapshot, pause: &MenuItem, skip: &MenuItem) -> Result<TrayIcon> {
+    let menu = Menu::new();
+    menu.append_items(&[pause, skip]).map_err(|e| Error::integration("tray", e))?;
+    pause.set_text(if snapshot.paused { "Resume" } else { "Pause" });
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(tooltip(snapshot))
+        .with_icon(icon(snapshot.state))
+        .build()
+        .map_err(|e| Error::integration("tray", e))
+}
+
+// Messages for the icon and its menu, without waiting for more
+fn pump_messages() {
+    // SAFETY: MSG is plain data, filled in by PeekMessageW before it's read
+    let mut msg: MSG = unsafe { std::mem::zeroed() };
+    // SAFETY: a null window takes every message for this thread
+    while unsafe { PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_REMOVE) } != 0 {
+        // SAFETY: msg was just filled in by PeekMessageW
+        unsafe {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+}
+
+// Like the waybar tooltip: "rpomodoro: work 12:34", "(paused)" after it
+fn tooltip(snapshot: &Snapshot) -> String {
+    let paused = if snapshot.paused { " (paused)" } else { "" };
+    format!("rpomodoro: {} {}{}", snapshot.state, snapshot.remaining, paused)
+}
+
+// A filled circle, red for work and green for breaks
+fn icon(state: &str) -> Icon {
+    const SIZE: u32 = 32;
+    let color = if state == "work" { [0xd9, 0x3f, 0x2c] } else { [0x3c, 0xa3, 0x5a] };
+    let radius = SIZE as f32 / 2.0 - 1.0;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let (dx, dy) = (x as f32 + 0.5 - SIZE as f32 / 2.0, y as f32 + 0.5 - SIZE as f32 / 2.0);
+            let alpha = ((radius - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0) * 255.0) as u8;
+            rgba.extend_from_slice(&[color[0], color[1], color[2], alpha]);
+        }
+    }
+    Icon::from_rgba(rgba, SIZE, SIZE).expect("a square RGBA image")
+}
@@ -0,0 +1,28 @@
+use serde_json::{json, Value};
+use std::process::Command;
+
+use super::spawn;
+use crate::error::{Error, Result};
+
+// POSTs an alert as JSON to "webhook_url" (the "webhook" alert sink), e.g.
+// {"event": "work_end", "title": "rpomodoro", "body": "..."}. Sent with
+// curl, which ships with macOS, Windows 10 and most Linux systems, so
+// there's no HTTP client to link.
+pub fn post(url: &str, event: &str, title: &str, body: &str) -> Result<()> {
+    if url.is_empty() {
+        return Err(Error::integration("webhook", "no \"webhook_url\" is set"));
+    }
+    send("webhook", url, &json!({ "event": event, "title": title, "body": body }))
+}
+
+// POSTs a timer event to "event_webhook_url" (App::timer_event)
+pub fn post_event(url: &str, payload: &Value) -> Result<()> {
+    send("event webhook", url, payload)
+}
+
+fn send(name: &str, url: &str, payload: &Value) -> Result<()> {
+    let payload = payload.to_string();
+    let mut command = Command::new("curl");
+    command.args(["--silent", "--max-time", "10", "--header", "Content-Type: application/json", "--data", &payload, "--", url]);
+    spawn(name, &mut command)
+}
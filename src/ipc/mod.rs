@@ -0,0 +1,68 @@
+use std::io;
+use std::sync::mpsc::Sender;
+
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::integrations::Integration;
+use crate::live::Live;
+use crate::storage::Dirs;
+
+#[cfg(unix)]
+pub mod signals;
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
+
+// Local control channel between a running timer and `rpomodoro <command>`.
+// The protocol is one request line per connection, answered with one line:
+// "ok" or "error: <reason>", or for "status" the state as a JSON object.
+// Attached TUIs and `rpomodoro status` poll that.
+pub trait Ipc {
+    // Start accepting requests on a background thread, forwarding commands
+    // into the main loop's event channel
+    fn serve(&self, events: Sender<AppEvent>, live: Live) -> io::Result<()>;
+    // Send one request to a running instance and return its reply
+    fn request(&self, line: &str) -> io::Result<String>;
+}
+
+// A Unix socket, or a named pipe on Windows
+pub fn transport(dirs: &Dirs) -> Box<dyn Ipc> {
+    #[cfg(unix)]
+    {
+        let dir = ::dirs::runtime_dir().unwrap_or_else(|| dirs.state.clone());
+        Box::new(unix::UnixSocket::new(dir.join("rpomodoro.sock")))
+    }
+    #[cfg(windows)]
+    {
+        let _ = dirs;
+        Box::new(windows::NamedPipe::new())
+    }
+}
+
+// Serves the Ipc transport for as long as the integration is kept alive
+pub struct ControlChannel(pub Box<dyn Ipc>, pub Live);
+
+impl Integration for ControlChannel {
+    fn name(&self) -> &'static str {
+        "control channel"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        self.0.serve(events, self.1.clone()).map_err(|e| Error::integration(self.name(), e))
+    }
+}
+
+// Answer one request line on behalf of the running timer
+fn respond(line: &str, events: &Sender<AppEvent>, live: &Live) -> String {
+    if line.trim() == "status" {
+        return serde_json::to_string(&live.get()).unwrap_or_default();
+    }
+    match Command::from_name(line.trim()) {
+        Some(command) => match events.send(AppEvent::Command(command)) {
+            Ok(()) => "ok".to_string(),
+            Err(_) => "error: shutting down".to_string(),
+        },
+        None => format!("error: unknown command {:?}", line.trim()),
+    }
+}
@@ -0,0 +1,31 @@
+use signal_hook::consts::{SIGUSR1, SIGUSR2};
+use signal_hook::iterator::Signals as SignalIterator;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use crate::error::{Error, Result};
+use crate::events::{AppEvent, Command};
+use crate::integrations::Integration;
+
+// SIGUSR1 toggles pause and SIGUSR2 skips, so a window manager keybinding
+// can drive a running timer with `pkill -USR1 rpomodoro`
+pub struct Signals;
+
+impl Integration for Signals {
+    fn name(&self) -> &'static str {
+        "signals"
+    }
+
+    fn start(&mut self, events: Sender<AppEvent>) -> Result<()> {
+        let mut signals = SignalIterator::new([SIGUSR1, SIGUSR2]).map_err(|e| Error::integration(self.name(), e))?;
+        thread::spawn(move || {
+            for signal in signals.forever() {
+                let command = if signal == SIGUSR1 { Command::TogglePause } else { Command::Skip };
+                if events.send(AppEvent::Command(command)).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(())
+    }
+}
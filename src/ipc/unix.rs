@@ -0,0 +1,72 @@
+use std::cell::Cell;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use super::{respond, Ipc};
+use crate::events::AppEvent;
+use crate::live::Live;
+
+pub struct UnixSocket {
+    path: PathBuf,
+    // Set once this process owns the socket file, so it's removed on exit
+    serving: Cell<bool>,
+}
+
+impl UnixSocket {
+    pub fn new(path: PathBuf) -> Self {
+        UnixSocket { path, serving: Cell::new(false) }
+    }
+}
+
+impl Ipc for UnixSocket {
+    fn serve(&self, events: Sender<AppEvent>, live: Live) -> io::Result<()> {
+        // A socket file nobody answers on is left over from a crash
+        if self.path.exists() {
+            if UnixStream::connect(&self.path).is_ok() {
+                return Err(io::Error::new(io::ErrorKind::AddrInUse, "another rpomodoro is already running"));
+            }
+            fs::remove_file(&self.path)?;
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let listener = UnixListener::bind(&self.path)?;
+        self.serving.set(true);
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = handle(stream, &events, &live);
+            }
+        });
+        Ok(())
+    }
+
+    fn request(&self, line: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(&self.path)?;
+        stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+        writeln!(stream, "{}", line)?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        Ok(reply.trim_end().to_string())
+    }
+}
+
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        if self.serving.get() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn handle(stream: UnixStream, events: &Sender<AppEvent>, live: &Live) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    writeln!(&stream, "{}", respond(&line, events, live))
+}
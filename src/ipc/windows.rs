@@ -0,0 +1,90 @@
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::io::{AsRawHandle, FromRawHandle};
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_PIPE_CONNECTED, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+use windows_sys::Win32::System::Pipes::{ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT};
+
+use super::{respond, Ipc};
+use crate::events::AppEvent;
+use crate::live::Live;
+
+pub struct NamedPipe {
+    name: String,
+}
+
+impl NamedPipe {
+    // One pipe per user, so people sharing a machine don't drive each
+    // other's timer
+    pub fn new() -> Self {
+        let user = std::env::var("USERNAME").unwrap_or_default();
+        NamedPipe { name: format!(r"\\.\pipe\rpomodoro-{}", user) }
+    }
+
+    // A new server instance of the pipe. Only the first may claim the name,
+    // which is how a second rpomodoro notices the first.
+    fn create(&self, first: bool) -> io::Result<File> {
+        let name: Vec<u16> = OsStr::new(&self.name).encode_wide().chain(Some(0)).collect();
+        let open_mode = if first { PIPE_ACCESS_DUPLEX | FILE_FLAG_FIRST_PIPE_INSTANCE } else { PIPE_ACCESS_DUPLEX };
+        let handle = unsafe {
+            CreateNamedPipeW(
+                name.as_ptr(),
+                open_mode,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                PIPE_UNLIMITED_INSTANCES,
+                512,
+                512,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            let error = io::Error::last_os_error();
+            if first && error.raw_os_error() == Some(ERROR_ACCESS_DENIED as i32) {
+                return Err(io::Error::new(io::ErrorKind::AddrInUse, "another rpomodoro is already running"));
+            }
+            return Err(error);
+        }
+        // The File owns the handle from here and closes it on drop
+        Ok(unsafe { File::from_raw_handle(handle) })
+    }
+}
+
+impl Ipc for NamedPipe {
+    fn serve(&self, events: Sender<AppEvent>, live: Live) -> io::Result<()> {
+        let mut pipe = self.create(true)?;
+        let server = NamedPipe { name: self.name.clone() };
+        thread::spawn(move || loop {
+            let connected = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) } != 0
+                || io::Error::last_os_error().raw_os_error() == Some(ERROR_PIPE_CONNECTED as i32);
+            if connected {
+                let _ = handle(&pipe, &events, &live);
+            }
+            pipe = match server.create(false) {
+                Ok(pipe) => pipe,
+                Err(_) => return,
+            };
+        });
+        Ok(())
+    }
+
+    fn request(&self, line: &str) -> io::Result<String> {
+        let mut pipe = OpenOptions::new().read(true).write(true).open(&self.name)?;
+        writeln!(pipe, "{}", line)?;
+        let mut reply = String::new();
+        BufReader::new(pipe).read_line(&mut reply)?;
+        Ok(reply.trim_end().to_string())
+    }
+}
+
+fn handle(mut pipe: &File, events: &Sender<AppEvent>, live: &Live) -> io::Result<()> {
+    let mut line = String::new();
+    BufReader::new(pipe).read_line(&mut line)?;
+    writeln!(pipe, "{}", respond(&line, events, live))?;
+    pipe.flush()
+}
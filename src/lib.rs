@@ -0,0 +1,82 @@
+// The pomodoro engine: config, the timer's state machine (App), theming,
+// stats and history, and the renderer and integrations built on them. The
+// rpomodoro binary is a thin terminal front end over this crate.
+//
+// Built for wasm32 only the engine, the config schema and the wasm interface
+// are left; everything else needs a terminal or the OS.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod activity;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ambient;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod background;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod backup;
+pub mod config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod countdown;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crash;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod crypto;
+#[cfg(all(feature = "control", not(target_arch = "wasm32")))]
+pub mod daemon;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod debug_keys;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod doctor;
+pub mod duration;
+pub mod engine;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod escalation;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod events;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
+pub mod flow;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod history;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod input;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod integrations;
+#[cfg(all(feature = "control", not(target_arch = "wasm32")))]
+pub mod ipc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod live;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod locale;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod navigation;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod palette;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recording;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod render;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod report;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod scheduler;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod session;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod settings;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod simulate;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stats;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod status;
+pub mod storage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tasks;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod theme;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod version;
+#[cfg(feature = "wasm")]
+pub mod wasm;
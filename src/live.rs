@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::app::{App, PomodoroState};
+use crate::error::{Error, Result};
+use crate::stats::Stats;
+
+// What outside clients see of the timer. The main loop publishes one per
+// frame; readers on other threads get a copy, or wait for the next change.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub state: &'static str,
+    pub paused: bool,
+    pub remaining_secs: u64,
+    // mm:ss, as the big clock shows it
+    pub remaining: String,
+    // Time counted up, in stopwatch mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopwatch_secs: Option<u64>,
+    // Time past the end of a session that runs over
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overtime_secs: Option<u64>,
+    pub cycle: u32,
+    pub cycles_before_long: u32,
+    pub extended_minutes: u32,
+    pub completed_today: u32,
+    pub daily_goal: u32,
+    // Within "ending_soon_minutes" of the end
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ending_soon: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct Live(Arc<(Mutex<Snapshot>, Condvar)>);
+
+impl Live {
+    // Replace the snapshot, waking waiters only if something changed
+    pub fn publish(&self, snapshot: Snapshot) {
+        let (current, changed) = &*self.0;
+        let mut current = current.lock().unwrap_or_else(|e| e.into_inner());
+        if *current != snapshot {
+            *current = snapshot;
+            changed.notify_all();
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "control", feature = "remote", feature = "dbus", feature = "tray")), allow(dead_code))]
+    pub fn get(&self) -> Snapshot {
+        self.0 .0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    // The next snapshot that differs from `seen`, or the current one after
+    // `timeout`
+    #[cfg_attr(not(any(feature = "remote", feature = "dbus", feature = "tray")), allow(dead_code))]
+    pub fn wait_change(&self, seen: &Snapshot, timeout: Duration) -> Snapshot {
+        let (current, changed) = &*self.0;
+        let current = current.lock().unwrap_or_else(|e| e.into_inner());
+        let (current, _) = changed
+            .wait_timeout_while(current, timeout, |current| current == seen)
+            .unwrap_or_else(|e| e.into_inner());
+        current.clone()
+    }
+}
+
+impl App {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            state: self.engine.state.name(),
+            paused: self.engine.paused,
+            remaining_secs: self.engine.time_remaining.as_secs(),
+            remaining: self.clock_text(),
+            stopwatch_secs: self.engine.stopwatch.map(|counted| counted.as_secs()),
+            overtime_secs: self.engine.overtime.map(|over| over.as_secs()),
+            cycle: self.engine.cycle_count,
+            cycles_before_long: self.engine.cycles_before_long(),
+            extended_minutes: self.engine.extended_minutes,
+            completed_today: self.stats.completed_today(),
+            daily_goal: self.config.daily_goal,
+            ending_soon: self.ending_soon(),
+        }
+    }
+
+    // Show the timer this TUI is attached to, from its "status" reply. Stats
+    // are read again from disk when the other side has counted a pomodoro.
+    #[cfg_attr(not(feature = "control"), allow(dead_code))]
+    pub fn mirror(&mut self, status: &str) -> Result<()> {
+        let remote: Mirrored = serde_json::from_str(status)
+            .map_err(|_| Error::integration("control channel", format!("unexpected status reply {:?}", status)))?;
+        if remote.completed_today != self.stats.completed_today() {
+            self.stats = Stats::load(&self.stats_path, self.passphrase.as_ref())?;
+        }
+        self.engine.state = remote.state;
+        self.engine.paused = remote.paused;
+        self.engine.time_remaining = Duration::from_secs(remote.remaining_secs);
+        self.engine.stopwatch = remote.stopwatch_secs.map(Duration::from_secs);
+        self.engine.overtime = remote.overtime_secs.map(Duration::from_secs);
+        self.engine.cycle_count = remote.cycle;
+        self.engine.extended_minutes = remote.extended_minutes;
+        Ok(())
+    }
+}
+
+// The part of a Snapshot an attached TUI reads back
+#[derive(Deserialize)]
+struct Mirrored {
+    state: PomodoroState,
+    paused: bool,
+    remaining_secs: u64,
+    #[serde(default)]
+    stopwatch_secs: Option<u64>,
+    #[serde(default)]
+    overtime_secs: Option<u64>,
+    cycle: u32,
+    #[serde(default)]
+    extended_minutes: u32,
+    completed_today: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn attached_app_mirrors_a_snapshot() {
+        let mut timer = App::for_test(Config::default());
+        timer.toggle_pause();
+        timer.tick(Duration::from_secs(90)).unwrap();
+        timer.extend_session();
+        let status = serde_json::to_string(&timer.snapshot()).unwrap();
+
+        let mut attached = App::for_test(Config::default());
+        attached.mirror(&status).unwrap();
+        assert_eq!(attached.snapshot(), timer.snapshot());
+        assert!(attached.mirror("error: unknown command").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+// How a locale writes durations and times of day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeLocale {
+    pub hours: &'static str,
+    pub minutes: &'static str,
+    // Space between number and unit ("25 min" vs "25分")
+    pub spaced: bool,
+    // 12-hour clock by default
+    pub hour12: bool,
+    pub am: &'static str,
+    pub pm: &'static str,
+    // am/pm marker goes before the time ("午後2:35")
+    pub marker_first: bool,
+    // Written right to left
+    pub rtl: bool,
+}
+
+impl TimeLocale {
+    // `tag` is a language with optional region, e.g. "de", "en-US" or
+    // "en_US.UTF-8". "auto" reads the environment.
+    pub fn from_tag(tag: &str) -> Self {
+        let tag = if tag == "auto" { Self::detect() } else { tag.to_string() };
+        let tag = tag.split('.').next().unwrap_or("").replace('_', "-");
+        let mut parts = tag.split('-');
+        let language = parts.next().unwrap_or("").to_lowercase();
+        let region = parts.next().unwrap_or("").to_uppercase();
+
+        let base = TimeLocale {
+            hours: "h",
+            minutes: "min",
+            spaced: true,
+            hour12: false,
+            am: "am",
+            pm: "pm",
+            marker_first: false,
+            rtl: false,
+        };
+        match language.as_str() {
+            "en" => TimeLocale {
+                hour12: region.is_empty() || ["US", "CA", "AU", "NZ", "PH", "IN"].contains(&region.as_str()),
+                ..base
+            },
+            "de" => TimeLocale { hours: "Std.", minutes: "Min.", ..base },
+            "nl" => TimeLocale { hours: "u", ..base },
+            "ar" => TimeLocale { hours: "س", minutes: "د", hour12: true, am: "ص", pm: "م", rtl: true, ..base },
+            "he" => TimeLocale { hours: "שע׳", minutes: "דק׳", rtl: true, ..base },
+            "fa" | "ur" => TimeLocale { rtl: true, ..base },
+            "ja" => TimeLocale {
+                hours: "時間",
+                minutes: "分",
+                spaced: false,
+                am: "午前",
+                pm: "午後",
+                marker_first: true,
+                ..base
+            },
+            _ => base,
+        }
+    }
+
+    pub fn detect() -> String {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .unwrap_or_else(|| "en".to_string())
+    }
+
+    pub fn unit(&self, value: String, unit: &str) -> String {
+        if self.spaced { format!("{} {}", value, unit) } else { format!("{}{}", value, unit) }
+    }
+
+    // "25 min", "1 h 05 min"
+    pub fn format_duration(&self, duration: Duration) -> String {
+        let total_mins = duration.as_secs() / 60;
+        let (hours, mins) = (total_mins / 60, total_mins % 60);
+        if hours == 0 {
+            return self.unit(mins.to_string(), self.minutes);
+        }
+        let sep = if self.spaced { " " } else { "" };
+        format!("{}{}{}", self.unit(hours.to_string(), self.hours), sep, self.unit(format!("{:02}", mins), self.minutes))
+    }
+
+    // Hours and minutes plus the am/pm marker when on a 12-hour clock
+    pub fn split_time<T: chrono::Timelike>(&self, time: &T, hour12: bool) -> (String, Option<&'static str>) {
+        if !hour12 {
+            return (format!("{:02}:{:02}", time.hour(), time.minute()), None);
+        }
+        let (pm, hour) = time.hour12();
+        (format!("{}:{:02}", hour, time.minute()), Some(if pm { self.pm } else { self.am }))
+    }
+
+    pub fn format_time<T: chrono::Timelike>(&self, time: &T, hour12: bool) -> String {
+        match self.split_time(time, hour12) {
+            (digits, Some(marker)) if self.marker_first => format!("{}{}", marker, digits),
+            (digits, Some(marker)) => format!("{} {}", digits, marker),
+            (digits, None) => digits,
+        }
+    }
+}
+
+// Hebrew, Arabic and the scripts written with it, and their presentation
+// forms
+pub fn is_rtl_char(c: char) -> bool {
+    matches!(c as u32, 0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF)
+}
+
+// `text` as a right-to-left line in the order a terminal that prints cells
+// as given should show it: right-to-left runs reversed, left-to-right runs
+// (words, numbers) kept as they are, and the runs themselves in reverse.
+// Spacing and punctuation between two left-to-right runs stays with them.
+pub fn visual_order(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let strong: Vec<Option<bool>> = chars
+        .iter()
+        .map(|&c| if is_rtl_char(c) { Some(false) } else if c.is_alphanumeric() { Some(true) } else { None })
+        .collect();
+    let mut runs: Vec<(bool, Vec<char>)> = Vec::new();
+    for (i, &c) in chars.iter().enumerate() {
+        let ltr = strong[i].unwrap_or_else(|| {
+            let before = strong[..i].iter().rev().find_map(|&s| s);
+            let after = strong[i + 1..].iter().find_map(|&s| s);
+            before == Some(true) && after == Some(true)
+        });
+        match runs.last_mut() {
+            Some((run_ltr, run)) if *run_ltr == ltr => run.push(c),
+            _ => runs.push((ltr, vec![c])),
+        }
+    }
+    let mut visual = String::with_capacity(text.len());
+    for (ltr, run) in runs.iter().rev() {
+        if *ltr {
+            visual.extend(run);
+        } else {
+            visual.extend(run.iter().rev().map(|&c| mirrored(c)));
+        }
+    }
+    visual
+}
+
+// Brackets point the other way in right-to-left text
+fn mirrored(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn right_to_left_text_is_printed_in_display_order() {
+        assert_eq!(visual_order("25 דקות"), "תוקד 25");
+        assert_eq!(visual_order("עבודה: write docs"), "write docs :הדובע");
+        assert_eq!(visual_order("(שלום)"), "(םולש)");
+        assert!(TimeLocale::from_tag("he_IL.UTF-8").rtl && !TimeLocale::from_tag("de").rtl);
+    }
+}
@@ -1,498 +1,370 @@
-use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    execute,
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    terminal::{self, Clear, ClearType},
-};
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::io::{self, Write};
+use clap::{Parser, Subcommand};
+use crossterm::event::Event;
+use crossterm::style::Print;
+use crossterm::{cursor, execute, terminal};
+use std::io;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Config {
-    theme: String,
-    work_duration: u32,
-    short_break: u32,
-    long_break: u32,
-    cycles_before_long: u32,
-}
-
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            theme: "blue".to_string(),
-            work_duration: 25,
-            short_break: 5,
-            long_break: 15,
-            cycles_before_long: 4,
-        }
+use rpomodoro::app::{App, Attachment};
+use rpomodoro::backup::run_backup;
+use rpomodoro::config::{migrate_to_toml, Overrides};
+use rpomodoro::countdown::parse_length;
+#[cfg(feature = "control")]
+use rpomodoro::daemon::{attach, run_daemon};
+use rpomodoro::debug_keys::run_debug_keys;
+use rpomodoro::doctor::run_doctor;
+use rpomodoro::error::{Error, Result};
+use rpomodoro::events::{AppEvent, Events};
+use rpomodoro::integrations::focus::WINDOW_TITLE;
+use rpomodoro::integrations::Integration;
+use rpomodoro::recording::{self, Recorder};
+use rpomodoro::render::screen::Screen;
+use rpomodoro::report::run_report;
+use rpomodoro::simulate::run_simulate;
+use rpomodoro::status::{run_bar, run_status, BAR_FORMATS};
+use rpomodoro::storage::Dirs;
+use rpomodoro::theme::ColorDepth;
+use rpomodoro::version::run_version;
+#[cfg(feature = "control")]
+use rpomodoro::ipc;
+use rpomodoro::{background, crash, duration, integrations, storage};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("rpomodoro: {}", e);
+        std::process::exit(1);
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum PomodoroState {
-    Work,
-    ShortBreak,
-    LongBreak,
+// The command line. Flags go before or after the subcommand; without one
+// the timer runs in the terminal.
+#[derive(Parser)]
+#[command(name = "rpomodoro", about = "A terminal-based Pomodoro timer", disable_version_flag = true)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    #[arg(long, global = true, help = "Print the terminal's key events, to report key problems")]
+    debug_keys: bool,
+    #[arg(long, global = true, help = "Run the timer without a terminal")]
+    daemon: bool,
+    #[arg(short = 'V', long, global = true, help = "Print the version")]
+    version: bool,
+    #[arg(long, global = true, help = "Print status, report or version as JSON")]
+    json: bool,
+    #[arg(long, global = true, help = "Keep config and data next to the executable")]
+    portable: bool,
+    #[arg(long, global = true, help = "Write nothing to disk")]
+    read_only: bool,
+    #[arg(long, global = true, help = "Start with integrations held off")]
+    offline: bool,
+    #[arg(long, global = true, help = "Start with the clock counting up")]
+    stopwatch: bool,
+    #[arg(long, global = true, value_name = "DIR", help = "Keep the config in DIR")]
+    config_dir: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "DIR", help = "Keep stats and history in DIR")]
+    data_dir: Option<PathBuf>,
+    #[arg(long = "config", global = true, value_name = "FILE", help = "Read and save this config file")]
+    config_file: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "SPEC", help = "Run the config on a virtual clock, e.g. \"8h at 60x speed\"")]
+    simulate: Option<String>,
+    // Key presses to save, or to play back, with their timing
+    #[arg(long, global = true, value_name = "FILE", help = "Save key presses with their timing")]
+    record: Option<PathBuf>,
+    #[arg(long, global = true, value_name = "FILE", help = "Play back key presses saved with --record")]
+    replay: Option<PathBuf>,
+    // The rest override the config for this run only
+    #[arg(long, global = true, value_name = "LENGTH", value_parser = minutes, help = "Work length for this run, e.g. 50 or 1h")]
+    work: Option<u32>,
+    #[arg(long, global = true, value_name = "LENGTH", value_parser = minutes, help = "Short break length for this run")]
+    short: Option<u32>,
+    #[arg(long, global = true, value_name = "LENGTH", value_parser = minutes, help = "Long break length for this run")]
+    long: Option<u32>,
+    #[arg(long, global = true, value_name = "NAME", help = "Theme for this run")]
+    theme: Option<String>,
+    #[arg(long, global = true, value_name = "NAME", help = "Profile for this run; with report, only its pomodoros")]
+    profile: Option<String>,
+    #[arg(long, global = true, value_name = "NAME", help = "Preset for this run")]
+    preset: Option<String>,
 }
 
-#[derive(Debug, Clone, Copy)]
-struct Theme {
-    primary: Color,
-    dim: Color,
+#[derive(Subcommand)]
+enum CliCommand {
+    #[command(about = "Check the terminal, config and data directories")]
+    Doctor,
+    #[command(about = "Print where the timer is")]
+    Status,
+    #[command(about = "Print a line for status bars whenever the timer changes")]
+    Bar {
+        #[arg(long, default_value = "text", value_parser = BAR_FORMATS)]
+        format: String,
+    },
+    #[command(about = "Convert config.json to config.toml")]
+    MigrateConfig,
+    #[command(about = "Sum up the last 7 days")]
+    Report,
+    #[command(about = "Write the config and data to FILE, or back from it")]
+    Backup {
+        #[arg(value_parser = ["create", "restore"])]
+        action: String,
+        file: PathBuf,
+    },
+    #[command(about = "Start with a one-off countdown, e.g. 17m, 1h30m or 25:30")]
+    Timer {
+        #[arg(value_parser = parse_length)]
+        length: Duration,
+    },
+    // Sent to the running timer, by their names in Command::NAMES
+    #[command(about = "Pause or resume the running timer")]
+    Toggle,
+    #[command(about = "Pause the running timer")]
+    Pause,
+    #[command(about = "Resume the running timer")]
+    Resume,
+    #[command(about = "Reset the running timer's session")]
+    Reset,
+    #[command(about = "Skip to the next session")]
+    Skip,
+    #[command(about = "Extend the running session")]
+    Extend,
+    #[command(about = "Count the running timer's clock up or down")]
+    Stopwatch,
+    #[command(about = "Stop the running timer")]
+    Quit,
 }
 
-impl Theme {
-    fn from_name(name: &str) -> Self {
-        match name {
-            "blue" => Theme {
-                primary: Color::Rgb { r: 96, g: 165, b: 250 },
-                dim: Color::Rgb { r: 147, g: 197, b: 253 },
-            },
-            "purple" => Theme {
-                primary: Color::Rgb { r: 192, g: 132, b: 252 },
-                dim: Color::Rgb { r: 233, g: 213, b: 255 },
-            },
-            "green" => Theme {
-                primary: Color::Rgb { r: 74, g: 222, b: 128 },
-                dim: Color::Rgb { r: 134, g: 239, b: 172 },
-            },
-            "red" => Theme {
-                primary: Color::Rgb { r: 248, g: 113, b: 113 },
-                dim: Color::Rgb { r: 254, g: 202, b: 202 },
-            },
-            "orange" => Theme {
-                primary: Color::Rgb { r: 251, g: 191, b: 36 },
-                dim: Color::Rgb { r: 253, g: 224, b: 71 },
-            },
-            "cyan" => Theme {
-                primary: Color::Rgb { r: 34, g: 211, b: 238 },
-                dim: Color::Rgb { r: 103, g: 232, b: 249 },
-            },
-            _ => Theme::from_name("blue"),
+impl CliCommand {
+    // The control channel's name for the commands sent to a running timer
+    fn control_name(&self) -> Option<&'static str> {
+        match self {
+            CliCommand::Toggle => Some("toggle"),
+            CliCommand::Pause => Some("pause"),
+            CliCommand::Resume => Some("resume"),
+            CliCommand::Reset => Some("reset"),
+            CliCommand::Skip => Some("skip"),
+            CliCommand::Extend => Some("extend"),
+            CliCommand::Stopwatch => Some("stopwatch"),
+            CliCommand::Quit => Some("quit"),
+            _ => None,
         }
     }
 }
 
-// tty-clock style: 3x5 matrix, each cell is 2 chars wide
-// This matches the exact tty-clock implementation
-const DIGITS: [[[bool; 3]; 5]; 10] = [
-    // 0
-    [[true, true, true], [true, false, true], [true, false, true], [true, false, true], [true, true, true]],
-    // 1
-    [[false, false, true], [false, false, true], [false, false, true], [false, false, true], [false, false, true]],
-    // 2
-    [[true, true, true], [false, false, true], [true, true, true], [true, false, false], [true, true, true]],
-    // 3
-    [[true, true, true], [false, false, true], [true, true, true], [false, false, true], [true, true, true]],
-    // 4
-    [[true, false, true], [true, false, true], [true, true, true], [false, false, true], [false, false, true]],
-    // 5
-    [[true, true, true], [true, false, false], [true, true, true], [false, false, true], [true, true, true]],
-    // 6
-    [[true, true, true], [true, false, false], [true, true, true], [true, false, true], [true, true, true]],
-    // 7
-    [[true, true, true], [false, false, true], [false, false, true], [false, false, true], [false, false, true]],
-    // 8
-    [[true, true, true], [true, false, true], [true, true, true], [true, false, true], [true, true, true]],
-    // 9
-    [[true, true, true], [true, false, true], [true, true, true], [false, false, true], [true, true, true]],
-];
-
-struct App {
-    config: Config,
-    config_path: PathBuf,
-    state: PomodoroState,
-    cycle_count: u32,
-    time_remaining: Duration,
-    last_tick: Instant,
-    paused: bool,
-    theme: Theme,
-    width: u16,
-    height: u16,
-    config_mode: bool,
-    config_cursor: usize,
+impl Args {
+    fn overrides(&self) -> Overrides {
+        Overrides {
+            work_duration: self.work,
+            short_break: self.short,
+            long_break: self.long,
+            theme: self.theme.clone(),
+            profile: self.profile.clone(),
+            preset: self.preset.clone(),
+        }
+    }
 }
 
-impl App {
-    fn new() -> io::Result<Self> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("rpomodoro");
-        
-        fs::create_dir_all(&config_dir)?;
-        let config_path = config_dir.join("config.json");
-        
-        let config = if config_path.exists() {
-            let content = fs::read_to_string(&config_path)?;
-            serde_json::from_str(&content).unwrap_or_default()
-        } else {
-            let default = Config::default();
-            let json = serde_json::to_string_pretty(&default)?;
-            fs::write(&config_path, json)?;
-            default
-        };
+fn minutes(text: &str) -> std::result::Result<u32, String> {
+    duration::parse_minutes(text, 1, 1440)
+}
 
-        let theme = Theme::from_name(&config.theme);
-        let (width, height) = terminal::size()?;
-        
-        Ok(App {
-            time_remaining: Duration::from_secs(config.work_duration as u64 * 60),
-            config,
-            config_path,
-            state: PomodoroState::Work,
-            cycle_count: 0,
-            last_tick: Instant::now(),
-            paused: true,
-            theme,
-            width,
-            height,
-            config_mode: false,
-            config_cursor: 0,
-        })
+fn run() -> Result<()> {
+    let args = Args::parse();
+    let overrides = args.overrides();
+    if args.version {
+        run_version(args.json);
+        return Ok(());
     }
-
-    fn save_config(&self) -> io::Result<()> {
-        let json = serde_json::to_string_pretty(&self.config)?;
-        fs::write(&self.config_path, json)?;
-        Ok(())
+    if args.debug_keys {
+        return run_debug_keys();
     }
-
-    fn update(&mut self) {
-        if !self.paused {
-            let now = Instant::now();
-            let elapsed = now.duration_since(self.last_tick);
-            self.last_tick = now;
-
-            if let Some(new_remaining) = self.time_remaining.checked_sub(elapsed) {
-                self.time_remaining = new_remaining;
-            } else {
-                self.time_remaining = Duration::ZERO;
-                self.advance_state();
-            }
-        }
+    storage::set_read_only(args.read_only);
+    let mut dirs = Dirs::resolve(args.config_dir, args.data_dir, args.portable);
+    if let Some(file) = args.config_file {
+        dirs.set_config_file(file);
     }
-
-    fn advance_state(&mut self) {
-        match self.state {
-            PomodoroState::Work => {
-                self.cycle_count += 1;
-                if self.cycle_count >= self.config.cycles_before_long {
-                    self.state = PomodoroState::LongBreak;
-                    self.time_remaining = Duration::from_secs(self.config.long_break as u64 * 60);
-                    self.cycle_count = 0;
-                } else {
-                    self.state = PomodoroState::ShortBreak;
-                    self.time_remaining = Duration::from_secs(self.config.short_break as u64 * 60);
-                }
-            }
-            PomodoroState::ShortBreak | PomodoroState::LongBreak => {
-                self.state = PomodoroState::Work;
-                self.time_remaining = Duration::from_secs(self.config.work_duration as u64 * 60);
+    if let Some(spec) = args.simulate.as_deref().filter(|_| args.command.is_none()) {
+        return run_simulate(&dirs, &overrides, spec);
+    }
+    let mut countdown = None;
+    match &args.command {
+        Some(CliCommand::Doctor) => {
+            if !run_doctor(&dirs) {
+                std::process::exit(1);
             }
+            return Ok(());
         }
-        self.paused = true;
-    }
-
-    fn draw(&self) -> io::Result<()> {
-        let mut stdout = io::stdout();
-        execute!(stdout, Clear(ClearType::All))?;
-
-        let center_x = self.width / 2;
-        let center_y = self.height / 2;
-
-        // Draw large clock
-        self.draw_clock(center_x, center_y.saturating_sub(3))?;
-
-        // Draw minimal status bar at bottom
-        self.draw_statusline()?;
-
-        stdout.flush()?;
-        Ok(())
-    }
-
-    fn draw_digit(&self, digit: usize, x: u16, y: u16) -> io::Result<()> {
-        let mut stdout = io::stdout();
-        
-        for row in 0..5 {
-            execute!(stdout, cursor::MoveTo(x, y + row as u16))?;
-            for col in 0..3 {
-                if DIGITS[digit][row as usize][col] {
-                    execute!(stdout, SetForegroundColor(self.theme.primary))?;
-                    print!("██");
-                } else {
-                    print!("  ");
-                }
+        Some(CliCommand::Status) => return run_status(&dirs, args.json),
+        Some(CliCommand::Report) => return run_report(&dirs, args.json, overrides.profile.as_deref()),
+        Some(CliCommand::Bar { format }) => return run_bar(&dirs, format),
+        Some(CliCommand::MigrateConfig) => {
+            if !dirs.has_legacy_config() {
+                return Err(Error::Usage(format!("{} is already the config; there's no config.json to migrate", dirs.config_file().display())));
             }
+            let toml = migrate_to_toml(&dirs.config_file())?;
+            println!("config migrated to {}; the old one is kept as config.json.migrated", toml.display());
+            return Ok(());
         }
-        
-        execute!(stdout, ResetColor)?;
-        Ok(())
+        Some(CliCommand::Backup { action, file }) => return run_backup(&dirs, action, file),
+        Some(CliCommand::Timer { length }) => countdown = Some(*length),
+        Some(command) => return send_command(&dirs, command.control_name().unwrap_or_default()),
+        None => {}
     }
-
-    fn draw_colon(&self, x: u16, y: u16) -> io::Result<()> {
-        let mut stdout = io::stdout();
-        
-        execute!(stdout, SetForegroundColor(self.theme.primary))?;
-        
-        execute!(stdout, cursor::MoveTo(x, y + 1))?;
-        print!("██");
-        execute!(stdout, cursor::MoveTo(x, y + 3))?;
-        print!("██");
-        
-        execute!(stdout, ResetColor)?;
-        Ok(())
+    if args.daemon {
+        return run_daemon(&dirs, overrides);
     }
 
-    fn draw_clock(&self, center_x: u16, y: u16) -> io::Result<()> {
-        let total_secs = self.time_remaining.as_secs();
-        let mins = total_secs / 60;
-        let secs = total_secs % 60;
-
-        let digit1 = (mins / 10) as usize;
-        let digit2 = (mins % 10) as usize;
-        let digit3 = (secs / 10) as usize;
-        let digit4 = (secs % 10) as usize;
-
-        // Each digit is 6 chars wide (3 cols * 2 chars)
-        // Add 2 char spacing between digit pairs = 2 chars
-        // Colon is 2 chars, with 2 char spacing on each side = 6 chars total
-        // Total: 6 + 2 + 6 + 6 + 6 + 2 + 6 = 34 chars
-        let total_width = 34;
-        let start_x = center_x.saturating_sub(total_width / 2);
+    // Before the terminal is taken over, so a bad file is reported plainly
+    let replay = args.replay.as_deref().map(recording::load).transpose()?;
 
-        // Draw minutes
-        self.draw_digit(digit1, start_x, y)?;
-        self.draw_digit(digit2, start_x + 8, y)?;  // 6 + 2 spacing
-        
-        // Draw colon
-        self.draw_colon(start_x + 16, y)?;
-        
-        // Draw seconds
-        self.draw_digit(digit3, start_x + 20, y)?;
-        self.draw_digit(digit4, start_x + 28, y)?;  // 6 + 2 spacing
-
-        Ok(())
+    let attached = attach(&dirs);
+    let mut app = App::new(&dirs, overrides)?;
+    app.offline |= args.offline;
+    if args.stopwatch {
+        app.engine.set_stopwatch(true);
     }
+    app.attached = attached.is_some();
+    if let Some(length) = countdown {
+        app.start_countdown(length)?;
+    }
+    
+    crash::install(dirs.state.clone(), &app.config);
+    terminal::enable_raw_mode()?;
+    app.background = background::detect(&app.config.background);
+    app.apply_theme();
+    let mut stdout = io::stdout();
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide, terminal::SetTitle(WINDOW_TITLE))?;
 
-    fn draw_statusline(&self) -> io::Result<()> {
-        let mut stdout = io::stdout();
-        let y = self.height - 1;
-
-        // Clear the line first
-        execute!(stdout, cursor::MoveTo(0, y))?;
-        print!("{}", " ".repeat(self.width as usize));
-
-        // Left side - mode indicator (lowercase, clean)
-        let mode = match self.state {
-            PomodoroState::Work => "work",
-            PomodoroState::ShortBreak => "break",
-            PomodoroState::LongBreak => "long break",
-        };
-        
-        let status = if self.paused { "paused" } else { "running" };
-        let left_side = format!(" {} | {} ", mode, status);
-
-        // Center - cycle info
-        let cycles = format!("cycles: {}/{}", self.cycle_count, self.config.cycles_before_long);
-
-        // Right side - keybindings (lowercase, vim-style)
-        let right_side = " space:start/pause  r:reset  s:skip  c:config  q:quit ";
-
-        execute!(
-            stdout,
-            cursor::MoveTo(0, y),
-            SetForegroundColor(self.theme.primary)
-        )?;
-        print!("{}", left_side);
+    let mut integrations = integrations::registry(&dirs, &app);
+    let result = run_app(&mut app, &mut integrations, attached, Input { record: args.record, replay });
 
-        let center_x = (self.width / 2).saturating_sub((cycles.len() / 2) as u16);
-        execute!(stdout, cursor::MoveTo(center_x, y), SetForegroundColor(self.theme.dim))?;
-        print!("{}", cycles);
+    app.clear_ambient_cue();
+    execute!(stdout, Print(std::mem::take(&mut app.terminal_output)))?;
+    execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
+    terminal::disable_raw_mode()?;
 
-        let right_x = self.width.saturating_sub(right_side.len() as u16);
-        execute!(stdout, cursor::MoveTo(right_x, y), SetForegroundColor(self.theme.dim))?;
-        print!("{}", right_side);
+    result
+}
 
-        execute!(stdout, ResetColor)?;
-        Ok(())
+#[cfg(feature = "control")]
+fn send_command(dirs: &Dirs, command: &str) -> Result<()> {
+    let reply = ipc::transport(dirs)
+        .request(command)
+        .map_err(|e| Error::integration("control channel", format!("no running rpomodoro answered ({})", e)))?;
+    match reply.strip_prefix("error: ") {
+        Some(reason) => Err(Error::integration("control channel", reason)),
+        None => Ok(()),
     }
+}
 
-    fn draw_config(&self) -> io::Result<()> {
-        let mut stdout = io::stdout();
-        execute!(stdout, Clear(ClearType::All))?;
-
-        let center_x = self.width / 2;
-        let start_y = self.height / 2 - 10;
+#[cfg(not(feature = "control"))]
+fn send_command(_dirs: &Dirs, _command: &str) -> Result<()> {
+    Err(Error::Usage("this build has no control channel (cargo feature \"control\")".to_string()))
+}
 
-        let configs = [
-            ("theme", self.config.theme.clone()),
-            ("work_duration", format!("{}", self.config.work_duration)),
-            ("short_break", format!("{}", self.config.short_break)),
-            ("long_break", format!("{}", self.config.long_break)),
-            ("cycles_before_long", format!("{}", self.config.cycles_before_long)),
-        ];
+#[cfg(not(feature = "control"))]
+fn run_daemon(_dirs: &Dirs, _overrides: Overrides) -> Result<()> {
+    Err(Error::Usage("this build has no control channel for a daemon (cargo feature \"control\")".to_string()))
+}
 
-        for (i, (label, value)) in configs.iter().enumerate() {
-            let y = start_y + i as u16 * 2;
-            let is_selected = i == self.config_cursor;
-            
-            let color = if is_selected { self.theme.primary } else { self.theme.dim };
-            let pointer = if is_selected { "> " } else { "  " };
-            
-            let line = format!("{}{}: {}", pointer, label, value);
-            let x = center_x.saturating_sub((line.len() / 2) as u16);
-            
-            execute!(
-                stdout,
-                cursor::MoveTo(x, y),
-                SetForegroundColor(color),
-                Print(&line),
-                ResetColor
-            )?;
-        }
+#[cfg(not(feature = "control"))]
+fn attach(_dirs: &Dirs) -> Option<Attachment> {
+    None
+}
 
-        // Statusline for config mode
-        let y = self.height - 1;
-        execute!(stdout, cursor::MoveTo(0, y))?;
-        print!("{}", " ".repeat(self.width as usize));
-        
-        let help = " config | j/k:navigate  h/l:change  q/esc:save&exit ";
-        let help_x = (self.width / 2).saturating_sub((help.len() / 2) as u16);
-        execute!(
-            stdout,
-            cursor::MoveTo(help_x, y),
-            SetForegroundColor(self.theme.primary),
-            Print(help),
-            ResetColor
-        )?;
+// --record and --replay
+struct Input {
+    record: Option<PathBuf>,
+    replay: Option<Vec<(Duration, Event)>>,
+}
 
-        stdout.flush()?;
-        Ok(())
+// `attached` follows a timer running elsewhere instead of this one
+fn run_app(app: &mut App, integrations: &mut [Box<dyn Integration>], mut attached: Option<Attachment>, input: Input) -> Result<()> {
+    let mut stdout = io::stdout();
+    let mut previous: Option<Screen> = None;
+    let events = Events::new();
+    // Keys pressed during a replay still count, e.g. to quit it early
+    events.spawn_terminal();
+    if let Some(replay) = input.replay {
+        events.spawn_replay(replay);
     }
+    let mut recorder = input.record.as_deref().map(Recorder::create).transpose()?;
+    let mut started = false;
 
-    fn handle_config_input(&mut self, key: KeyEvent) -> io::Result<()> {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                self.config_mode = false;
-                self.save_config()?;
-                self.theme = Theme::from_name(&self.config.theme);
-            }
-            KeyCode::Char('j') | KeyCode::Down => {
-                self.config_cursor = (self.config_cursor + 1).min(4);
-            }
-            KeyCode::Char('k') | KeyCode::Up => {
-                self.config_cursor = self.config_cursor.saturating_sub(1);
-            }
-            KeyCode::Char('h') | KeyCode::Left => {
-                match self.config_cursor {
-                    0 => {
-                        let themes = ["blue", "purple", "green", "red", "orange", "cyan"];
-                        if let Some(pos) = themes.iter().position(|&t| t == self.config.theme) {
-                            let new_pos = if pos == 0 { themes.len() - 1 } else { pos - 1 };
-                            self.config.theme = themes[new_pos].to_string();
-                            self.theme = Theme::from_name(&self.config.theme);
-                        }
-                    }
-                    1 => self.config.work_duration = self.config.work_duration.saturating_sub(1).max(1),
-                    2 => self.config.short_break = self.config.short_break.saturating_sub(1).max(1),
-                    3 => self.config.long_break = self.config.long_break.saturating_sub(1).max(1),
-                    4 => self.config.cycles_before_long = self.config.cycles_before_long.saturating_sub(1).max(1),
-                    _ => {}
+    loop {
+        // Integrations start once the app is online, which is at launch
+        // unless offline mode was asked for. A failed integration leaves a
+        // plain timer, e.g. when another instance owns the control channel.
+        // An attached TUI leaves them to the timer it shows.
+        if !app.offline && !started && attached.is_none() {
+            for integration in integrations.iter_mut() {
+                if let Err(e) = app.health.track(integration.name(), integration.start(events.sender())) {
+                    app.show_error(e);
                 }
             }
-            KeyCode::Char('l') | KeyCode::Right => {
-                match self.config_cursor {
-                    0 => {
-                        let themes = ["blue", "purple", "green", "red", "orange", "cyan"];
-                        if let Some(pos) = themes.iter().position(|&t| t == self.config.theme) {
-                            let new_pos = (pos + 1) % themes.len();
-                            self.config.theme = themes[new_pos].to_string();
-                            self.theme = Theme::from_name(&self.config.theme);
-                        }
-                    }
-                    1 => self.config.work_duration = (self.config.work_duration + 1).min(120),
-                    2 => self.config.short_break = (self.config.short_break + 1).min(60),
-                    3 => self.config.long_break = (self.config.long_break + 1).min(120),
-                    4 => self.config.cycles_before_long = (self.config.cycles_before_long + 1).min(10),
-                    _ => {}
+            started = true;
+        }
+
+        let frame_start = Instant::now();
+        let mut screen = Screen::new(app.width, app.height);
+        if let Some(sync) = &mut attached {
+            sync(app)?;
+        } else if let Err(e) = app.update().and_then(|_| app.export_badge()).and_then(|_| app.autosave(false)) {
+            app.show_error(e);
+        }
+        if !app.offline && attached.is_none() {
+            app.live.publish(app.snapshot());
+        }
+        app.draw_frame(&mut screen);
+        if app.show_overlay {
+            app.draw_overlay(&mut screen);
+        }
+        screen.flush(&mut stdout, previous.as_ref(), ColorDepth::from_setting(&app.config.color_mode))?;
+        if std::mem::take(&mut app.ring_bell) {
+            execute!(stdout, Print('\x07'))?;
+        }
+        if !app.terminal_output.is_empty() {
+            execute!(stdout, Print(std::mem::take(&mut app.terminal_output)))?;
+        }
+        app.frame_stats.record(frame_start.elapsed());
+        previous = Some(screen);
+
+        // Sleep until the next event or until the display would change
+        for event in events.wait(app.redraw_timeout()) {
+            if let (Some(keys), AppEvent::Terminal(event)) = (&mut recorder, &event) {
+                if let Err(e) = keys.record(event) {
+                    app.show_error(e);
+                    recorder = None;
                 }
             }
-            _ => {}
+            if !app.handle_app_event(event)? {
+                return if attached.is_some() { Ok(()) } else { app.autosave(true) };
+            }
         }
-        Ok(())
     }
 }
 
-fn main() -> io::Result<()> {
-    let mut app = App::new()?;
-    
-    terminal::enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
-
-    let result = run_app(&mut app);
-
-    execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
-    terminal::disable_raw_mode()?;
-
-    result
-}
-
-fn run_app(app: &mut App) -> io::Result<()> {
-    loop {
-        if app.config_mode {
-            app.draw_config()?;
-        } else {
-            app.update();
-            app.draw()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+    use rpomodoro::events::Command;
+
+    #[test]
+    fn arguments_are_checked() {
+        Args::command().debug_assert();
+        let args = Args::try_parse_from(["rpomodoro", "report", "--json", "--work", "1h", "--profile", "study"]).unwrap();
+        assert!(matches!(args.command, Some(CliCommand::Report)) && args.json);
+        assert_eq!((args.overrides().work_duration, args.overrides().profile.as_deref()), (Some(60), Some("study")));
+        for wrong in [&["rpomodoro", "--wrok", "50"][..], &["rpomodoro", "--work", "0"], &["rpomodoro", "timer"], &["rpomodoro", "bar", "--format", "xml"]] {
+            assert!(Args::try_parse_from(wrong).is_err(), "{:?}", wrong);
         }
+    }
 
-        if event::poll(Duration::from_millis(50))? {
-            match event::read()? {
-                Event::Key(key) => {
-                    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
-                        break;
-                    }
-
-                    if app.config_mode {
-                        app.handle_config_input(key)?;
-                    } else {
-                        match key.code {
-                            KeyCode::Char('q') | KeyCode::Char('Q') => break,
-                            KeyCode::Char(' ') => {
-                                app.paused = !app.paused;
-                                if !app.paused {
-                                    app.last_tick = Instant::now();
-                                }
-                            }
-                            KeyCode::Char('r') | KeyCode::Char('R') => {
-                                app.paused = true;
-                                app.cycle_count = 0;
-                                app.state = PomodoroState::Work;
-                                app.time_remaining = Duration::from_secs(app.config.work_duration as u64 * 60);
-                            }
-                            KeyCode::Char('s') | KeyCode::Char('S') => {
-                                app.advance_state();
-                            }
-                            KeyCode::Char('c') | KeyCode::Char('C') => {
-                                app.config_mode = true;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                Event::Resize(w, h) => {
-                    app.width = w;
-                    app.height = h;
-                }
-                _ => {}
-            }
+    #[test]
+    fn control_subcommands_match_the_channel() {
+        for (name, _) in Command::NAMES {
+            let args = Args::try_parse_from(["rpomodoro", name]).unwrap();
+            assert_eq!(args.command.and_then(|command| command.control_name()), Some(name));
         }
     }
-
-    Ok(())
 }
@@ -0,0 +1,108 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// Vim-style movement shared by every screen with something to move
+// through, so the same keys work everywhere: j/k (h/l across the stats
+// chart) and the arrows step, ctrl-d/ctrl-u move half a page, gg and Home
+// go to the first entry, G and End to the last
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Back,
+    Forward,
+    HalfPageBack,
+    HalfPageForward,
+    First,
+    Last,
+}
+
+// Which way a screen's entries run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+// Remembers the first g of gg between key presses
+#[derive(Debug, Default)]
+pub struct Navigation {
+    pending_g: bool,
+}
+
+impl Navigation {
+    // The motion `key` completes, if any. Any other key drops a pending g.
+    pub fn motion(&mut self, key: KeyEvent, axis: Axis) -> Option<Motion> {
+        let pending_g = std::mem::take(&mut self.pending_g);
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let (back, forward) = match axis {
+            Axis::Vertical => ('k', 'j'),
+            Axis::Horizontal => ('h', 'l'),
+        };
+        match key.code {
+            KeyCode::Char('d') if ctrl => Some(Motion::HalfPageForward),
+            KeyCode::Char('u') if ctrl => Some(Motion::HalfPageBack),
+            _ if ctrl => None,
+            KeyCode::Char(c) if c == back => Some(Motion::Back),
+            KeyCode::Char(c) if c == forward => Some(Motion::Forward),
+            KeyCode::Up if axis == Axis::Vertical => Some(Motion::Back),
+            KeyCode::Down if axis == Axis::Vertical => Some(Motion::Forward),
+            KeyCode::Left if axis == Axis::Horizontal => Some(Motion::Back),
+            KeyCode::Right if axis == Axis::Horizontal => Some(Motion::Forward),
+            KeyCode::PageUp => Some(Motion::HalfPageBack),
+            KeyCode::PageDown => Some(Motion::HalfPageForward),
+            KeyCode::Home => Some(Motion::First),
+            KeyCode::End | KeyCode::Char('G') => Some(Motion::Last),
+            KeyCode::Char('g') if pending_g => Some(Motion::First),
+            KeyCode::Char('g') => {
+                self.pending_g = true;
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Motion {
+    // Where `cursor` lands among `len` entries, `page` of them on screen
+    pub fn apply(self, cursor: usize, len: usize, page: usize) -> usize {
+        let last = len.saturating_sub(1);
+        let half = (page / 2).max(1);
+        match self {
+            Motion::Back => cursor.saturating_sub(1),
+            Motion::Forward => (cursor + 1).min(last),
+            Motion::HalfPageBack => cursor.saturating_sub(half),
+            Motion::HalfPageForward => (cursor + half).min(last),
+            Motion::First => 0,
+            Motion::Last => last,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_move_the_same_way_on_every_screen() {
+        let mut navigation = Navigation::default();
+        let mut cursor = 0;
+        let keys = [
+            (KeyEvent::from(KeyCode::Char('j')), 1),
+            (KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL), 6),
+            (KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT), 19),
+            (KeyEvent::from(KeyCode::Char('k')), 18),
+            (KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL), 13),
+            (KeyEvent::from(KeyCode::Char('g')), 13),
+            (KeyEvent::from(KeyCode::Char('g')), 0),
+        ];
+        for (key, expected) in keys {
+            if let Some(motion) = navigation.motion(key, Axis::Vertical) {
+                cursor = motion.apply(cursor, 20, 10);
+            }
+            assert_eq!(cursor, expected, "after {:?}", key.code);
+        }
+        // A g then anything else is no gg
+        navigation.motion(KeyEvent::from(KeyCode::Char('g')), Axis::Vertical);
+        navigation.motion(KeyEvent::from(KeyCode::Char('x')), Axis::Vertical);
+        assert_eq!(navigation.motion(KeyEvent::from(KeyCode::Char('g')), Axis::Vertical), None);
+        assert_eq!(navigation.motion(KeyEvent::from(KeyCode::Char('l')), Axis::Horizontal), Some(Motion::Forward));
+    }
+}
@@ -0,0 +1,213 @@
+use serde_json::Value;
+use std::time::Instant;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::error::Result;
+
+// The command line opened with ":", for reaching any setting in the config
+// file without the config screen: `:set work_duration 30`, nested ones by
+// dotted name (`:set sound.volume 0.3`) and maps as JSON. Values are as in
+// config.json, with on/off for switches and bare words for text. Tab
+// completes names, and the line says as it's typed whether what's there
+// would be accepted.
+impl App {
+    pub fn open_palette(&mut self) {
+        self.palette = Some(String::new());
+    }
+
+    // Run the typed line. A bad one stays open with its hint showing why.
+    pub fn run_palette(&mut self) -> Result<()> {
+        let Some(line) = self.palette.clone() else {
+            return Ok(());
+        };
+        let Some((name, value)) = parse_set(&line) else {
+            return Ok(());
+        };
+        if value.is_empty() {
+            if let Some(current) = current_value(&self.config, name) {
+                self.toast = Some((format!("{} = {}", name, current), Instant::now()));
+                self.palette = None;
+            }
+            return Ok(());
+        }
+        let Ok(config) = set(&self.config, name, value) else {
+            return Ok(());
+        };
+        self.palette = None;
+        self.config = config;
+        self.apply_config();
+        self.toast = Some((format!("{} = {}", name, value), Instant::now()));
+        self.save_config()
+    }
+
+    pub fn complete_palette(&mut self) {
+        if let Some(line) = &mut self.palette {
+            *line = complete(&self.config, line);
+        }
+    }
+
+    // What the line would do, and whether it would work
+    pub fn palette_hint(&self) -> Option<(String, bool)> {
+        let line = self.palette.as_deref()?;
+        if line.trim().is_empty() {
+            return Some(("set <setting> <value>, tab completes".to_string(), true));
+        }
+        let Some((name, value)) = parse_set(line) else {
+            return Some(("the only command is set".to_string(), false));
+        };
+        let names = setting_names(&self.config);
+        let hint = match current_value(&self.config, name) {
+            None if name.is_empty() => ("set <setting> <value>".to_string(), true),
+            None => match names.iter().filter(|n| n.starts_with(name)).count() {
+                0 => (format!("no setting {}", name), false),
+                count => (format!("{} settings start with {}, tab completes", count, name), true),
+            },
+            Some(current) if value.is_empty() => (format!("now {}", current), true),
+            Some(_) => match set(&self.config, name, value) {
+                Ok(_) => ("enter sets it".to_string(), true),
+                Err(reason) => (reason, false),
+            },
+        };
+        Some(hint)
+    }
+}
+
+// The setting and the value of `set <setting> <value>`, either may be
+// empty. None for any other command.
+fn parse_set(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim_start();
+    let rest = match line.split_once(' ') {
+        Some(("set", rest)) => rest.trim_start(),
+        None if "set".starts_with(line) => "",
+        _ => return None,
+    };
+    Some(rest.split_once(' ').map_or((rest, ""), |(name, value)| (name, value.trim())))
+}
+
+fn pointer(name: &str) -> String {
+    format!("/{}", name.replace('.', "/"))
+}
+
+// Every setting's dotted name, tables included
+pub fn setting_names(config: &Config) -> Vec<String> {
+    fn walk(value: &Value, prefix: &str, names: &mut Vec<String>) {
+        let Value::Object(fields) = value else {
+            return;
+        };
+        for (key, value) in fields {
+            let name = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+            walk(value, &name, names);
+            names.push(name);
+        }
+    }
+    let mut names = Vec::new();
+    walk(&serde_json::to_value(config).unwrap_or_default(), "", &mut names);
+    names.sort();
+    names
+}
+
+fn current_value(config: &Config, name: &str) -> Option<String> {
+    let value = serde_json::to_value(config).ok()?;
+    let current = value.pointer(&pointer(name)).filter(|_| !name.is_empty())?;
+    Some(match current {
+        Value::Bool(on) => if *on { "on" } else { "off" }.to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    })
+}
+
+// `config` with `name` set to `text`, or why it can't be: no such setting,
+// the wrong kind of value, or one the config would correct on loading
+pub fn set(config: &Config, name: &str, text: &str) -> std::result::Result<Config, String> {
+    let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    let slot = value.pointer_mut(&pointer(name)).filter(|_| !name.is_empty()).ok_or_else(|| format!("no setting {}", name))?;
+    *slot = match (&*slot, text) {
+        (Value::Bool(_), "on" | "true" | "yes") => Value::Bool(true),
+        (Value::Bool(_), "off" | "false" | "no") => Value::Bool(false),
+        (Value::String(_), text) => serde_json::from_str::<String>(text).map_or_else(|_| Value::String(text.to_string()), Value::String),
+        (_, text) => serde_json::from_str(text).unwrap_or_else(|_| Value::String(text.to_string())),
+    };
+    let updated: Config = serde_json::from_value(value).map_err(|e| format!("{}: {}", name, e))?;
+    let mut sanitized = updated.clone();
+    sanitized.sanitize();
+    if serde_json::to_value(&sanitized).ok() != serde_json::to_value(&updated).ok() {
+        return Err(format!("{} can't be {}", name, text));
+    }
+    Ok(updated)
+}
+
+// The line with the command or setting name being typed filled in as far
+// as it's unambiguous, or a switch's on/off
+fn complete(config: &Config, line: &str) -> String {
+    let Some((name, value)) = parse_set(line) else {
+        return line.to_string();
+    };
+    let Some(rest) = line.trim_start().strip_prefix("set ") else {
+        return "set ".to_string();
+    };
+    if !rest.trim_start().contains(' ') {
+        let names: Vec<String> = setting_names(config).into_iter().filter(|n| n.starts_with(name)).collect();
+        return match names.as_slice() {
+            [] => line.to_string(),
+            [only] => format!("set {} ", only),
+            [first, others @ ..] => {
+                let common = others.iter().fold(first.len(), |len, n| first.bytes().zip(n.bytes()).take(len).take_while(|(a, b)| a == b).count());
+                format!("set {}", &first[..common])
+            }
+        };
+    }
+    let switch = serde_json::to_value(config).ok().and_then(|v| v.pointer(&pointer(name)).cloned()).is_some_and(|v| v.is_boolean());
+    let options: Vec<&str> = ["on", "off"].into_iter().filter(|option| switch && option.starts_with(value)).collect();
+    match options.as_slice() {
+        [only] => format!("set {} {}", name, only),
+        _ => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_takes_any_setting_and_refuses_bad_values() {
+        let config = Config::default();
+        let config = set(&config, "work_duration", "30").unwrap();
+        assert_eq!(config.work_duration, 30);
+        assert!(set(&config, "show_wall_clock", "on").unwrap().show_wall_clock);
+        assert_eq!(set(&config, "weekly_summary.ntfy_url", "https://ntfy.sh/me").unwrap().weekly_summary.ntfy_url, "https://ntfy.sh/me");
+        assert_eq!(set(&config, "auto_start", "sometimes").unwrap_err(), "auto_start can't be sometimes");
+        assert_eq!(set(&config, "work_duration", "1h30m").unwrap().work_duration, 90);
+        assert_eq!(set(&config, "work_duration", "long").unwrap_err(), "work_duration: \"long\" isn't a length; use e.g. 25m, 1h30m, 90 or 25:30");
+        assert!(set(&config, "daily_goal", "lots").unwrap_err().starts_with("daily_goal: invalid type"));
+        assert_eq!(set(&config, "work_durations", "30").unwrap_err(), "no setting work_durations");
+
+        assert_eq!(complete(&config, "se"), "set ");
+        assert_eq!(complete(&config, "set work_d"), "set work_duration ");
+        assert_eq!(complete(&config, "set show_w"), "set show_wall_clock ");
+        assert_eq!(complete(&config, "set show_wall_clock o"), "set show_wall_clock o");
+        assert_eq!(complete(&config, "set show_wall_clock of"), "set show_wall_clock off");
+    }
+
+    #[test]
+    fn the_command_line_applies_what_it_sets() {
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let mut app = App::for_test(Config::default());
+        app.handle_event(crossterm::event::Event::Key(KeyEvent::from(KeyCode::Char(':'))));
+        for c in "set short_br".chars() {
+            app.handle_palette_input(KeyEvent::from(KeyCode::Char(c))).unwrap();
+        }
+        app.handle_palette_input(KeyEvent::from(KeyCode::Tab)).unwrap();
+        app.handle_palette_input(KeyEvent::from(KeyCode::Char('x'))).unwrap();
+        assert_eq!(app.palette_hint(), Some(("short_break: \"x\" isn't a length; use e.g. 25m, 1h30m, 90 or 25:30".to_string(), false)));
+        app.handle_palette_input(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert!(app.palette.is_some(), "a bad value keeps the line open");
+
+        app.handle_palette_input(KeyEvent::from(KeyCode::Backspace)).unwrap();
+        app.handle_palette_input(KeyEvent::from(KeyCode::Char('7'))).unwrap();
+        app.handle_palette_input(KeyEvent::from(KeyCode::Enter)).unwrap();
+        assert_eq!((app.palette.as_deref(), app.config.short_break), (None, 7));
+        assert_eq!(app.active_toast(), Some("short_break = 7"));
+    }
+}
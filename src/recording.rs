@@ -0,0 +1,79 @@
+use crossterm::event::Event;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+// `--record FILE` and `--replay FILE`: key presses with when they came, in
+// milliseconds from the start of the run, one JSON object per line. Replayed
+// keys go through the same channel as the terminal's, so a recording
+// reproduces a bug report or drives a demo.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Recorded {
+    pub at: u64,
+    pub event: Event,
+}
+
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+    started: Instant,
+}
+
+impl Recorder {
+    // Starts the clock; an existing file is replaced
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path).map_err(|e| Error::io(path, e))?;
+        Ok(Recorder { path: path.to_path_buf(), file, started: Instant::now() })
+    }
+
+    // Keys only: a resize would be replayed onto a terminal of another size
+    pub fn record(&mut self, event: &Event) -> Result<()> {
+        if !matches!(event, Event::Key(_)) {
+            return Ok(());
+        }
+        let recorded = Recorded { at: self.started.elapsed().as_millis() as u64, event: event.clone() };
+        let line = serde_json::to_string(&recorded).unwrap_or_default();
+        // Written as it happens, so a crash still leaves the keys that led to it
+        writeln!(self.file, "{}", line).map_err(|e| Error::io(&self.path, e))
+    }
+}
+
+// A recording's events and how long after the start each is due
+pub fn load(path: &Path) -> Result<Vec<(Duration, Event)>> {
+    let content = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match serde_json::from_str::<Recorded>(line) {
+            Ok(recorded) => Ok((Duration::from_millis(recorded.at), recorded.event)),
+            Err(e) => Err(Error::config(path, format!("line {}: {}", i + 1, e))),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    #[test]
+    fn recordings_load_back() {
+        let path = std::env::temp_dir().join(format!("rpomodoro-test-recording-{}.log", std::process::id()));
+        let mut recorder = Recorder::create(&path).unwrap();
+        recorder.record(&Event::Key(KeyEvent::from(KeyCode::Char(' ')))).unwrap();
+        recorder.record(&Event::Resize(80, 24)).unwrap();
+        recorder.record(&Event::Key(KeyEvent::from(KeyCode::Char('q')))).unwrap();
+
+        let events: Vec<Event> = load(&path).unwrap().into_iter().map(|(_, event)| event).collect();
+        assert_eq!(events, [Event::Key(KeyEvent::from(KeyCode::Char(' '))), Event::Key(KeyEvent::from(KeyCode::Char('q')))]);
+
+        fs::write(&path, "{\"at\": 5}\n").unwrap();
+        assert!(load(&path).unwrap_err().to_string().contains("line 1"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,58 @@
+// tty-clock style: 3x5 matrix, each cell is 2 chars wide
+// This matches the exact tty-clock implementation
+pub const DIGITS: [[[bool; 3]; 5]; 10] = [
+    // 0
+    [[true, true, true], [true, false, true], [true, false, true], [true, false, true], [true, true, true]],
+    // 1
+    [[false, false, true], [false, false, true], [false, false, true], [false, false, true], [false, false, true]],
+    // 2
+    [[true, true, true], [false, false, true], [true, true, true], [true, false, false], [true, true, true]],
+    // 3
+    [[true, true, true], [false, false, true], [true, true, true], [false, false, true], [true, true, true]],
+    // 4
+    [[true, false, true], [true, false, true], [true, true, true], [false, false, true], [false, false, true]],
+    // 5
+    [[true, true, true], [true, false, false], [true, true, true], [false, false, true], [true, true, true]],
+    // 6
+    [[true, true, true], [true, false, false], [true, true, true], [true, false, true], [true, true, true]],
+    // 7
+    [[true, true, true], [false, false, true], [false, false, true], [false, false, true], [false, false, true]],
+    // 8
+    [[true, true, true], [true, false, true], [true, true, true], [true, false, true], [true, true, true]],
+    // 9
+    [[true, true, true], [true, false, true], [true, true, true], [false, false, true], [true, true, true]],
+];
+
+// How the matrix is drawn for each "clock_style": the size of one of its
+// cells in terminal cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockFont {
+    pub cell_width: u16,
+    pub cell_height: u16,
+}
+
+impl ClockFont {
+    pub const CLASSIC: ClockFont = ClockFont { cell_width: 2, cell_height: 1 };
+    // 6x10, twice the classic size
+    pub const LARGE: ClockFont = ClockFont { cell_width: 4, cell_height: 2 };
+    // Single width, for narrow panes
+    pub const SLIM: ClockFont = ClockFont { cell_width: 1, cell_height: 1 };
+
+    pub fn from_style(style: &str) -> Self {
+        match style {
+            "large" => ClockFont::LARGE,
+            "slim" => ClockFont::SLIM,
+            _ => ClockFont::CLASSIC,
+        }
+    }
+
+    // mm:ss is four digits 3 cells wide, a 1 cell gap inside each pair, and
+    // the colon with a 1 cell gap either side: 17 cells
+    pub fn clock_width(self) -> u16 {
+        17 * self.cell_width
+    }
+
+    pub fn clock_height(self) -> u16 {
+        5 * self.cell_height
+    }
+}
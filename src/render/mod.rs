@@ -0,0 +1,818 @@
+pub mod digits;
+pub mod overlay;
+pub mod screen;
+pub mod segments;
+pub mod stats;
+pub mod theme_editor;
+
+use crossterm::style::Color;
+use std::time::Duration;
+
+use crate::activity::BreakActivity;
+use crate::app::{App, PomodoroState, SkipRequest, SKIP_COOLDOWN};
+use crate::settings::{Setting, SETTINGS};
+use crate::theme::{blend, parse_hex_color, pulse};
+use digits::{ClockFont, DIGITS};
+use screen::{Chunk, Screen};
+use segments::Segment;
+
+// Smallest terminal the clock and statusline fit in; anything smaller
+// gets a notice instead
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 10;
+// How far toward black the clock dims at the bottom of a breath
+const BREATH_DEPTH: f32 = 0.6;
+
+impl App {
+    // Whichever screen the app is on
+    pub fn draw_frame(&self, screen: &mut Screen) {
+        screen.rtl = self.rtl();
+        if self.width < MIN_WIDTH || self.height < MIN_HEIGHT {
+            self.draw_too_small(screen);
+        } else if self.theme_editor.is_some() {
+            self.draw_theme_editor(screen);
+        } else if self.config_mode {
+            self.draw_config(screen);
+        } else if self.stats_view.is_some() {
+            self.draw_stats(screen);
+        } else if self.task_pane.is_some() {
+            self.draw_tasks(screen);
+        } else if self.show_integrations {
+            self.draw_integrations(screen);
+        } else if let Some(activity) = &self.break_activity {
+            self.draw_break_activity(screen, activity.as_ref());
+        } else {
+            self.draw(screen);
+            if self.paused_dimmed() {
+                screen.tint(self.theme.dim);
+            }
+        }
+    }
+
+    // Paused for at least "dim_paused_seconds"
+    pub fn paused_dimmed(&self) -> bool {
+        let after = Duration::from_secs(self.config.dim_paused_seconds as u64);
+        self.config.dim_paused_seconds > 0 && self.paused_since.is_some_and(|since| since.elapsed() >= after)
+    }
+
+    // Centered, and clipped by the screen on terminals too small for even
+    // this
+    pub fn draw_too_small(&self, screen: &mut Screen) {
+        let lines = ["terminal too small".to_string(), format!("need {}x{}, have {}x{}", MIN_WIDTH, MIN_HEIGHT, self.width, self.height)];
+        let top = (self.height / 2).saturating_sub(1);
+        for (i, line) in lines.iter().enumerate() {
+            let x = (self.width / 2).saturating_sub(line.chars().count() as u16 / 2);
+            screen.print(x, top + i as u16, line, if i == 0 { self.theme.urgent } else { self.theme.dim });
+        }
+    }
+
+    pub fn draw(&self, screen: &mut Screen) {
+        let center_x = self.width / 2;
+        let center_y = self.height / 2;
+
+        // Draw large clock, or the dial when there's room for it. Everything
+        // shown below the timer (wall clock, tomato row) is stacked under it.
+        // The dial's hand already shows progress, so the bar is only for the
+        // digits.
+        let wall_rows = if self.config.show_wall_clock { 4 } else { 0 };
+        let tomato_rows = if self.config.show_tomatoes { self.tomato_rows() } else { 0 };
+        let reserved = wall_rows + if tomato_rows > 0 { tomato_rows + 1 } else { 0 };
+        let progress_rows = if self.config.show_progress { 2 } else { 0 };
+
+        let analog = self.config.clock_face == "analog"
+            && self.draw_analog(screen, center_x, center_y.saturating_sub(reserved / 2), reserved);
+        let below_y = if analog {
+            self.height.saturating_sub(1 + reserved)
+        } else {
+            let font = self.clock_font(reserved + progress_rows);
+            let clock_rows = font.clock_height() + 1;
+            let clock_y = center_y.saturating_sub(clock_rows / 2 + (reserved + progress_rows) / 2);
+            self.draw_clock(screen, center_x, clock_y, font);
+            if let Some(task) = self.tasks.active_task().filter(|_| clock_y >= 2) {
+                self.draw_task_title(screen, center_x, clock_y - 2, &task.title);
+            }
+            if self.config.show_progress {
+                self.draw_progress(screen, center_x, clock_y + clock_rows, font.clock_width());
+            }
+            clock_y + clock_rows + progress_rows
+        };
+
+        if self.config.show_wall_clock {
+            self.draw_wall_clock(screen, center_x, below_y);
+        }
+        if tomato_rows > 0 {
+            self.draw_tomatoes(screen, center_x, below_y + wall_rows);
+        }
+
+        // Draw minimal status bar at bottom
+        self.draw_statusline(screen);
+    }
+
+    // Digit color, fading toward the urgent color near the end of a work
+    // session and urgent past the warning or in overtime, and breathing
+    // during running breaks when a pace is set
+    pub fn clock_color(&self) -> Color {
+        if self.engine.overtime.is_some() {
+            return self.theme.urgent;
+        }
+        if self.engine.state != PomodoroState::Work && self.config.breathing_pace > 0 && !self.engine.paused {
+            let elapsed = self.engine.counted().as_secs_f32();
+            return pulse(self.theme.primary, elapsed, self.config.breathing_pace as f32, BREATH_DEPTH);
+        }
+        let warning = Duration::from_secs(self.config.warning_minutes as u64 * 60);
+        if self.engine.state == PomodoroState::Work && self.engine.time_remaining <= warning {
+            return self.theme.urgent;
+        }
+        let window = Duration::from_secs(self.config.urgency_minutes as u64 * 60);
+        if self.engine.state != PomodoroState::Work || window.is_zero() || self.engine.time_remaining >= window {
+            return self.theme.primary;
+        }
+
+        let t = 1.0 - self.engine.time_remaining.as_secs_f32() / window.as_secs_f32();
+        blend(self.theme.primary, self.theme.urgent, t)
+    }
+
+    // The "clock_style" font, or classic when large wouldn't fit beside the
+    // margins and above the `reserved` rows and the statusline
+    pub fn clock_font(&self, reserved: u16) -> ClockFont {
+        let font = ClockFont::from_style(&self.config.clock_style);
+        if font == ClockFont::LARGE && (self.width < font.clock_width() + 4 || self.height < font.clock_height() + 3 + reserved) {
+            return ClockFont::CLASSIC;
+        }
+        font
+    }
+
+    // One cell of the digit matrix, `font` sized. Block style paints lit
+    // cells with the foreground color; inverted style paints unlit cells as
+    // background and leaves lit ones as the terminal's own background.
+    pub fn draw_cell(&self, screen: &mut Screen, x: u16, y: u16, font: ClockFont, lit: bool, color: Color) {
+        for row in 0..font.cell_height {
+            if self.config.digit_style == "inverted" {
+                let bg = if lit { Color::Reset } else { color };
+                screen.put(x, y + row, &" ".repeat(font.cell_width as usize), Color::Reset, bg);
+            } else if lit {
+                screen.print(x, y + row, &"█".repeat(font.cell_width as usize), color);
+            }
+        }
+    }
+
+    pub fn draw_digit(&self, screen: &mut Screen, digit: usize, x: u16, y: u16, font: ClockFont, color: Color) {
+        for (row, cells) in DIGITS[digit].iter().enumerate() {
+            for (col, &lit) in cells.iter().enumerate() {
+                self.draw_cell(screen, x + col as u16 * font.cell_width, y + row as u16 * font.cell_height, font, lit, color);
+            }
+        }
+    }
+
+    pub fn draw_colon(&self, screen: &mut Screen, x: u16, y: u16, font: ClockFont, color: Color) {
+        self.draw_cell(screen, x, y + font.cell_height, font, true, color);
+        self.draw_cell(screen, x, y + 3 * font.cell_height, font, true, color);
+    }
+
+    // Solid block behind the clock for the inverted style, with a margin of
+    // 2 columns and 1 row
+    pub fn draw_clock_backdrop(&self, screen: &mut Screen, x: u16, y: u16, width: u16, height: u16, color: Color) {
+        let x = x.saturating_sub(2);
+        let y = y.saturating_sub(1);
+        let fill = " ".repeat(width as usize + 4);
+
+        for row in 0..height + 2 {
+            screen.put(x, y + row, &fill, Color::Reset, color);
+        }
+    }
+
+    // The big clock's two pairs of digits: time left, or counted in
+    // stopwatch mode or overtime, as mm:ss, or hh:mm from 100 minutes on
+    pub fn clock_time(&self) -> (u64, u64) {
+        let total_secs = self.engine.stopwatch.or(self.engine.overtime).unwrap_or(self.engine.time_remaining).as_secs();
+        if total_secs < 100 * 60 {
+            return (total_secs / 60, total_secs % 60);
+        }
+        ((total_secs / 3600).min(99), total_secs / 60 % 60)
+    }
+
+    // The clock as text, "+" in front in overtime
+    pub fn clock_text(&self) -> String {
+        let (high, low) = self.clock_time();
+        let sign = if self.engine.overtime.is_some() { "+" } else { "" };
+        format!("{}{:02}:{:02}", sign, high, low)
+    }
+
+    pub fn draw_clock(&self, screen: &mut Screen, center_x: u16, y: u16, font: ClockFont) {
+        let (high, low) = self.clock_time();
+
+        let digit1 = (high / 10) as usize;
+        let digit2 = (high % 10) as usize;
+        let digit3 = (low / 10) as usize;
+        let digit4 = (low % 10) as usize;
+
+        // Each digit is 3 cells wide, with 1 cell spacing between digit
+        // pairs and on each side of the 1 cell colon: 34 chars for classic
+        let cell = font.cell_width;
+        let total_width = font.clock_width();
+        let start_x = center_x.saturating_sub(total_width / 2);
+        let color = self.clock_color();
+
+        if self.config.digit_style == "inverted" {
+            self.draw_clock_backdrop(screen, start_x, y, total_width, font.clock_height(), color);
+        }
+
+        // Draw minutes
+        self.draw_digit(screen, digit1, start_x, y, font, color);
+        self.draw_digit(screen, digit2, start_x + 4 * cell, y, font, color);
+
+        // Draw colon
+        self.draw_colon(screen, start_x + 8 * cell, y, font, color);
+
+        // Draw seconds
+        self.draw_digit(screen, digit3, start_x + 10 * cell, y, font, color);
+        self.draw_digit(screen, digit4, start_x + 14 * cell, y, font, color);
+    }
+
+    // How far through the session, as a bar the width of the clock. The
+    // filled part ends in a partial block for eighths of a cell.
+    pub fn draw_progress(&self, screen: &mut Screen, center_x: u16, y: u16, width: u16) {
+        const PARTS: [&str; 8] = [" ", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+        let total = self.session_length().as_secs_f32();
+        let done = if total > 0.0 { self.engine.counted().as_secs_f32() / total } else { 0.0 };
+        let eighths = (done.clamp(0.0, 1.0) * width as f32 * 8.0) as u16;
+        let (full, part) = (eighths / 8, eighths % 8);
+
+        let x = center_x.saturating_sub(width / 2);
+        screen.print(x, y, &"░".repeat(width as usize), self.theme.dim);
+        screen.print(x, y, &"█".repeat(full as usize), self.clock_color());
+        if part > 0 {
+            screen.print(x + full, y, PARTS[part as usize], self.clock_color());
+        }
+    }
+
+    // Time of day in half-height digits: each pair of matrix rows is folded
+    // into one terminal row using upper/lower half blocks, so a digit is 3x3.
+    pub fn draw_wall_clock(&self, screen: &mut Screen, center_x: u16, y: u16) {
+        let (time, marker) = self.locale.split_time(&chrono::Local::now(), self.hour12());
+
+        // Digits are 3 cols and the colon 1 col, each followed by a 1 col gap
+        let total_width = time.chars().map(|c| if c == ':' { 2 } else { 4 }).sum::<u16>() - 1;
+        let mut x = center_x.saturating_sub(total_width / 2);
+
+        for ch in time.chars() {
+            let cols: Vec<[bool; 5]> = match ch.to_digit(10) {
+                Some(d) => (0..3).map(|c| std::array::from_fn(|r| DIGITS[d as usize][r][c])).collect(),
+                None => vec![[false, true, false, true, false]],
+            };
+            for row in 0..3 {
+                for (i, col) in cols.iter().enumerate() {
+                    let top = col[row * 2];
+                    let bottom = row * 2 + 1 < 5 && col[row * 2 + 1];
+                    let glyph = match (top, bottom) {
+                        (true, true) => "█",
+                        (true, false) => "▀",
+                        (false, true) => "▄",
+                        (false, false) => continue,
+                    };
+                    screen.print(x + i as u16, y + row as u16, glyph, self.theme.dim);
+                }
+            }
+            x += cols.len() as u16 + 1;
+        }
+        if let Some(marker) = marker {
+            screen.print(x, y + 2, marker, self.theme.dim);
+        }
+    }
+
+    pub fn hour12(&self) -> bool {
+        match self.config.clock_format.as_str() {
+            "12h" => true,
+            "24h" => false,
+            _ => self.locale.hour12,
+        }
+    }
+
+    pub fn rtl(&self) -> bool {
+        match self.config.text_direction.as_str() {
+            "rtl" => true,
+            "ltr" => false,
+            _ => self.locale.rtl,
+        }
+    }
+
+    // Tomatoes per row and rows needed for max(completed today, daily goal),
+    // wrapping to the terminal width and capped at 3 rows
+    pub fn tomato_layout(&self) -> (u16, u16) {
+        let count = self.stats.completed_today().max(self.config.daily_goal);
+        let per_row = (self.width.saturating_sub(4) / 2).clamp(1, 20);
+        let rows = count.div_ceil(per_row as u32).min(3) as u16;
+        (per_row, rows)
+    }
+
+    pub fn tomato_rows(&self) -> u16 {
+        self.tomato_layout().1
+    }
+
+    // One filled glyph per completed pomodoro today, hollow ones up to the goal
+    pub fn draw_tomatoes(&self, screen: &mut Screen, center_x: u16, y: u16) {
+        let completed = self.stats.completed_today();
+        let count = completed.max(self.config.daily_goal);
+        let (per_row, rows) = self.tomato_layout();
+        let shown = count.min(per_row as u32 * rows as u32);
+
+        for row in 0..rows {
+            let first = row as u32 * per_row as u32;
+            let in_row = (shown - first).min(per_row as u32);
+            let mut x = center_x.saturating_sub(in_row as u16);
+
+            for i in first..first + in_row {
+                if i < completed {
+                    screen.print(x, y + row, "●", self.theme.primary);
+                } else {
+                    screen.print(x, y + row, "○", self.theme.dim);
+                }
+                x += 2;
+            }
+        }
+    }
+
+    // Fraction of the current session that has elapsed, 0.0..=1.0
+    pub fn progress(&self) -> f32 {
+        let total = self.session_length().as_secs_f32();
+        if total == 0.0 {
+            return 1.0;
+        }
+        (self.engine.counted().as_secs_f32() / total).clamp(0.0, 1.0)
+    }
+
+    // Dial made of dots with a hand sweeping clockwise from 12 o'clock as the
+    // session progresses. Cells are roughly twice as tall as they are wide, so
+    // the horizontal radius is doubled. Returns false if the terminal is too
+    // small for a legible dial.
+    pub fn draw_analog(&self, screen: &mut Screen, center_x: u16, center_y: u16, reserved: u16) -> bool {
+        // Leave a row for the statusline and one of margin on each side
+        let radius_y = (self.height.saturating_sub(4 + reserved) / 2).min(self.width.saturating_sub(4) / 4);
+        if radius_y < 4 {
+            return false;
+        }
+        let radius_y = radius_y as f32;
+        let radius_x = radius_y * 2.0;
+        let (cx, cy) = (center_x as f32, center_y as f32);
+        let color = self.clock_color();
+        let progress = self.progress();
+
+        let mut plot = |x: f32, y: f32, glyph: &str, color: Color| {
+            screen.print(x.round().max(0.0) as u16, y.round().max(0.0) as u16, glyph, color);
+        };
+
+        // Rim: elapsed part lit, remainder dimmed
+        let steps = (radius_x * 8.0) as usize;
+        for step in 0..steps {
+            let fraction = step as f32 / steps as f32;
+            let angle = fraction * std::f32::consts::TAU;
+            let (x, y) = (cx + radius_x * angle.sin(), cy - radius_y * angle.cos());
+            if fraction <= progress {
+                plot(x, y, "█", color);
+            } else {
+                plot(x, y, "·", self.theme.dim);
+            }
+        }
+
+        // Hand
+        let angle = progress * std::f32::consts::TAU;
+        let length = (radius_y * 0.75) as usize * 2;
+        for step in 1..=length {
+            let t = step as f32 / length as f32;
+            let (x, y) = (cx + radius_x * 0.75 * t * angle.sin(), cy - radius_y * 0.75 * t * angle.cos());
+            plot(x, y, "•", color);
+        }
+        plot(cx, cy, "●", color);
+
+        // Remaining time under the hub
+        let label = self.clock_text();
+        let label_y = if progress > 0.25 && progress < 0.75 { cy - 2.0 } else { cy + 2.0 };
+        plot(cx - (label.len() / 2) as f32, label_y, &label, self.theme.dim);
+
+        true
+    }
+
+    // Statusline content as named segments, grouped left, center and right
+    // in the order the config lists them
+    pub fn statusline_groups(&self) -> [Vec<(&'static str, String)>; 3] {
+        let statusline = &self.config.statusline;
+        let group = |names: &[String]| -> Vec<(&'static str, String)> {
+            names
+                .iter()
+                .filter_map(|name| segments::find(name))
+                .filter_map(|segment| Some((segment.name(), segment.text(self)?)))
+                .collect()
+        };
+        let mut center = group(&statusline.center);
+
+        // Errors take over the center while they're fresh, and a pending
+        // break skip over everything
+        if let Some(message) = self.active_toast() {
+            center = vec![("error", message.to_string())];
+        }
+        if self.escalation.as_ref().is_some_and(|e| e.flashing) {
+            let mode = segments::Mode.text(self).unwrap_or_default();
+            center = vec![("alert", format!("{} is up, any key stops the alerts", mode))];
+        }
+        match &self.skip_request {
+            Some(SkipRequest::Justify(reason)) => {
+                center = vec![("prompt", format!("why skip this break? {}_  (enter: skip, esc: cancel)", reason))];
+            }
+            Some(SkipRequest::Cooldown(since)) => {
+                let left = SKIP_COOLDOWN.saturating_sub(since.elapsed()).as_secs_f32().ceil();
+                center = vec![("prompt", format!("skipping break in {}s, any key cancels", left))];
+            }
+            None => {}
+        }
+        if let Some(gap) = self.sleep_gap {
+            center = vec![("prompt", format!("asleep {} min: count it? y: yes, n: no, other keys stay paused", gap.as_secs().div_ceil(60)))];
+        }
+        if let Some(label) = &self.lap_label {
+            center = vec![("prompt", format!("lap {} label: {}_  (enter: mark, esc: cancel)", self.laps.len() + 1, label))];
+        }
+        if let (Some(input), Some((hint, valid))) = (&self.countdown_input, self.countdown_hint()) {
+            center = vec![("prompt", format!("countdown for {}_", input)), (if valid { "dim" } else { "error" }, hint)];
+        }
+        if let (Some(line), Some((hint, valid))) = (&self.palette, self.palette_hint()) {
+            center = vec![("prompt", format!(":{}_", line)), (if valid { "dim" } else { "error" }, hint)];
+        }
+
+        [group(&statusline.left), center, group(&statusline.right)]
+    }
+
+    pub fn segment_color(&self, name: &str) -> Color {
+        let default = match segments::find(name) {
+            Some(segment) => segment.default_color(),
+            None if name == "error" => "urgent",
+            None if name == "prompt" => "primary",
+            None if name == "alert" && self.escalation.as_ref().is_some_and(|e| e.lit()) => "urgent",
+            None => "dim",
+        };
+        let spec = self.config.statusline.colors.get(name).map(String::as_str).unwrap_or(default);
+        match spec {
+            "primary" => self.theme.primary,
+            "dim" => self.theme.dim,
+            "urgent" => self.theme.urgent,
+            hex => parse_hex_color(hex).unwrap_or(self.theme.dim),
+        }
+    }
+
+    // Lay out one group as styled chunks. Plain mode joins segments with the
+    // configured separator; powerline mode draws each segment as a colored
+    // block with arrow glyphs pointing away from the screen edge.
+    pub fn render_group(&self, group: &[(&'static str, String)], points_left: bool) -> Vec<Chunk> {
+        let statusline = &self.config.statusline;
+        let colors: Vec<Color> = group.iter().map(|(name, _)| self.segment_color(name)).collect();
+        let mut chunks = Vec::new();
+
+        if !statusline.powerline {
+            for (i, (_, text)) in group.iter().enumerate() {
+                let text = if i == 0 { format!(" {}", text) } else { format!(" {} {}", statusline.separator, text) };
+                chunks.push(Chunk::new(text, colors[i], Color::Reset));
+            }
+            if let Some(&last) = colors.last() {
+                chunks.push(Chunk::new(" ".to_string(), last, Color::Reset));
+            }
+            return chunks;
+        }
+
+        for (i, (_, text)) in group.iter().enumerate() {
+            let block = Chunk::new(format!(" {} ", text), Color::Black, colors[i]);
+            if points_left {
+                let outer = if i == 0 { Color::Reset } else { colors[i - 1] };
+                chunks.push(Chunk::new(statusline.powerline_left.clone(), colors[i], outer));
+                chunks.push(block);
+            } else {
+                let outer = colors.get(i + 1).copied().unwrap_or(Color::Reset);
+                chunks.push(block);
+                chunks.push(Chunk::new(statusline.powerline_right.clone(), colors[i], outer));
+            }
+        }
+        chunks
+    }
+
+    pub fn draw_chunks(&self, screen: &mut Screen, chunks: &[Chunk], mut x: u16, y: u16) {
+        for chunk in chunks {
+            screen.put(x, y, &chunk.text, chunk.fg, chunk.bg);
+            x += chunk.text.chars().count() as u16;
+        }
+    }
+
+    pub fn draw_statusline(&self, screen: &mut Screen) {
+        let y = self.height.saturating_sub(1);
+
+        // Right to left, the line is read from the right edge: the groups
+        // swap sides and their segments run the other way
+        let [mut left, mut center, mut right] = self.statusline_groups();
+        if self.rtl() {
+            std::mem::swap(&mut left, &mut right);
+            for group in [&mut left, &mut center, &mut right] {
+                group.reverse();
+            }
+        }
+
+        let left = self.render_group(&left, false);
+        self.draw_chunks(screen, &left, 0, y);
+
+        let center = self.render_group(&center, false);
+        let center_x = (self.width / 2).saturating_sub(Chunk::width(&center) / 2);
+        self.draw_chunks(screen, &center, center_x, y);
+
+        let right = self.render_group(&right, true);
+        let right_x = self.width.saturating_sub(Chunk::width(&right));
+        self.draw_chunks(screen, &right, right_x, y);
+    }
+
+    // Frame-time/CPU overlay in the top right corner, toggled with F12
+    pub fn draw_overlay(&self, screen: &mut Screen) {
+        let summary = self.frame_stats.summary();
+        let x = self.width.saturating_sub(summary.chars().count() as u16);
+        screen.put(x, 0, &summary, Color::Black, self.theme.dim);
+    }
+
+    // Settings panel over the dimmed clock, so theme and style changes can
+    // be judged in place before saving
+    pub fn draw_config(&self, screen: &mut Screen) {
+        self.draw(screen);
+        screen.dim();
+
+        let view = &self.config_view;
+        let header = match &view.search {
+            Some(query) => format!("search: {}{}", query, if view.typing { "_" } else { "" }),
+            None => format!("{} ({}/{})", SETTINGS[view.page].0, view.page + 1, SETTINGS.len()),
+        };
+        let rows = view.rows();
+        // The header, the border and the help line take four rows; longer
+        // pages scroll with the cursor
+        let fits = (self.height.saturating_sub(4) as usize).max(1);
+        let first = (view.cursor + 1).saturating_sub(fits);
+        let mut lines = vec![header];
+        for (i, setting) in rows.iter().enumerate().skip(first).take(fits) {
+            let edit = if *setting == Setting::Theme && i == view.cursor { "  (enter: edit)" } else { "" };
+            lines.push(format!("{}{}: {}{}", if i == view.cursor { "> " } else { "  " }, setting.label(), setting.value(self), edit));
+        }
+        if rows.is_empty() {
+            lines.push("  no matching settings".to_string());
+        }
+        self.draw_panel(screen, &lines, Some(view.cursor - first + 1).filter(|_| !rows.is_empty()));
+        let help = if view.typing { " search | enter:keep  esc:clear " } else { " config | h/l:change  tab:page  /:search  q/esc:save " };
+        self.draw_help(screen, help);
+
+        // The big clock is dimmed behind the panel, so the session's time is
+        // repeated in the statusline's corner
+        let mode = segments::Mode.text(self).unwrap_or_default();
+        let time = segments::Time.text(self).unwrap_or_default();
+        let color = if self.engine.paused { self.theme.dim } else { self.theme.primary };
+        screen.print(0, self.height.saturating_sub(1), &format!(" {} {} ", mode, time), color);
+    }
+
+    // Each enabled integration and whether it's working, with its last
+    // error, over the dimmed clock
+    pub fn draw_integrations(&self, screen: &mut Screen) {
+        self.draw(screen);
+        screen.dim();
+
+        let reports = self.health.reports();
+        let mut lines: Vec<String> = Vec::new();
+        for (name, report) in &reports {
+            lines.push(format!("{} {}: {}", if report.healthy { "●" } else { "✗" }, name, if report.healthy { "ok" } else { "failing" }));
+            if let Some((at, reason)) = &report.last_error {
+                lines.push(format!("    last error {}: {}", at, reason));
+            }
+        }
+        if lines.is_empty() {
+            lines.push("no integrations enabled".to_string());
+        }
+        self.draw_panel(screen, &lines, None);
+        self.draw_help(screen, &format!(" integrations | {}/q/esc:close ", self.config.keybindings.integrations));
+    }
+
+    // The break's activity over the dimmed clock, with the statusline left
+    // bright for the break's time and any prompt
+    pub fn draw_break_activity(&self, screen: &mut Screen, activity: &dyn BreakActivity) {
+        self.draw(screen);
+        screen.dim();
+        let mut lines = vec![activity.title()];
+        lines.extend(activity.lines());
+        lines.push(activity.help().to_string());
+        self.draw_panel(screen, &lines, Some(0));
+        self.draw_statusline(screen);
+    }
+
+    // The active task, centered over the digital clock and cut to fit
+    pub fn draw_task_title(&self, screen: &mut Screen, center_x: u16, y: u16, title: &str) {
+        let width = self.width.saturating_sub(4) as usize;
+        let title: String = title.chars().take(width).collect();
+        let x = center_x.saturating_sub(title.chars().count() as u16 / 2);
+        screen.print(x, y, &title, self.theme.dim);
+    }
+
+    // The task list with each task's pomodoros, the active one marked, over
+    // the dimmed clock
+    pub fn draw_tasks(&self, screen: &mut Screen) {
+        self.draw(screen);
+        screen.dim();
+
+        let Some(pane) = &self.task_pane else {
+            return;
+        };
+        let mut lines: Vec<String> = self
+            .tasks
+            .tasks
+            .iter()
+            .enumerate()
+            .map(|(i, task)| {
+                let cursor = if i == pane.cursor && pane.new_title.is_none() { ">" } else { " " };
+                let active = if self.tasks.active == Some(i) { "●" } else { " " };
+                format!("{}{} {} ({})", cursor, active, task.title, task.pomodoros)
+            })
+            .collect();
+        if self.tasks.tasks.is_empty() {
+            lines.push("no tasks yet".to_string());
+        }
+        let highlight = match &pane.new_title {
+            Some(title) => {
+                lines.push(format!("new task: {}_", title));
+                lines.len() - 1
+            }
+            None => pane.cursor,
+        };
+        self.draw_panel(screen, &lines, Some(highlight));
+        let help = if pane.new_title.is_some() { " new task | enter:add  esc:cancel " } else { " tasks | j/k:navigate  enter:select  n:new  d:delete  q/esc:close " };
+        self.draw_help(screen, help);
+    }
+
+    // A bordered, double spaced panel when it fits above the statusline,
+    // shrinking to a bare single spaced list on small terminals. The
+    // `highlight` line is drawn in the primary color.
+    pub fn draw_panel(&self, screen: &mut Screen, lines: &[String], highlight: Option<usize>) {
+        let rows = self.height.saturating_sub(1);
+        let count = lines.len() as u16;
+        let spacing = if count * 2 + 1 < rows { 2 } else { 1 };
+        let inner_width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as u16 + 2;
+        let inner_height = count * spacing - (spacing - 1);
+        let border = inner_height + 2 <= rows && inner_width + 2 <= self.width;
+        let (width, height) = if border { (inner_width + 2, inner_height + 2) } else { (inner_width, inner_height) };
+        let left = (self.width.saturating_sub(width)) / 2;
+        let top = (rows.saturating_sub(height)) / 2;
+
+        for y in top..(top + height).min(rows) {
+            screen.print(left, y, &" ".repeat(width as usize), self.theme.dim);
+        }
+        if border {
+            let horizontal = "─".repeat(inner_width as usize);
+            screen.print(left, top, &format!("╭{}╮", horizontal), self.theme.dim);
+            for y in top + 1..top + height - 1 {
+                screen.print(left, y, "│", self.theme.dim);
+                screen.print(left + width - 1, y, "│", self.theme.dim);
+            }
+            screen.print(left, top + height - 1, &format!("╰{}╯", horizontal), self.theme.dim);
+        }
+
+        let (text_x, text_y) = if border { (left + 1, top + 1) } else { (left, top) };
+        for (i, line) in lines.iter().enumerate() {
+            let color = if Some(i) == highlight { self.theme.primary } else { self.theme.dim };
+            screen.print(text_x, text_y + i as u16 * spacing, line, color);
+        }
+    }
+
+    // Statusline replacement for the panel screens
+    pub fn draw_help(&self, screen: &mut Screen, help: &str) {
+        let y = self.height.saturating_sub(1);
+        screen.print(0, y, &" ".repeat(self.width as usize), self.theme.dim);
+        let help_x = (self.width / 2).saturating_sub((help.len() / 2) as u16);
+        screen.print(help_x, y, help, self.theme.primary);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::history::StatsView;
+    use crate::tasks::TaskPane;
+    use crate::theme::ThemeEditor;
+    use std::time::Instant;
+
+    // Every screen at a few representative terminal sizes, through the
+    // headless Screen backend
+    #[test]
+    fn screen_snapshots() {
+        let sizes = [(80, 24), (40, 12), (200, 50)];
+        let config = Config { time_locale: "en-GB".to_string(), ..Config::default() };
+        type Setup = fn(&mut App);
+        let screens: [(&str, Setup); 13] = [
+            ("clock", |_| {}),
+            ("clock_analog", |app| {
+                app.config.clock_face = "analog".to_string();
+                app.engine.time_remaining = Duration::from_secs(10 * 60);
+            }),
+            ("clock_inverted", |app| app.config.digit_style = "inverted".to_string()),
+            ("clock_large", |app| app.config.clock_style = "large".to_string()),
+            ("clock_slim", |app| app.config.clock_style = "slim".to_string()),
+            ("clock_progress", |app| {
+                app.config.show_progress = true;
+                app.engine.time_remaining = Duration::from_secs(10 * 60 + 20);
+            }),
+            ("clock_task", |app| {
+                app.tasks.add("write the quarterly report");
+                app.tasks.select(0);
+            }),
+            ("config", |app| app.config_mode = true),
+            ("theme_editor", |app| {
+                app.config_mode = true;
+                app.theme_editor = Some(ThemeEditor::new(&app.theme));
+            }),
+            ("stats", |app| {
+                let days = ["Tue", "Wed", "Thu", "Fri", "Sat", "Sun", "Mon"];
+                let counts = [6, 8, 0, 3, 1, 0, 4];
+                let days = days.iter().zip(counts).map(|(day, count)| (day.to_string(), count)).collect();
+                app.stats_view = Some(StatsView { profile: None, completed_today: 4, focus_minutes_today: 100, days });
+                app.stats_cursor = 6;
+            }),
+            ("integrations", |app| {
+                app.show_integrations = true;
+                app.health.ok("control channel");
+                app.health.failed_at("remote", "09:41:00".to_string(), "Address already in use (os error 98)");
+            }),
+            ("tasks", |app| {
+                for (title, pomodoros) in [("write the quarterly report", 3), ("review PRs", 1)] {
+                    app.tasks.add(title);
+                    app.tasks.tasks.last_mut().unwrap().pomodoros = pomodoros;
+                }
+                app.tasks.select(0);
+                app.task_pane = Some(TaskPane { cursor: 1, new_title: None });
+            }),
+            ("break_activity", |app| {
+                app.engine.state = PomodoroState::ShortBreak;
+                app.engine.time_remaining = Duration::from_secs(5 * 60);
+                app.break_activity = crate::activity::new_activity("2048", 1);
+            }),
+        ];
+
+        for (name, setup) in screens {
+            for (width, height) in sizes {
+                let mut app = App::for_test(config.clone());
+                app.width = width;
+                app.height = height;
+                setup(&mut app);
+
+                let mut screen = Screen::new(width, height);
+                app.draw_frame(&mut screen);
+                insta::assert_snapshot!(format!("{}_{}x{}", name, width, height), screen.text());
+            }
+        }
+    }
+
+    #[test]
+    fn a_long_pause_dims_the_whole_screen() {
+        let mut app = App::for_test(Config { dim_paused_seconds: 60, ..Config::default() });
+        let colors = |app: &App| {
+            let mut screen = Screen::new(80, 24);
+            app.draw_frame(&mut screen);
+            screen.cells.iter().map(|cell| cell.fg).filter(|&fg| fg != Color::Reset).collect::<Vec<Color>>()
+        };
+        app.update().unwrap();
+        assert!(colors(&app).contains(&app.theme.primary), "not dimmed straight away");
+
+        app.paused_since = Some(Instant::now() - Duration::from_secs(60));
+        assert!(colors(&app).iter().all(|&fg| fg == app.theme.dim));
+        app.toggle_pause();
+        app.update().unwrap();
+        assert!(colors(&app).contains(&app.theme.primary));
+    }
+
+    #[test]
+    fn right_to_left_mirrors_the_statusline() {
+        let statusline = |direction: &str| {
+            let mut app = App::for_test(Config { text_direction: direction.to_string(), ..Config::default() });
+            (app.width, app.height) = (80, 24);
+            let mut screen = Screen::new(80, 24);
+            app.draw_frame(&mut screen);
+            screen.text().lines().last().unwrap_or_default().to_string()
+        };
+        let (ltr, rtl) = (statusline("ltr"), statusline("rtl"));
+        assert!(ltr.trim_start().starts_with("work"), "{:?}", ltr);
+        assert!(rtl.trim_end().ends_with("work"), "{:?}", rtl);
+    }
+
+    #[test]
+    fn tiny_terminals_get_a_notice() {
+        let mut app = App::for_test(Config::default());
+        app.theme_editor = Some(ThemeEditor::new(&app.theme));
+        (app.width, app.height) = (30, 6);
+        let mut screen = Screen::new(30, 6);
+        app.draw_frame(&mut screen);
+        insta::assert_snapshot!("too_small_30x6", screen.text());
+
+        // Each screen also survives being drawn at any size, down to nothing
+        type Draw = fn(&App, &mut Screen);
+        let draws: [Draw; 7] = [App::draw_frame, App::draw, App::draw_config, App::draw_integrations, App::draw_stats, App::draw_theme_editor, App::draw_too_small];
+        for (width, height) in [(0, 0), (1, 1), (0, 24), (80, 0), (MIN_WIDTH, MIN_HEIGHT)] {
+            (app.width, app.height) = (width, height);
+            for draw in draws {
+                draw(&app, &mut Screen::new(width, height));
+            }
+        }
+    }
+}
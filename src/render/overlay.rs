@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+
+// Rolling frame-time and CPU figures for the F12 overlay
+pub struct FrameStats {
+    pub frames: VecDeque<Duration>,
+    pub cpu_sampled_at: Instant,
+    pub cpu_time: Option<Duration>,
+    pub cpu_percent: Option<f32>,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameStats {
+    pub const WINDOW: usize = 60;
+
+    pub fn new() -> Self {
+        FrameStats {
+            frames: VecDeque::with_capacity(Self::WINDOW),
+            cpu_sampled_at: Instant::now(),
+            cpu_time: process_cpu_time(),
+            cpu_percent: None,
+        }
+    }
+
+    pub fn record(&mut self, frame: Duration) {
+        if self.frames.len() == Self::WINDOW {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+
+        // CPU usage over the last second of wall time
+        let wall = self.cpu_sampled_at.elapsed();
+        if wall >= Duration::from_secs(1) {
+            let cpu_time = process_cpu_time();
+            if let (Some(now), Some(before)) = (cpu_time, self.cpu_time) {
+                self.cpu_percent = Some(now.saturating_sub(before).as_secs_f32() / wall.as_secs_f32() * 100.0);
+            }
+            self.cpu_time = cpu_time;
+            self.cpu_sampled_at = Instant::now();
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+        let last = self.frames.back().copied().unwrap_or_default();
+        let max = self.frames.iter().max().copied().unwrap_or_default();
+        let avg = self.frames.iter().sum::<Duration>() / self.frames.len().max(1) as u32;
+        let cpu = match self.cpu_percent {
+            Some(percent) => format!("{:.1}%", percent),
+            None => "n/a".to_string(),
+        };
+        format!(" frame {:.2}ms avg {:.2} max {:.2} | cpu {} ", ms(last), ms(avg), ms(max), cpu)
+    }
+}
+
+// CPU time used by this process so far, from /proc (Linux only)
+pub fn process_cpu_time() -> Option<Duration> {
+    let stat = fs::read_to_string("/proc/self/stat").ok()?;
+    // utime and stime are fields 14 and 15; skip past the command name, which
+    // may itself contain spaces
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    // Clock ticks, 100 per second on practically every Linux system
+    Some(Duration::from_millis((utime + stime) * 10))
+}
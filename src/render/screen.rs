@@ -0,0 +1,155 @@
+use crossterm::{
+    cursor, queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+use std::io::Write;
+
+use crate::error::Result;
+use crate::locale::{is_rtl_char, visual_order};
+use crate::theme::{blend, ColorDepth};
+
+// One terminal cell of a rendered frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Cell {
+    pub const BLANK: Cell = Cell { ch: ' ', fg: Color::Reset, bg: Color::Reset };
+}
+
+// Off-screen frame. All drawing goes into a Screen; `flush` then sends only
+// the cells that changed since the previous frame to the terminal. Tests use
+// it directly as a headless backend.
+pub struct Screen {
+    pub width: u16,
+    pub height: u16,
+    pub cells: Vec<Cell>,
+    // Text with right-to-left letters is laid out right to left, for
+    // terminals that print cells as given
+    pub rtl: bool,
+}
+
+impl Screen {
+    pub fn new(width: u16, height: u16) -> Self {
+        Screen { width, height, cells: vec![Cell::BLANK; width as usize * height as usize], rtl: false }
+    }
+
+    // Write `text` from (x, y) onwards, clipped at the screen edges
+    pub fn put(&mut self, x: u16, y: u16, text: &str, fg: Color, bg: Color) {
+        if y >= self.height {
+            return;
+        }
+        if self.rtl && text.chars().any(is_rtl_char) {
+            let text = visual_order(text);
+            return self.put_cells(x, y, &text, fg, bg);
+        }
+        self.put_cells(x, y, text, fg, bg);
+    }
+
+    fn put_cells(&mut self, x: u16, y: u16, text: &str, fg: Color, bg: Color) {
+        let row = y as usize * self.width as usize;
+        for (i, ch) in text.chars().enumerate() {
+            let x = x as usize + i;
+            if x >= self.width as usize {
+                break;
+            }
+            self.cells[row + x] = Cell { ch, fg, bg };
+        }
+    }
+
+    // `put` on the terminal's own background
+    pub fn print(&mut self, x: u16, y: u16, text: &str, fg: Color) {
+        self.put(x, y, text, fg, Color::Reset);
+    }
+
+    // Fade everything drawn so far toward black, for content shown behind a
+    // panel
+    pub fn dim(&mut self) {
+        let black = Color::Rgb { r: 0, g: 0, b: 0 };
+        for cell in &mut self.cells {
+            cell.fg = match cell.fg {
+                Color::Rgb { .. } => blend(cell.fg, black, 0.6),
+                Color::Reset => Color::Reset,
+                _ => Color::DarkGrey,
+            };
+            if let Color::Rgb { .. } = cell.bg {
+                cell.bg = blend(cell.bg, black, 0.6);
+            }
+        }
+    }
+
+    // Everything drawn so far in one color, keeping the characters
+    pub fn tint(&mut self, color: Color) {
+        for cell in &mut self.cells {
+            if cell.fg != Color::Reset {
+                cell.fg = color;
+            }
+        }
+    }
+
+    // Plain text of the frame, one line per row with trailing blanks trimmed
+    #[cfg(test)]
+    pub fn text(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| row.iter().map(|c| c.ch).collect::<String>().trim_end().to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Send the frame to the terminal in colors it has. Cells unchanged from
+    // `previous` are skipped; without a previous frame of the same size
+    // everything is redrawn.
+    pub fn flush(&self, out: &mut impl Write, previous: Option<&Screen>, depth: ColorDepth) -> Result<()> {
+        let previous = previous.filter(|p| p.width == self.width && p.height == self.height);
+        if previous.is_none() {
+            queue!(out, ResetColor, Clear(ClearType::All))?;
+        }
+
+        let (mut fg, mut bg, mut at) = (None, None, None);
+        for (i, cell) in self.cells.iter().enumerate() {
+            if previous.is_some_and(|p| p.cells[i] == *cell) {
+                continue;
+            }
+            let (x, y) = ((i % self.width as usize) as u16, (i / self.width as usize) as u16);
+            if at != Some((x, y)) {
+                queue!(out, cursor::MoveTo(x, y))?;
+            }
+            if fg != Some(cell.fg) {
+                queue!(out, SetForegroundColor(depth.map(cell.fg)))?;
+                fg = Some(cell.fg);
+            }
+            if bg != Some(cell.bg) {
+                queue!(out, SetBackgroundColor(depth.map(cell.bg)))?;
+                bg = Some(cell.bg);
+            }
+            queue!(out, Print(cell.ch))?;
+            at = Some((x + 1, y));
+        }
+
+        queue!(out, ResetColor)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+// A run of statusline text in a single style
+pub struct Chunk {
+    pub text: String,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+impl Chunk {
+    pub fn new(text: String, fg: Color, bg: Color) -> Self {
+        Chunk { text, fg, bg }
+    }
+
+    pub fn width(chunks: &[Chunk]) -> u16 {
+        chunks.iter().map(|c| c.text.chars().count() as u16).sum()
+    }
+}
@@ -0,0 +1,207 @@
+use crate::app::{App, PomodoroState};
+
+// One piece of statusline content. The config lists segments by name for
+// each side of the statusline, so new content is a new Segment here rather
+// than another branch in the statusline code.
+pub trait Segment: Sync {
+    fn name(&self) -> &'static str;
+    // None hides the segment for this frame
+    fn text(&self, app: &App) -> Option<String>;
+    // "primary", "dim" or "urgent", unless the config sets a color
+    fn default_color(&self) -> &'static str {
+        "dim"
+    }
+}
+
+pub const SEGMENTS: [&dyn Segment; 11] = [&Mode, &Status, &Preset, &Time, &Cycles, &Ends, &Flow, &Health, &WallClock, &Battery, &Keys];
+
+pub fn find(name: &str) -> Option<&'static dyn Segment> {
+    SEGMENTS.iter().copied().find(|segment| segment.name() == name)
+}
+
+pub struct Mode;
+
+impl Segment for Mode {
+    fn name(&self) -> &'static str {
+        "mode"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        if app.countdown.is_some() {
+            return Some("timer".to_string());
+        }
+        let mode = match app.engine.state {
+            PomodoroState::Work => "work",
+            PomodoroState::ShortBreak => "break",
+            PomodoroState::LongBreak => "long break",
+        };
+        match (app.engine.stopwatch, app.engine.overtime) {
+            (Some(_), _) => Some(format!("{} stopwatch", mode)),
+            (_, Some(_)) => Some(format!("{} overtime", mode)),
+            _ => Some(mode.to_string()),
+        }
+    }
+
+    fn default_color(&self) -> &'static str {
+        "primary"
+    }
+}
+
+pub struct Status;
+
+impl Segment for Status {
+    fn name(&self) -> &'static str {
+        "status"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        let status = if app.engine.paused { "paused" } else { "running" };
+        Some(if app.offline { format!("{} offline", status) } else { status.to_string() })
+    }
+
+    fn default_color(&self) -> &'static str {
+        "primary"
+    }
+}
+
+// Name of the preset the durations match. Hidden when they match none.
+pub struct Preset;
+
+impl Segment for Preset {
+    fn name(&self) -> &'static str {
+        "preset"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        app.config.active_preset().map(str::to_string)
+    }
+}
+
+// Remaining time, for layouts that hide the big clock's digits
+pub struct Time;
+
+impl Segment for Time {
+    fn name(&self) -> &'static str {
+        "time"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        Some(app.clock_text())
+    }
+}
+
+pub struct Cycles;
+
+impl Segment for Cycles {
+    fn name(&self) -> &'static str {
+        "cycles"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        match app.engine.sequence.len() {
+            0 => Some(format!("cycles: {}/{}", app.engine.cycle_count, app.config.cycles_before_long)),
+            steps => Some(format!("step: {}/{}", app.engine.step + 1, steps)),
+        }
+    }
+}
+
+// When the session ends, while it's running
+pub struct Ends;
+
+impl Segment for Ends {
+    fn name(&self) -> &'static str {
+        "ends"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        if app.engine.paused || app.engine.stopwatch.is_some() {
+            return None;
+        }
+        let end = chrono::Local::now() + chrono::Duration::from_std(app.engine.time_remaining).unwrap_or_default();
+        Some(format!("ends {}", app.locale.format_time(&end, app.hour12())))
+    }
+}
+
+// Offer of a longer work length after repeatedly extended sessions
+pub struct Flow;
+
+impl Segment for Flow {
+    fn name(&self) -> &'static str {
+        "flow"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        let minutes = app.flow_suggestion()?;
+        Some(format!("in flow? f: {} min sessions today", minutes))
+    }
+
+    fn default_color(&self) -> &'static str {
+        "primary"
+    }
+}
+
+// Integrations that are currently failing. Hidden while all are healthy.
+pub struct Health;
+
+impl Segment for Health {
+    fn name(&self) -> &'static str {
+        "health"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        match app.health.failing().as_slice() {
+            [] => None,
+            [name] => Some(format!("{} failing (i)", name)),
+            names => Some(format!("{} integrations failing (i)", names.len())),
+        }
+    }
+
+    fn default_color(&self) -> &'static str {
+        "urgent"
+    }
+}
+
+pub struct WallClock;
+
+impl Segment for WallClock {
+    fn name(&self) -> &'static str {
+        "clock"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        Some(app.locale.format_time(&chrono::Local::now(), app.hour12()))
+    }
+}
+
+// Charge of the first battery, on Linux. Hidden without one.
+pub struct Battery;
+
+impl Segment for Battery {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn text(&self, _app: &App) -> Option<String> {
+        let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+        let battery = supplies
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("BAT")))?;
+        let capacity = std::fs::read_to_string(battery.join("capacity")).ok()?;
+        let charging = std::fs::read_to_string(battery.join("status")).is_ok_and(|status| status.trim() == "Charging");
+        Some(format!("bat {}%{}", capacity.trim(), if charging { "+" } else { "" }))
+    }
+}
+
+pub struct Keys;
+
+impl Segment for Keys {
+    fn name(&self) -> &'static str {
+        "keys"
+    }
+
+    fn text(&self, app: &App) -> Option<String> {
+        let keys = &app.config.keybindings;
+        Some(format!("{}:start/pause  {}:reset  {}:skip  {}:+5m  {}:config  {}:quit", keys.toggle, keys.reset, keys.skip, keys.extend, keys.config, keys.quit))
+    }
+}
@@ -0,0 +1,58 @@
+use crossterm::style::Color;
+
+use super::screen::Screen;
+use crate::app::App;
+
+impl App {
+    // Today's totals over a bar chart of completed pomodoros for the last
+    // seven days, drawn with the same cell primitives as the clock. Moving
+    // off today puts that day's count in the title instead. The title names
+    // the profile shown once profiles are in use.
+    pub fn draw_stats(&self, screen: &mut Screen) {
+        let Some(view) = &self.stats_view else {
+            return;
+        };
+        const BAR: &str = "████";
+        const COLUMN: u16 = 6;
+
+        let rows = self.height.saturating_sub(1);
+        let chart_height = rows.saturating_sub(8).min(12);
+        let block_height = 2 + chart_height + 2;
+        let top = rows.saturating_sub(block_height) / 2;
+
+        let mut title = match view.days.get(self.stats_cursor) {
+            Some((label, count)) if self.stats_cursor + 1 < view.days.len() => format!("{}: {} pomodoros", label, count),
+            _ => format!("today: {} pomodoros, {} focus minutes", view.completed_today, view.focus_minutes_today),
+        };
+        match &view.profile {
+            Some(profile) => title = format!("{}, {}", profile, title),
+            None if !self.config.profile.is_empty() => title = format!("all profiles, {}", title),
+            None => {}
+        }
+        let title_x = (self.width / 2).saturating_sub(title.chars().count() as u16 / 2);
+        screen.print(title_x, top, &title, self.theme.primary);
+
+        let chart_width = view.days.len() as u16 * COLUMN - 2;
+        let left = (self.width / 2).saturating_sub(chart_width / 2);
+        let base_y = top + 2 + chart_height;
+        let max = view.days.iter().map(|&(_, count)| count).max().unwrap_or(0).max(1);
+        for (i, (label, count)) in view.days.iter().enumerate() {
+            let x = left + i as u16 * COLUMN;
+            let color = if i == self.stats_cursor { self.theme.primary } else { self.theme.dim };
+            // Any completed pomodoro gets at least one row
+            let bar = match *count {
+                0 => 0,
+                count => (count as u64 * chart_height as u64).div_ceil(max as u64) as u16,
+            };
+            for row in 0..bar {
+                screen.put(x, base_y - 1 - row, BAR, color, Color::Reset);
+            }
+            if *count > 0 {
+                screen.print(x, base_y.saturating_sub(bar + 1), &format!("{:^4}", count), color);
+            }
+            screen.print(x, base_y, &format!("{:^4}", label), color);
+        }
+
+        self.draw_help(screen, &format!(" stats | h/l:day  p:profile  {}/q/esc:close ", self.config.keybindings.stats));
+    }
+}
@@ -0,0 +1,42 @@
+use super::screen::Screen;
+use crate::app::App;
+use crate::theme::to_hex;
+
+const CHANNELS: [&str; 6] = ["primary red", "primary green", "primary blue", "dim red", "dim green", "dim blue"];
+
+impl App {
+    // The theme editor over the dimmed clock. The panel is drawn in the
+    // colors being edited, which is the preview.
+    pub fn draw_theme_editor(&self, screen: &mut Screen) {
+        let Some(editor) = &self.theme_editor else {
+            return;
+        };
+        self.draw(screen);
+        screen.dim();
+
+        let mut lines: Vec<String> = CHANNELS
+            .iter()
+            .zip(editor.channels)
+            .enumerate()
+            .map(|(i, (name, value))| {
+                let cursor = if i == editor.row && editor.name.is_none() { "> " } else { "  " };
+                format!("{}{:<14}{:>3} {:<8}", cursor, name, value, "█".repeat(value as usize / 32))
+            })
+            .collect();
+        lines.push(format!("  primary {}  dim {}", to_hex(editor.primary()), to_hex(editor.dim())));
+        let highlight = match &editor.name {
+            Some(name) => {
+                lines.push(format!("> save as: {}_", name));
+                lines.len() - 1
+            }
+            None => editor.row,
+        };
+        self.draw_panel(screen, &lines, Some(highlight));
+
+        let help = match editor.name {
+            Some(_) => " theme name | enter:save  esc:back ",
+            None => " theme editor | j/k:channel  h/l:-/+1  H/L:-/+16  enter:save  esc:cancel ",
+        };
+        self.draw_help(screen, help);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{Datelike, Days, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::app::App;
+use crate::config::Config;
+use crate::crypto::Passphrase;
+use crate::error::{Error, Result};
+use crate::history::{self, in_profile, HistoryEntry};
+use crate::integrations::summary;
+use crate::storage::{is_read_only, write_atomic, Dirs};
+
+// Tasks listed in a report
+const TOP_TASKS: usize = 3;
+
+// The history summed up over a span of days, for `rpomodoro report` and the
+// weekly summary push, for one profile or all of them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    // First and last day, YYYY-MM-DD
+    pub from: String,
+    pub to: String,
+    // None for all profiles together
+    pub profile: Option<String>,
+    pub completed: u32,
+    pub skipped: u32,
+    pub focus_minutes: u64,
+    // Days in a row, up to `to`, with a completed pomodoro
+    pub streak: u32,
+    // Tasks with the most completed pomodoros, most first
+    pub top_tasks: Vec<(String, u32)>,
+    // Completed pomodoros per profile, for all of them together; empty
+    // when none were done under a profile
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<(String, u32)>,
+}
+
+impl Report {
+    pub fn build(entries: &[HistoryEntry], from: NaiveDate, to: NaiveDate, profile: Option<&str>) -> Self {
+        let key = |day: NaiveDate| day.format("%Y-%m-%d").to_string();
+        let (first, last) = (key(from), key(to));
+        let entries: Vec<&HistoryEntry> = entries.iter().filter(|e| in_profile(e, profile)).collect();
+        let in_span = |e: &&&HistoryEntry| e.state == "work" && e.end.get(..10).is_some_and(|day| (first.as_str()..=last.as_str()).contains(&day));
+        let work: Vec<&HistoryEntry> = entries.iter().filter(in_span).copied().collect();
+
+        let mut profiles: BTreeMap<&str, u32> = BTreeMap::new();
+        if profile.is_none() && work.iter().any(|e| e.profile.is_some()) {
+            for entry in work.iter().filter(|e| !e.skipped) {
+                *profiles.entry(entry.profile.as_deref().unwrap_or("no profile")).or_default() += 1;
+            }
+        }
+
+        let mut tasks: BTreeMap<&str, u32> = BTreeMap::new();
+        for entry in work.iter().filter(|e| !e.skipped) {
+            if let Some(task) = &entry.task {
+                *tasks.entry(task).or_default() += 1;
+            }
+        }
+        let mut top_tasks: Vec<(String, u32)> = tasks.into_iter().map(|(task, count)| (task.to_string(), count)).collect();
+        top_tasks.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        top_tasks.truncate(TOP_TASKS);
+
+        let completed_on = |day: NaiveDate| entries.iter().any(|e| e.state == "work" && !e.skipped && e.end.starts_with(&key(day)));
+        let streak = (0..).map_while(|ago| to.checked_sub_days(Days::new(ago))).take_while(|&day| completed_on(day)).count() as u32;
+
+        Report {
+            from: first.clone(),
+            to: last.clone(),
+            profile: profile.map(str::to_string),
+            completed: work.iter().filter(|e| !e.skipped).count() as u32,
+            skipped: work.iter().filter(|e| e.skipped).count() as u32,
+            focus_minutes: work.iter().map(|e| e.seconds).sum::<u64>() / 60,
+            streak,
+            top_tasks,
+            profiles: profiles.into_iter().map(|(profile, count)| (profile.to_string(), count)).collect(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "{} to {}", self.from, self.to);
+        match &self.profile {
+            Some(profile) => {
+                let _ = writeln!(text, "profile: {}", profile);
+            }
+            None if !self.profiles.is_empty() => {
+                let _ = writeln!(text, "profile: all");
+            }
+            None => {}
+        }
+        let _ = writeln!(text, "pomodoros: {} ({} skipped)", self.completed, self.skipped);
+        for (profile, count) in &self.profiles {
+            let _ = writeln!(text, "  {}: {}", profile, count);
+        }
+        let _ = writeln!(text, "focus: {}h {:02}m", self.focus_minutes / 60, self.focus_minutes % 60);
+        let _ = writeln!(text, "streak: {} day{}", self.streak, if self.streak == 1 { "" } else { "s" });
+        for (task, count) in &self.top_tasks {
+            let _ = writeln!(text, "  {} ({})", task, count);
+        }
+        text
+    }
+}
+
+// `rpomodoro report`: the last 7 days, today included, for `profile` or
+// all profiles together
+pub fn run_report(dirs: &Dirs, as_json: bool, profile: Option<&str>) -> Result<()> {
+    let mut dirs = dirs.clone();
+    let config = Config::read(&dirs.config_file());
+    dirs.apply_config(&config.data_dir);
+    if let Some(profile) = profile.filter(|profile| !config.profiles.contains_key(*profile)) {
+        return Err(Error::Usage(format!("unknown profile {:?}, pick one of {}", profile, config.profile_names().join(", "))));
+    }
+    let history_path = dirs.data.join("history.jsonl");
+    let passphrase = if config.encrypt_data || history::has_encrypted_lines(&history_path) { Some(Passphrase::obtain(&history_path)?) } else { None };
+    let entries = history::load(&history_path, passphrase.as_ref())?;
+
+    let today = chrono::Local::now().date_naive();
+    let report = Report::build(&entries, today - Days::new(6), today, profile);
+    if as_json {
+        println!("{}", serde_json::to_string(&report).map_err(|e| Error::integration("report", e))?);
+    } else {
+        print!("{}", report.text());
+    }
+    Ok(())
+}
+
+impl App {
+    // Push last week's report (Monday to Sunday) to the "weekly_summary"
+    // targets, once per week. The week last sent is kept in the state dir,
+    // so with --read-only, where it can't be, nothing is sent.
+    pub fn push_weekly_summary(&mut self) -> Result<()> {
+        let targets = &self.config.weekly_summary;
+        if self.offline || is_read_only() || (targets.ntfy_url.is_empty() && targets.smtp_url.is_empty()) {
+            return Ok(());
+        }
+        let today = chrono::Local::now().date_naive();
+        let monday = today - Days::new(today.weekday().num_days_from_monday() as u64 + 7);
+        let marker = self.session_path.with_file_name("weekly-summary");
+        let week = monday.format("%Y-%m-%d").to_string();
+        if std::fs::read_to_string(&marker).is_ok_and(|sent| sent.trim() == week) {
+            return Ok(());
+        }
+        // Marked first, so a failing target isn't retried on every transition
+        write_atomic(&marker, &week)?;
+
+        let entries = history::load(&self.history_path, self.passphrase.as_ref())?;
+        let report = Report::build(&entries, monday, monday + Days::new(6), None);
+        let title = format!("rpomodoro: week of {}", report.from);
+        let mut result = Ok(());
+        if !targets.ntfy_url.is_empty() {
+            result = result.and(self.health.track("weekly summary", summary::ntfy(&targets.ntfy_url, &title, &report.text())));
+        }
+        if !targets.smtp_url.is_empty() {
+            result = result.and(self.health.track("weekly summary", summary::email(targets, &title, &report.text())));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::PomodoroState;
+
+    #[test]
+    fn reports_count_the_span_and_the_streak() {
+        let entry = |end: &str, seconds, skipped, task: Option<&str>| {
+            let mut entry = HistoryEntry::new(PomodoroState::Work, None, seconds, skipped);
+            entry.end = end.to_string();
+            entry.task = task.map(str::to_string);
+            entry
+        };
+        let entries = [
+            entry("2026-01-03T10:00:00", 1500, false, Some("report")),
+            entry("2026-01-04T10:00:00", 1500, false, Some("email")),
+            entry("2026-01-05T10:00:00", 1500, false, Some("report")),
+            entry("2026-01-05T11:00:00", 600, true, Some("report")),
+            entry("2026-01-06T10:00:00", 1500, false, None),
+        ];
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let report = Report::build(&entries, day(4), day(5), None);
+        assert_eq!((report.completed, report.skipped, report.focus_minutes, report.streak), (2, 1, 60, 3));
+        assert_eq!(report.top_tasks, [("email".to_string(), 1), ("report".to_string(), 1)]);
+        assert!(report.profiles.is_empty());
+        assert!(report.text().contains("focus: 1h 00m"));
+    }
+
+    #[test]
+    fn reports_keep_profiles_apart() {
+        let entry = |end: &str, profile: Option<&str>| {
+            let mut entry = HistoryEntry::new(PomodoroState::Work, None, 1500, false);
+            entry.end = end.to_string();
+            entry.profile = profile.map(str::to_string);
+            entry
+        };
+        let entries = [
+            entry("2026-01-04T10:00:00", Some("study")),
+            entry("2026-01-05T09:00:00", Some("deep-work")),
+            entry("2026-01-05T10:00:00", Some("study")),
+            entry("2026-01-05T11:00:00", None),
+        ];
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let all = Report::build(&entries, day(4), day(5), None);
+        assert_eq!(all.completed, 4);
+        assert_eq!(all.profiles, [("deep-work".to_string(), 1), ("no profile".to_string(), 1), ("study".to_string(), 2)]);
+        assert!(all.text().contains("profile: all\npomodoros: 4 (0 skipped)\n  deep-work: 1\n"));
+
+        let study = Report::build(&entries, day(4), day(5), Some("study"));
+        assert_eq!((study.completed, study.streak), (2, 2));
+        assert!(study.profiles.is_empty());
+        assert!(study.text().contains("profile: study\n"));
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use crate::app::{App, PomodoroState};
+use crate::config::Config;
+use crate::error::Result;
+use crate::events::Command;
+use crate::integrations::notify::Silent;
+use crate::live::Snapshot;
+use crate::stats::Stats;
+use crate::storage::create_dir;
+
+// What a Scheduler reports to the program embedding it
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulerEvent {
+    // A session or break ended, by running out or by a skip, and `next`
+    // began (paused)
+    Transition { finished: PomodoroState, next: PomodoroState, skipped: bool },
+    Paused,
+    Resumed,
+    // Back to the first work session
+    Reset,
+    // Minutes added to the current session
+    Extended(u32),
+}
+
+pub type Callback = Box<dyn FnMut(&SchedulerEvent) + Send>;
+
+// The pomodoro engine for other programs to embed: the timer's sequencing
+// and settings without the terminal, notifications or integrations. Call
+// update() from your own loop (next_update() says when it's worth it),
+// send commands, and render snapshot(); changes arrive as SchedulerEvents
+// through callbacks and channels. Completed pomodoros go to stats.json and
+// history.jsonl in the data dir it's given, like the TUI's.
+pub struct Scheduler {
+    app: App,
+    callbacks: Vec<Callback>,
+    subscribers: Vec<Sender<SchedulerEvent>>,
+}
+
+impl Scheduler {
+    pub fn new(mut config: Config, data_dir: &Path) -> Result<Self> {
+        // Alerts are the embedding program's to give
+        config.sound.enabled = false;
+        config.escalation.clear();
+        create_dir(data_dir)?;
+        let stats_path = data_dir.join("stats.json");
+        let stats = Stats::load(&stats_path, None)?;
+        let mut app = App::with_config(config, data_dir.join("config.json"), stats, stats_path, 80, 24);
+        app.notifier = Box::new(Silent);
+        app.offline = true;
+        Ok(Scheduler { app, callbacks: Vec::new(), subscribers: Vec::new() })
+    }
+
+    // Called with every event, on the thread that caused it
+    pub fn on_event(&mut self, callback: impl FnMut(&SchedulerEvent) + Send + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    // Every event from now on, for another thread. Dropping the receiver
+    // unsubscribes.
+    pub fn subscribe(&mut self) -> Receiver<SchedulerEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.push(tx);
+        rx
+    }
+
+    // Run the timer up to now by the wall clock
+    pub fn update(&mut self) -> Result<()> {
+        self.observe(false, |app| app.update())
+    }
+
+    // Run the timer forward by `elapsed` instead, for programs with their
+    // own clock
+    pub fn tick(&mut self, elapsed: Duration) -> Result<()> {
+        self.observe(false, |app| app.tick(elapsed))
+    }
+
+    // Toggle, pause, resume, skip, extend or reset. Quit does nothing.
+    pub fn command(&mut self, command: Command) -> Result<()> {
+        if command == Command::Reset {
+            self.app.reset();
+            self.emit(SchedulerEvent::Reset);
+            return Ok(());
+        }
+        self.observe(command == Command::Skip, |app| {
+            app.execute(command);
+            Ok(())
+        })
+    }
+
+    // How long until the displayed time would change
+    pub fn next_update(&self) -> Duration {
+        self.app.redraw_timeout()
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.app.snapshot()
+    }
+
+    pub fn state(&self) -> PomodoroState {
+        self.app.engine.state
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.app.engine.time_remaining
+    }
+
+    pub fn paused(&self) -> bool {
+        self.app.engine.paused
+    }
+
+    // Run `step` on the timer and report what it changed. A transition is a
+    // skip when `skip` asked for one or a break skip under the "cooldown"
+    // rule went through.
+    fn observe(&mut self, skip: bool, step: impl FnOnce(&mut App) -> Result<()>) -> Result<()> {
+        let (state, paused, extended) = (self.app.engine.state, self.app.engine.paused, self.app.engine.extended_minutes);
+        let skips = self.app.stats.skipped_breaks.len();
+        let result = step(&mut self.app);
+
+        let app = &self.app;
+        let event = if app.engine.state != state {
+            let skipped = skip || app.stats.skipped_breaks.len() > skips;
+            Some(SchedulerEvent::Transition { finished: state, next: app.engine.state, skipped })
+        } else if app.engine.extended_minutes > extended {
+            Some(SchedulerEvent::Extended(app.engine.extended_minutes - extended))
+        } else if app.engine.paused != paused {
+            Some(if app.engine.paused { SchedulerEvent::Paused } else { SchedulerEvent::Resumed })
+        } else {
+            None
+        };
+        if let Some(event) = event {
+            self.emit(event);
+        }
+        result
+    }
+
+    fn emit(&mut self, event: SchedulerEvent) {
+        for callback in &mut self.callbacks {
+            callback(&event);
+        }
+        self.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn events_follow_the_session() {
+        let dir = std::env::temp_dir().join(format!("rpomodoro-test-scheduler-{}", std::process::id()));
+        let config = Config { work_duration: 1, short_break: 1, cycles_before_long: 2, ..Config::default() };
+        let mut scheduler = Scheduler::new(config, &dir).unwrap();
+        let events = scheduler.subscribe();
+        let transitions = Arc::new(Mutex::new(0));
+        let counted = transitions.clone();
+        scheduler.on_event(move |event| {
+            if matches!(event, SchedulerEvent::Transition { .. }) {
+                *counted.lock().unwrap() += 1;
+            }
+        });
+
+        scheduler.tick(Duration::from_secs(60)).unwrap();
+        assert!(events.try_recv().is_err(), "nothing happens while paused");
+        scheduler.command(Command::TogglePause).unwrap();
+        scheduler.tick(Duration::from_secs(30)).unwrap();
+        assert_eq!(scheduler.remaining(), Duration::from_secs(30));
+        scheduler.tick(Duration::from_secs(31)).unwrap();
+        scheduler.command(Command::Skip).unwrap();
+        scheduler.command(Command::Reset).unwrap();
+
+        let seen: Vec<SchedulerEvent> = events.try_iter().collect();
+        assert_eq!(
+            seen,
+            [
+                SchedulerEvent::Resumed,
+                SchedulerEvent::Transition { finished: PomodoroState::Work, next: PomodoroState::ShortBreak, skipped: false },
+                SchedulerEvent::Transition { finished: PomodoroState::ShortBreak, next: PomodoroState::Work, skipped: true },
+                SchedulerEvent::Reset,
+            ]
+        );
+        assert_eq!(*transitions.lock().unwrap(), 2);
+        assert_eq!(scheduler.snapshot().completed_today, 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
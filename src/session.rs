@@ -0,0 +1,262 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::app::{App, PomodoroState};
+use crate::error::{Error, Result};
+use crate::history::{self, HistoryEntry};
+use crate::storage::write_atomic;
+
+// Where the timer was, kept in the state dir so a restarted rpomodoro picks
+// up the same session instead of starting over. How often it's written is
+// up to the "autosave" setting.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub state: PomodoroState,
+    pub remaining_secs: u64,
+    pub cycle: u32,
+    pub extended_minutes: u32,
+    // When the session first started running and when this was written,
+    // local times like the history's. Files from before these were kept
+    // resume as a session not yet started.
+    #[serde(default)]
+    pub started: Option<String>,
+    #[serde(default)]
+    pub saved_at: Option<String>,
+    // Whether it was paused when written; false for a timer that was
+    // quit or crashed while running
+    #[serde(default = "paused_by_default")]
+    pub paused: bool,
+    // Time counted up, in stopwatch mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopwatch_secs: Option<u64>,
+    // Time past the end, for a session running over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtime_secs: Option<u64>,
+    // The step of a custom "sequence"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<usize>,
+}
+
+// Most overtime or stopwatch time a restored session keeps; more than a
+// day only comes from a damaged file
+const MAX_COUNTED: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn paused_by_default() -> bool {
+    true
+}
+
+impl Session {
+    // A missing or unreadable file just means a fresh start
+    pub fn load(path: &Path) -> Option<Self> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+}
+
+impl App {
+    // Resumes paused, since the time between runs isn't counted, with a
+    // toast offering to pick it up again. Saved within
+    // "resume_within_minutes", it carries on as the same session, recorded
+    // in the history once with its first start and marked resumed. Saved
+    // longer ago, the part already done is recorded as a skipped session of
+    // its own and the session starts over.
+    pub fn restore_session(&mut self, session: Session) -> Result<()> {
+        self.engine.state = session.state;
+        let step = session.step.filter(|&step| self.engine.sequence.get(step).is_some_and(|step| step.state == session.state));
+        match step.or_else(|| self.engine.sequence.iter().position(|step| step.state == session.state)) {
+            Some(step) => self.engine.step = step,
+            None if self.engine.sequence.is_empty() => {}
+            None => {
+                self.engine.reset();
+                return Ok(());
+            }
+        }
+        self.engine.cycle_count = session.cycle.min(self.engine.cycles_before_long().saturating_sub(1));
+        self.engine.extended_minutes = session.extended_minutes;
+        self.engine.time_remaining = Duration::from_secs(session.remaining_secs).min(self.session_length());
+        self.engine.stopwatch = session.stopwatch_secs.map(|secs| Duration::from_secs(secs).min(MAX_COUNTED));
+        self.engine.overtime = session.overtime_secs.filter(|_| self.engine.time_remaining.is_zero()).map(|secs| Duration::from_secs(secs).min(MAX_COUNTED));
+
+        let Some(started) = session.started else {
+            return Ok(());
+        };
+        let window = chrono::Duration::minutes(self.config.resume_within_minutes as i64);
+        let saved_at = session.saved_at.as_deref().and_then(|at| NaiveDateTime::parse_from_str(at, "%Y-%m-%dT%H:%M:%S").ok());
+        if saved_at.is_some_and(|at| chrono::Local::now().naive_local() - at <= window) {
+            self.session_started = Some(started);
+            self.session_resumed = true;
+            let how = if session.paused { "paused" } else { "interrupted" };
+            let at = match self.engine.stopwatch.or(self.engine.overtime) {
+                Some(_) => format!("at {}", self.clock_text()),
+                None => format!("with {} left", self.clock_text()),
+            };
+            let offer = format!("{} {} {}: {} resumes", self.engine.state.name(), how, at, self.config.keybindings.toggle);
+            self.toast = Some((offer, Instant::now()));
+            return Ok(());
+        }
+        let counted = self.engine.counted();
+        let mut entry = HistoryEntry::new(self.engine.state, Some(started), counted.as_secs(), true);
+        entry.end = session.saved_at.unwrap_or(entry.end);
+        entry.profile = Some(self.config.profile.clone()).filter(|profile| !profile.is_empty());
+        entry.overtime = self.engine.overtime.take().map(|over| over.as_secs()).filter(|&secs| secs > 0);
+        self.engine.extended_minutes = 0;
+        self.engine.time_remaining = self.session_length();
+        self.engine.stopwatch = self.engine.stopwatch.map(|_| Duration::ZERO);
+        self.session_dirty = true;
+        history::append(&self.history_path, &entry, self.encrypting())
+    }
+
+    // Write the session if it changed and the autosave policy says it's
+    // time: every "autosave_seconds", on transitions, or only on exit
+    pub fn autosave(&mut self, exiting: bool) -> Result<()> {
+        if !self.session_dirty {
+            return Ok(());
+        }
+        let due = exiting
+            || match self.config.autosave.as_str() {
+                "interval" => self.transitioned || self.last_autosave.elapsed() >= Duration::from_secs(self.config.autosave_seconds as u64),
+                "transitions" => self.transitioned,
+                _ => false,
+            };
+        if !due {
+            return Ok(());
+        }
+        // The pomodoro timer, not a countdown running in its place
+        let (engine, started) = match &self.countdown {
+            Some(countdown) => (&countdown.pomodoro, &countdown.session_started),
+            None => (&self.engine, &self.session_started),
+        };
+        let session = Session {
+            state: engine.state,
+            remaining_secs: engine.time_remaining.as_secs(),
+            cycle: engine.cycle_count,
+            extended_minutes: engine.extended_minutes,
+            started: started.clone(),
+            saved_at: Some(history::now()),
+            paused: engine.paused,
+            stopwatch_secs: engine.stopwatch.map(|counted| counted.as_secs()),
+            overtime_secs: engine.overtime.map(|over| over.as_secs()),
+            step: Some(engine.step).filter(|_| !engine.sequence.is_empty()),
+        };
+        let json = serde_json::to_string_pretty(&session).map_err(|e| Error::config(&self.session_path, e))?;
+        write_atomic(&self.session_path, json)?;
+        self.session_dirty = false;
+        self.transitioned = false;
+        self.last_autosave = Instant::now();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn exit_only_autosave_waits_for_exit() {
+        let mut app = App::for_test(Config { autosave: "exit".to_string(), ..Config::default() });
+        app.advance_state(true);
+        app.autosave(false).unwrap();
+        assert!(!app.session_path.exists());
+        app.autosave(true).unwrap();
+        assert!(app.session_path.exists());
+        assert!(!app.session_dirty);
+    }
+
+    #[test]
+    fn restarts_within_the_window_continue_the_session() {
+        let ago = |minutes| (chrono::Local::now() - chrono::Duration::minutes(minutes)).format("%Y-%m-%dT%H:%M:%S").to_string();
+        let started = ago(20);
+        let session = |saved_minutes_ago| Session {
+            state: PomodoroState::Work,
+            remaining_secs: 10 * 60,
+            cycle: 0,
+            extended_minutes: 0,
+            started: Some(started.clone()),
+            saved_at: Some(ago(saved_minutes_ago)),
+            paused: false,
+            stopwatch_secs: None,
+            overtime_secs: None,
+            step: None,
+        };
+
+        let mut app = App::for_test(Config::default());
+        app.restore_session(session(5)).unwrap();
+        assert_eq!((app.engine.time_remaining, app.session_started.clone()), (Duration::from_secs(600), Some(started.clone())));
+        assert_eq!(app.active_toast(), Some("work interrupted with 10:00 left: space resumes"));
+        app.toggle_pause();
+        app.tick(Duration::from_secs(601)).unwrap();
+        app.restore_session(session(90)).unwrap();
+        assert_eq!((app.engine.time_remaining, app.session_started.clone()), (Duration::from_secs(25 * 60), None));
+
+        let entries = history::load(&app.history_path, None).unwrap();
+        let summary: Vec<(bool, bool, u64)> = entries.iter().map(|e| (e.resumed, e.skipped, e.seconds)).collect();
+        assert_eq!(summary, [(true, false, 25 * 60), (false, true, 15 * 60)]);
+        assert_eq!(entries[0].start, Some(started));
+    }
+
+    #[test]
+    fn stale_sessions_in_overtime_start_over_without_it() {
+        let mut app = App::for_test(Config { overtime: "work".to_string(), ..Config::default() });
+        let saved_at = (chrono::Local::now() - chrono::Duration::minutes(90)).format("%Y-%m-%dT%H:%M:%S").to_string();
+        let session = Session {
+            state: PomodoroState::Work,
+            remaining_secs: 0,
+            cycle: 0,
+            extended_minutes: 0,
+            started: Some(saved_at.clone()),
+            saved_at: Some(saved_at),
+            paused: true,
+            stopwatch_secs: None,
+            overtime_secs: Some(120),
+            step: None,
+        };
+        app.restore_session(session).unwrap();
+        assert_eq!((app.engine.time_remaining, app.engine.overtime), (Duration::from_secs(25 * 60), None));
+        assert_eq!(app.clock_text(), "25:00");
+
+        let entries = history::load(&app.history_path, None).unwrap();
+        let logged: Vec<(u64, Option<u64>, bool)> = entries.iter().map(|e| (e.seconds, e.overtime, e.skipped)).collect();
+        assert_eq!(logged, [(25 * 60 + 120, Some(120), true)]);
+    }
+
+    #[test]
+    fn damaged_overtime_and_stopwatch_are_capped() {
+        let mut app = App::for_test(Config { overtime: "work".to_string(), ..Config::default() });
+        let session = Session {
+            state: PomodoroState::Work,
+            remaining_secs: 0,
+            cycle: 0,
+            extended_minutes: 0,
+            started: None,
+            saved_at: None,
+            paused: true,
+            stopwatch_secs: None,
+            overtime_secs: Some(u64::MAX),
+            step: None,
+        };
+        app.restore_session(session).unwrap();
+        assert_eq!(app.engine.overtime, Some(MAX_COUNTED));
+        assert_eq!(app.engine.counted(), Duration::from_secs(25 * 60) + MAX_COUNTED);
+
+        let session = Session {
+            state: PomodoroState::Work,
+            remaining_secs: 25 * 60,
+            cycle: 0,
+            extended_minutes: 0,
+            started: None,
+            saved_at: None,
+            paused: true,
+            stopwatch_secs: Some(u64::MAX),
+            overtime_secs: None,
+            step: None,
+        };
+        app.restore_session(session).unwrap();
+        assert_eq!(app.engine.stopwatch, Some(MAX_COUNTED));
+        app.toggle_pause();
+        app.tick(Duration::from_secs(5)).unwrap();
+        assert_eq!(app.engine.stopwatch, Some(MAX_COUNTED + Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::app::App;
+use crate::config::{cycle_option, AUTO_STARTS, BREAK_ACTIVITIES, BREAK_SKIPS, CLOCK_FACES, CLOCK_FORMATS, CLOCK_STYLES, DIGIT_STYLES, OVERTIMES, TEXT_DIRECTIONS};
+use crate::engine::PomodoroState;
+
+// The settings on the config screen, a page per group. New ones go in
+// SETTINGS under their group; rows, values and keys all follow from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    Profile,
+    Preset,
+    WorkDuration,
+    ShortBreak,
+    LongBreak,
+    CyclesBeforeLong,
+    AutoStart,
+    Overtime,
+    BreakSkip,
+    BreakActivity,
+    PauseInConfig,
+    Theme,
+    UrgencyMinutes,
+    WarningMinutes,
+    DigitStyle,
+    ClockStyle,
+    ClockFace,
+    ClockFormat,
+    TextDirection,
+    ShowWallClock,
+    ShowProgress,
+    DailyGoal,
+    ShowTomatoes,
+}
+
+pub const SETTINGS: [(&str, &[Setting]); 3] = [
+    (
+        "timer",
+        &[
+            Setting::Profile,
+            Setting::Preset,
+            Setting::WorkDuration,
+            Setting::ShortBreak,
+            Setting::LongBreak,
+            Setting::CyclesBeforeLong,
+            Setting::AutoStart,
+            Setting::Overtime,
+            Setting::BreakSkip,
+            Setting::BreakActivity,
+            Setting::PauseInConfig,
+        ],
+    ),
+    ("clock", &[Setting::Theme, Setting::UrgencyMinutes, Setting::WarningMinutes, Setting::DigitStyle, Setting::ClockStyle, Setting::ClockFace, Setting::ClockFormat, Setting::TextDirection, Setting::ShowWallClock]),
+    ("progress", &[Setting::ShowProgress, Setting::DailyGoal, Setting::ShowTomatoes]),
+];
+
+// Where the config screen is: the page, the highlighted row on it, and a
+// search across every page while one is typed or kept
+#[derive(Debug, Default)]
+pub struct ConfigView {
+    pub page: usize,
+    pub cursor: usize,
+    pub search: Option<String>,
+    // Keys go to the search until enter or esc
+    pub typing: bool,
+}
+
+impl ConfigView {
+    // The rows on screen: the page's, or every match for the search
+    pub fn rows(&self) -> Vec<Setting> {
+        match self.search.as_deref().map(str::to_lowercase) {
+            Some(query) => SETTINGS.iter().flat_map(|(_, settings)| settings.iter()).copied().filter(|setting| setting.label().contains(&query)).collect(),
+            None => SETTINGS[self.page].1.to_vec(),
+        }
+    }
+
+    pub fn selected(&self) -> Option<Setting> {
+        self.rows().get(self.cursor).copied()
+    }
+
+    pub fn turn_page(&mut self, forward: bool) {
+        let count = SETTINGS.len();
+        self.page = if forward { (self.page + 1) % count } else { (self.page + count - 1) % count };
+        self.cursor = 0;
+    }
+}
+
+impl Setting {
+    // As in the config file
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::Profile => "profile",
+            Setting::Preset => "preset",
+            Setting::WorkDuration => "work_duration",
+            Setting::ShortBreak => "short_break",
+            Setting::LongBreak => "long_break",
+            Setting::CyclesBeforeLong => "cycles_before_long",
+            Setting::AutoStart => "auto_start",
+            Setting::Overtime => "overtime",
+            Setting::BreakSkip => "break_skip",
+            Setting::BreakActivity => "break_activity",
+            Setting::PauseInConfig => "pause_in_config",
+            Setting::Theme => "theme",
+            Setting::UrgencyMinutes => "urgency_minutes",
+            Setting::WarningMinutes => "warning_minutes",
+            Setting::DigitStyle => "digit_style",
+            Setting::ClockStyle => "clock_style",
+            Setting::ClockFace => "clock_face",
+            Setting::ClockFormat => "clock_format",
+            Setting::TextDirection => "text_direction",
+            Setting::ShowWallClock => "show_wall_clock",
+            Setting::ShowProgress => "show_progress",
+            Setting::DailyGoal => "daily_goal",
+            Setting::ShowTomatoes => "show_tomatoes",
+        }
+    }
+
+    pub fn value(self, app: &App) -> String {
+        let config = &app.config;
+        let duration = |state| app.locale.format_duration(config.duration_for(state));
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        match self {
+            Setting::Profile if config.profile.is_empty() => "none".to_string(),
+            Setting::Profile => config.profile.clone(),
+            Setting::Preset => config.active_preset().unwrap_or("custom").to_string(),
+            Setting::WorkDuration => duration(PomodoroState::Work),
+            Setting::ShortBreak => duration(PomodoroState::ShortBreak),
+            Setting::LongBreak => duration(PomodoroState::LongBreak),
+            Setting::CyclesBeforeLong => config.cycles_before_long.to_string(),
+            Setting::AutoStart => config.auto_start.clone(),
+            Setting::Overtime => config.overtime.clone(),
+            Setting::BreakSkip => config.break_skip.clone(),
+            Setting::BreakActivity => config.break_activity.clone(),
+            Setting::PauseInConfig => on_off(config.pause_in_config),
+            Setting::Theme => config.theme.clone(),
+            Setting::UrgencyMinutes => config.urgency_minutes.to_string(),
+            Setting::WarningMinutes => config.warning_minutes.to_string(),
+            Setting::DigitStyle => config.digit_style.clone(),
+            Setting::ClockStyle => config.clock_style.clone(),
+            Setting::ClockFace => config.clock_face.clone(),
+            Setting::ClockFormat => config.clock_format.clone(),
+            Setting::TextDirection => config.text_direction.clone(),
+            Setting::ShowWallClock => on_off(config.show_wall_clock),
+            Setting::ShowProgress => on_off(config.show_progress),
+            Setting::DailyGoal => config.daily_goal.to_string(),
+            Setting::ShowTomatoes => on_off(config.show_tomatoes),
+        }
+    }
+
+    // One step with h/l
+    pub fn change(self, app: &mut App, forward: bool) {
+        let step = |value: u32, min: u32, max: u32| if forward { (value + 1).min(max) } else { value.saturating_sub(1).max(min) };
+        let config = &mut app.config;
+        match self {
+            Setting::Profile => app.cycle_profile(forward),
+            Setting::Preset => {
+                let name = cycle_option(&config.preset_names(), config.active_preset().unwrap_or_default(), forward);
+                config.apply_preset(&name);
+            }
+            Setting::WorkDuration => config.work_duration = step(config.work_duration, 1, 120),
+            Setting::ShortBreak => config.short_break = step(config.short_break, 1, 60),
+            Setting::LongBreak => config.long_break = step(config.long_break, 1, 120),
+            Setting::CyclesBeforeLong => config.cycles_before_long = step(config.cycles_before_long, 1, 10),
+            Setting::AutoStart => config.auto_start = cycle_option(&AUTO_STARTS, &config.auto_start, forward),
+            Setting::Overtime => config.overtime = cycle_option(&OVERTIMES, &config.overtime, forward),
+            Setting::BreakSkip => config.break_skip = cycle_option(&BREAK_SKIPS, &config.break_skip, forward),
+            Setting::BreakActivity => config.break_activity = cycle_option(&BREAK_ACTIVITIES, &config.break_activity, forward),
+            Setting::PauseInConfig => config.pause_in_config = !config.pause_in_config,
+            Setting::Theme => {
+                config.theme = cycle_option(&config.theme_names(), &config.theme, forward);
+                app.apply_theme();
+            }
+            Setting::UrgencyMinutes => config.urgency_minutes = step(config.urgency_minutes, 0, 30),
+            Setting::WarningMinutes => config.warning_minutes = step(config.warning_minutes, 0, 30),
+            Setting::DigitStyle => config.digit_style = cycle_option(&DIGIT_STYLES, &config.digit_style, forward),
+            Setting::ClockStyle => config.clock_style = cycle_option(&CLOCK_STYLES, &config.clock_style, forward),
+            Setting::ClockFace => config.clock_face = cycle_option(&CLOCK_FACES, &config.clock_face, forward),
+            Setting::ClockFormat => config.clock_format = cycle_option(&CLOCK_FORMATS, &config.clock_format, forward),
+            Setting::TextDirection => config.text_direction = cycle_option(&TEXT_DIRECTIONS, &config.text_direction, forward),
+            Setting::ShowWallClock => config.show_wall_clock = !config.show_wall_clock,
+            Setting::ShowProgress => config.show_progress = !config.show_progress,
+            Setting::DailyGoal => config.daily_goal = step(config.daily_goal, 0, 30),
+            Setting::ShowTomatoes => config.show_tomatoes = !config.show_tomatoes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    #[test]
+    fn search_reaches_settings_on_every_page() {
+        let mut app = App::for_test(Config::default());
+        app.config_mode = true;
+        for code in [KeyCode::Tab, KeyCode::Tab, KeyCode::Char('j')] {
+            app.handle_config_input(KeyEvent::from(code)).unwrap();
+        }
+        assert_eq!(app.config_view.selected(), Some(Setting::DailyGoal));
+
+        let search = "/clock".chars().map(KeyCode::Char);
+        for code in search.chain([KeyCode::Enter, KeyCode::Char('G'), KeyCode::Char('l')]) {
+            app.handle_config_input(KeyEvent::from(code)).unwrap();
+        }
+        let labels: Vec<&str> = app.config_view.rows().iter().map(|setting| setting.label()).collect();
+        assert_eq!(labels, ["clock_style", "clock_face", "clock_format", "show_wall_clock"]);
+        assert!(app.config.show_wall_clock);
+
+        app.handle_config_input(KeyEvent::from(KeyCode::Esc)).unwrap();
+        assert!(app.config_mode && app.config_view.search.is_none(), "esc leaves the search before the screen");
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::{App, PomodoroState};
+use crate::config::{Config, Overrides};
+use crate::duration;
+use crate::error::{Error, Result};
+use crate::events::Command;
+use crate::integrations::notify::Silent;
+use crate::stats::Stats;
+use crate::storage::{self, Dirs};
+
+// How far to run and how fast: `8h`, `1h30m at 60x speed`. Without a speed
+// the virtual clock runs as fast as the machine allows.
+#[derive(Debug, PartialEq)]
+pub struct Plan {
+    pub length: Duration,
+    pub speed: Option<u32>,
+}
+
+pub fn parse_plan(spec: &str) -> Result<Plan> {
+    let usage = || Error::Usage(format!("--simulate takes e.g. \"8h at 60x speed\", not {:?}", spec));
+    let words: Vec<&str> = spec.split_whitespace().collect();
+    let (length, speed) = match words[..] {
+        [length] => (length, None),
+        [length, "at", speed] | [length, "at", speed, "speed"] => (length, Some(speed)),
+        _ => return Err(usage()),
+    };
+
+    let length = duration::parse(length).map_err(|e| Error::Usage(format!("--simulate: {}", e)))?;
+    if length.is_zero() {
+        return Err(usage());
+    }
+
+    let speed = match speed {
+        Some(speed) => match speed.strip_suffix('x').and_then(|n| n.parse().ok()) {
+            Some(n @ 1..) => Some(n),
+            _ => return Err(usage()),
+        },
+        None => None,
+    };
+    Ok(Plan { length, speed })
+}
+
+// What the engine did over a simulated run
+#[derive(Debug, Default)]
+pub struct Report {
+    pub transitions: u32,
+    pub completed: u32,
+    pub focus: Duration,
+    pub breaks: Duration,
+    // Virtual time the sessions didn't account for; should stay near zero
+    pub drift: Duration,
+}
+
+// Hidden `--simulate` mode: runs the timer on a virtual clock with the
+// user's config, starting every session right away, and prints each
+// transition and a summary. Nothing is written and no integration runs.
+pub fn run_simulate(dirs: &Dirs, overrides: &Overrides, spec: &str) -> Result<()> {
+    let plan = parse_plan(spec)?;
+    storage::set_read_only(true);
+    let mut config = Config::load(&dirs.config_file())?;
+    overrides.apply(&mut config);
+    let mut app = App::with_config(config, dirs.config_file(), Stats::default(), dirs.data.join("stats.json"), 80, 24);
+    app.notifier = Box::new(Silent);
+    app.config.sound.enabled = false;
+    app.offline = true;
+
+    let mut stdout = io::stdout();
+    let report = simulate(&mut app, &plan, &mut stdout)?;
+    writeln!(stdout, "completed pomodoros: {}", report.completed)?;
+    writeln!(stdout, "focus: {}", clock(report.focus))?;
+    writeln!(stdout, "breaks: {}", clock(report.breaks))?;
+    writeln!(stdout, "transitions: {}", report.transitions)?;
+    writeln!(stdout, "drift: {} ms", report.drift.as_millis())?;
+    Ok(())
+}
+
+// Drive `app` for the plan's length in the steps the main loop would
+// redraw at, printing a line per transition
+pub fn simulate(app: &mut App, plan: &Plan, out: &mut impl Write) -> Result<Report> {
+    let mut report = Report::default();
+    let mut elapsed = Duration::ZERO;
+    app.toggle_pause();
+    writeln!(out, "{} start {} ({})", clock(elapsed), app.engine.state.name(), clock(app.session_length()))?;
+
+    while elapsed < plan.length {
+        let step = app.redraw_timeout().min(plan.length - elapsed);
+        if let Some(speed) = plan.speed {
+            thread::sleep(step / speed);
+        }
+        let (state, length) = (app.engine.state, app.session_length());
+        app.tick(step)?;
+        elapsed += step;
+        if !std::mem::take(&mut app.transitioned) {
+            continue;
+        }
+
+        report.transitions += 1;
+        if state == PomodoroState::Work {
+            report.completed += 1;
+            report.focus += length;
+        } else {
+            report.breaks += length;
+        }
+        writeln!(out, "{} {} -> {} ({})", clock(elapsed), state.name(), app.engine.state.name(), clock(app.session_length()))?;
+        // Unless "auto_start" already started it
+        app.execute(Command::Resume);
+    }
+
+    let current = app.session_length().saturating_sub(app.engine.time_remaining);
+    report.drift = elapsed.saturating_sub(report.focus + report.breaks + current);
+    Ok(report)
+}
+
+fn clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_parse() {
+        let plan = |length, speed| Plan { length: Duration::from_secs(length), speed };
+        assert_eq!(parse_plan("8h at 60x speed").unwrap(), plan(8 * 3600, Some(60)));
+        assert_eq!(parse_plan("1h30m at 10x").unwrap(), plan(5400, Some(10)));
+        assert_eq!(parse_plan("90s").unwrap(), plan(90, None));
+        assert_eq!(parse_plan("45").unwrap(), plan(45 * 60, None));
+        for bad in ["", "0m", "8h at", "8h at 0x", "8h at fastx", "8d", "h"] {
+            assert!(parse_plan(bad).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn two_hours_reach_the_long_break() {
+        for auto_start in ["off", "both"] {
+            let mut app = App::for_test(Config { auto_start: auto_start.to_string(), ..Config::default() });
+            let mut out = Vec::new();
+            let report = simulate(&mut app, &parse_plan("2h").unwrap(), &mut out).unwrap();
+
+            assert_eq!(report.completed, 4, "auto_start {}", auto_start);
+            assert_eq!(report.transitions, 7, "auto_start {}", auto_start);
+            assert_eq!(app.engine.state, PomodoroState::LongBreak);
+            assert!(report.drift < Duration::from_millis(10), "{:?}", report.drift);
+            assert_eq!(String::from_utf8(out).unwrap().lines().count(), 8);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::crypto::{load_json, save_json, Passphrase};
+use crate::error::Result;
+
+// Persisted productivity stats, kept in the data dir rather than next to the
+// config since they're written on every completed session
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    // Completed work sessions keyed by local date (YYYY-MM-DD)
+    pub completed: BTreeMap<String, u32>,
+    // The same per profile, for those completed with one active; `completed`
+    // is the total over all of them
+    pub profiles: BTreeMap<String, BTreeMap<String, u32>>,
+    // Minutes added to each extended work session, keyed by local date
+    pub extensions: BTreeMap<String, Vec<u32>>,
+    // Breaks skipped under the "justify" or "cooldown" skip rules
+    pub skipped_breaks: Vec<SkippedBreak>,
+    // Actual minutes of each break that ran on while the user was away
+    // ("break_idle_minutes"), keyed by local date
+    pub idle_breaks: BTreeMap<String, Vec<u32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkippedBreak {
+    // Local time, YYYY-MM-DDTHH:MM:SS
+    pub at: String,
+    // What was typed under the "justify" rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Stats {
+    // Missing stats start empty; unreadable ones are set aside, and
+    // encrypted ones need `passphrase` (crypto::load_json)
+    pub fn load(path: &Path, passphrase: Option<&Passphrase>) -> Result<Self> {
+        load_json(path, passphrase)
+    }
+
+    // Encrypted when a passphrase is given, plain JSON otherwise
+    pub fn save(&self, path: &Path, passphrase: Option<&Passphrase>) -> Result<()> {
+        save_json(path, self, passphrase)
+    }
+
+    pub fn today() -> String {
+        chrono::Local::now().format("%Y-%m-%d").to_string()
+    }
+
+    pub fn completed_today(&self) -> u32 {
+        self.completed.get(&Self::today()).copied().unwrap_or(0)
+    }
+
+    pub fn extensions_today(&self) -> &[u32] {
+        self.extensions.get(&Self::today()).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn record_extension(&mut self, minutes: u32) {
+        self.extensions.entry(Self::today()).or_default().push(minutes);
+    }
+
+    pub fn record_idle_break(&mut self, minutes: u32) {
+        self.idle_breaks.entry(Self::today()).or_default().push(minutes);
+    }
+
+    pub fn record_skipped_break(&mut self, reason: Option<String>) {
+        let at = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+        self.skipped_breaks.push(SkippedBreak { at, reason });
+    }
+
+    // One more for today, and for `profile` unless that's ""
+    pub fn record_completed(&mut self, profile: &str) {
+        *self.completed.entry(Self::today()).or_insert(0) += 1;
+        if !profile.is_empty() {
+            *self.profiles.entry(profile.to_string()).or_default().entry(Self::today()).or_insert(0) += 1;
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::app::PomodoroState;
+use crate::config::Config;
+use crate::engine::cycles_in;
+use crate::error::{Error, Result};
+use crate::session::Session;
+use crate::storage::Dirs;
+
+// `rpomodoro status`: where the timer is, for shell prompts and scripts.
+// Read from the running timer if there is one, else from the saved session.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub state: PomodoroState,
+    pub paused: bool,
+    pub remaining_secs: u64,
+    // Time counted up instead, in stopwatch mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopwatch_secs: Option<u64>,
+    // Time past the end, for a session running over
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtime_secs: Option<u64>,
+    pub cycle: u32,
+    pub cycles_before_long: u32,
+    // False when no timer is running and this is the saved session
+    #[serde(default)]
+    pub running: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ending_soon: bool,
+}
+
+impl Status {
+    // e.g. "work 12:34 1/4", with "paused" or "idle" after it when not
+    // counting down, and "+02:10" in overtime
+    pub fn line(&self) -> String {
+        let activity = match (self.running, self.paused) {
+            (false, _) => " idle",
+            (true, true) => " paused",
+            (true, false) => "",
+        };
+        format!("{} {} {}/{}{}", self.state.name(), self.time(), self.cycle, self.cycles_before_long, activity)
+    }
+
+    fn time(&self) -> String {
+        let secs = self.stopwatch_secs.or(self.overtime_secs).unwrap_or(self.remaining_secs);
+        let sign = if self.overtime_secs.is_some() { "+" } else { "" };
+        format!("{}{:02}:{:02}", sign, secs / 60, secs % 60)
+    }
+
+    // A Waybar custom module line: the state and time as text, the full
+    // line as tooltip, and classes to style by state and activity, plus
+    // "ending_soon" near the end
+    pub fn waybar(&self) -> String {
+        let activity = match (self.running, self.paused) {
+            (false, _) => "idle",
+            (true, true) => "paused",
+            (true, false) => "running",
+        };
+        let mut class = vec![self.state.name(), activity];
+        if self.ending_soon {
+            class.push("ending_soon");
+        }
+        if self.overtime_secs.is_some() {
+            class.push("overtime");
+        }
+        json!({
+            "text": format!("{} {}", self.state.name(), self.time()),
+            "tooltip": self.line(),
+            "class": class,
+        })
+        .to_string()
+    }
+}
+
+// Output formats for `rpomodoro bar`
+pub const BAR_FORMATS: [&str; 2] = ["text", "json"];
+
+// `rpomodoro bar`: a line for status bars whenever the timer changes,
+// polled once a second. "text" suits polybar's tail scripts, "json"
+// Waybar's custom modules with "return-type": "json".
+pub fn run_bar(dirs: &Dirs, format: &str) -> Result<()> {
+    let mut stdout = io::stdout();
+    let mut last = String::new();
+    loop {
+        let status = running(dirs).unwrap_or_else(|| saved(dirs));
+        let line = if format == "json" { status.waybar() } else { status.line() };
+        if line != last {
+            // The bar closing the pipe ends us
+            if writeln!(stdout, "{}", line).and_then(|_| stdout.flush()).is_err() {
+                return Ok(());
+            }
+            last = line;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+pub fn run_status(dirs: &Dirs, as_json: bool) -> Result<()> {
+    let status = running(dirs).unwrap_or_else(|| saved(dirs));
+    if as_json {
+        let json = serde_json::to_string(&status).map_err(|e| Error::integration("status", e))?;
+        println!("{}", json);
+    } else {
+        println!("{}", status.line());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "control")]
+fn running(dirs: &Dirs) -> Option<Status> {
+    let reply = crate::ipc::transport(dirs).request("status").ok()?;
+    let status: Status = serde_json::from_str(&reply).ok()?;
+    Some(Status { running: true, ..status })
+}
+
+#[cfg(not(feature = "control"))]
+fn running(_dirs: &Dirs) -> Option<Status> {
+    None
+}
+
+// The session the next run would resume, or a fresh one
+fn saved(dirs: &Dirs) -> Status {
+    let mut dirs = dirs.clone();
+    let config = Config::read(&dirs.config_file());
+    dirs.apply_config(&config.data_dir);
+    // The first session: work, or a custom sequence's first step
+    let first = config.sequence().first().map_or((PomodoroState::Work, config.work_duration), |step| (step.state, step.minutes));
+    let session = Session::load(&dirs.state.join("session.json")).unwrap_or(Session {
+        state: first.0,
+        remaining_secs: first.1 as u64 * 60,
+        cycle: 0,
+        extended_minutes: 0,
+        started: None,
+        saved_at: None,
+        paused: true,
+        stopwatch_secs: None,
+        overtime_secs: None,
+        step: None,
+    });
+    Status {
+        state: session.state,
+        paused: true,
+        remaining_secs: session.remaining_secs,
+        stopwatch_secs: session.stopwatch_secs,
+        overtime_secs: session.overtime_secs,
+        cycle: session.cycle,
+        cycles_before_long: cycles_in(&config.sequence(), config.cycles_before_long),
+        running: false,
+        ending_soon: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::App;
+
+    #[test]
+    fn reads_a_snapshot() {
+        let mut app = App::for_test(Config::default());
+        app.advance_state(true);
+        let status: Status = serde_json::from_str(&serde_json::to_string(&app.snapshot()).unwrap()).unwrap();
+        assert_eq!(status.line(), "short_break 05:00 1/4 idle");
+        assert_eq!(Status { running: true, ..status }.line(), "short_break 05:00 1/4 paused");
+        assert_eq!(
+            status.waybar(),
+            r#"{"class":["short_break","idle"],"text":"short_break 05:00","tooltip":"short_break 05:00 1/4 idle"}"#
+        );
+    }
+
+    #[test]
+    fn ending_soon_shows_near_the_end() {
+        let mut app = App::for_test(Config { ending_soon_minutes: 2, ..Config::default() });
+        app.toggle_pause();
+        app.tick(Duration::from_secs(22 * 60)).unwrap();
+        assert!(!app.snapshot().ending_soon);
+        app.tick(Duration::from_secs(60)).unwrap();
+        let status: Status = serde_json::from_str(&serde_json::to_string(&app.snapshot()).unwrap()).unwrap();
+        assert!(status.waybar().contains(r#""class":["work","idle","ending_soon"]"#));
+        app.execute(crate::events::Command::Stopwatch);
+        assert!(!app.snapshot().ending_soon, "a stopwatch has no end");
+    }
+}
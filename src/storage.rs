@@ -0,0 +1,181 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{Error, Result};
+
+// Set by --read-only. Every write of config, stats or state goes through
+// this module and quietly does nothing while it's set, so settings can be
+// tried out and the timer demoed without touching any files.
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+// Write via a temporary file and rename, so a crash mid-write can't leave a
+// truncated file behind
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    replace_file(path, contents)
+}
+
+// Output other programs read, like the OBS badge, rather than our own
+// state. Still atomic so readers never see half a file, but written even
+// in read-only mode, where streaming a demo is a typical use.
+pub fn write_export(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    replace_file(path, contents)
+}
+
+fn replace_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)
+        .and_then(|_| fs::rename(&tmp, path))
+        .map_err(|e| Error::io(path, e))
+}
+
+// Add one line to the end of a log-style file, creating it if needed
+pub fn append_line(path: &Path, line: &str) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .map_err(|e| Error::io(path, e))
+}
+
+// Move an unparseable file to `<name>.bad`, keeping it for inspection
+pub fn set_aside(path: &Path) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    let mut bad = path.as_os_str().to_owned();
+    bad.push(".bad");
+    fs::rename(path, bad).map_err(|e| Error::io(path, e))
+}
+
+pub fn create_dir(path: &Path) -> Result<()> {
+    if is_read_only() {
+        return Ok(());
+    }
+    fs::create_dir_all(path).map_err(|e| Error::io(path, e))
+}
+
+// Where rpomodoro keeps its files. Resolved once at startup from, in order
+// of precedence: --config-dir/--data-dir, the config's "data_dir", portable
+// mode, and the platform defaults (XDG on Linux).
+#[derive(Debug, Clone)]
+pub struct Dirs {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    // Runtime state that isn't worth backing up; XDG_STATE_HOME on Linux,
+    // the data dir elsewhere
+    pub state: PathBuf,
+    // --data-dir was given, so the config can't move the data dir
+    data_pinned: bool,
+    // --config, a config file other than the config dir's own
+    config_file: Option<PathBuf>,
+}
+
+impl Dirs {
+    pub fn resolve(config: Option<PathBuf>, data: Option<PathBuf>, portable: bool) -> Self {
+        let portable = portable || has_portable_marker();
+        let (default_config, default_data, default_state) = match portable_root().filter(|_| portable) {
+            Some(root) => (root.join("config"), root.join("data"), root.join("state")),
+            None => (config_dir(), data_dir(), state_dir()),
+        };
+        let data_pinned = data.is_some();
+        let data = data.unwrap_or(default_data);
+        Dirs {
+            config: config.unwrap_or(default_config),
+            state: if data_pinned { data.clone() } else { default_state },
+            data,
+            data_pinned,
+            config_file: None,
+        }
+    }
+
+    pub fn set_config_file(&mut self, path: PathBuf) {
+        self.config_file = Some(path);
+    }
+
+    // --config, or config.toml in the config dir. A config.json from before
+    // TOML is used while it's the only one there.
+    pub fn config_file(&self) -> PathBuf {
+        if let Some(file) = &self.config_file {
+            return file.clone();
+        }
+        let (toml, json) = (self.config.join("config.toml"), self.config.join("config.json"));
+        if !toml.exists() && json.exists() {
+            json
+        } else {
+            toml
+        }
+    }
+
+    // The config is a config.json in the config dir that could be migrated
+    pub fn has_legacy_config(&self) -> bool {
+        self.config_file.is_none() && self.config_file().extension().is_some_and(|extension| extension == "json")
+    }
+
+    // Apply the config's "data_dir" setting, unless --data-dir overrode it
+    pub fn apply_config(&mut self, data_dir: &str) {
+        if self.data_pinned || data_dir.is_empty() {
+            return;
+        }
+        self.data = expand_home(data_dir);
+        self.state = self.data.clone();
+    }
+}
+
+// Portable mode keeps everything in `rpomodoro-data` next to the binary.
+// It's switched on with --portable or by an empty file called `portable`
+// beside the binary, for USB sticks and machines without a writable home.
+fn portable_root() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.parent()?.join("rpomodoro-data"))
+}
+
+fn has_portable_marker() -> bool {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("portable")))
+        .is_some_and(|marker| marker.is_file())
+}
+
+// "~/..." paths from the config file
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rpomodoro")
+}
+
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rpomodoro")
+}
+
+pub fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .map(|dir| dir.join("rpomodoro"))
+        .unwrap_or_else(data_dir)
+}
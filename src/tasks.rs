@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::crypto::{load_json, save_json, Passphrase};
+use crate::error::Result;
+
+// The task list, kept in tasks.json in the data dir, encrypted like the
+// stats with "encrypt_data". Completed work sessions count toward the active
+// task and carry its title in the history.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tasks {
+    pub tasks: Vec<Task>,
+    // Index into `tasks`
+    pub active: Option<usize>,
+}
+
+// The task pane while it's open: the highlighted task, and the title being
+// typed for a new one
+#[derive(Debug, Default)]
+pub struct TaskPane {
+    pub cursor: usize,
+    pub new_title: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Task {
+    pub title: String,
+    // Work sessions completed while it was active
+    pub pomodoros: u32,
+}
+
+impl Tasks {
+    // Empty when there's no file yet; a damaged one is set aside, and an
+    // encrypted one needs `passphrase` (crypto::load_json)
+    pub fn load(path: &Path, passphrase: Option<&Passphrase>) -> Result<Self> {
+        let mut tasks: Tasks = load_json(path, passphrase)?;
+        tasks.active = tasks.active.filter(|&i| i < tasks.tasks.len());
+        Ok(tasks)
+    }
+
+    // Encrypted when a passphrase is given, plain JSON otherwise
+    pub fn save(&self, path: &Path, passphrase: Option<&Passphrase>) -> Result<()> {
+        save_json(path, self, passphrase)
+    }
+
+    pub fn active_task(&self) -> Option<&Task> {
+        self.tasks.get(self.active?)
+    }
+
+    pub fn add(&mut self, title: &str) {
+        self.tasks.push(Task { title: title.to_string(), pomodoros: 0 });
+    }
+
+    // Keeps the active task pointing at the same one
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.tasks.len() {
+            return;
+        }
+        self.tasks.remove(index);
+        self.active = match self.active {
+            Some(active) if active == index => None,
+            Some(active) if active > index => Some(active - 1),
+            active => active,
+        };
+    }
+
+    // Make `index` the active task, or no task if it already is
+    pub fn select(&mut self, index: usize) {
+        if index < self.tasks.len() {
+            self.active = if self.active == Some(index) { None } else { Some(index) };
+        }
+    }
+
+    // A work session ran to the end
+    pub fn credit(&mut self) {
+        if let Some(task) = self.active.and_then(|i| self.tasks.get_mut(i)) {
+            task.pomodoros += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removing_a_task_keeps_the_active_one() {
+        let mut tasks = Tasks::default();
+        for title in ["write report", "review PRs", "email"] {
+            tasks.add(title);
+        }
+        tasks.select(2);
+        tasks.credit();
+        tasks.remove(0);
+        assert_eq!(tasks.active_task(), Some(&Task { title: "email".to_string(), pomodoros: 1 }));
+        tasks.remove(1);
+        assert_eq!(tasks.active_task(), None);
+        tasks.select(0);
+        tasks.select(0);
+        assert_eq!(tasks.active, None);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encrypted_tasks_keep_their_titles_hidden() {
+        let path = std::env::temp_dir().join(format!("rpomodoro-test-tasks-{}.json", std::process::id()));
+        let passphrase = Passphrase::for_test("correct horse");
+        let mut tasks = Tasks::default();
+        tasks.add("secret project");
+        tasks.save(&path, Some(&passphrase)).unwrap();
+        assert!(!String::from_utf8_lossy(&std::fs::read(&path).unwrap()).contains("secret project"));
+        assert_eq!(Tasks::load(&path, Some(&passphrase)).unwrap(), tasks);
+        assert!(Tasks::load(&path, None).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
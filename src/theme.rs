@@ -0,0 +1,326 @@
+use crossterm::style::Color;
+
+use crate::config::{Config, CustomTheme};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub primary: Color,
+    pub dim: Color,
+    pub urgent: Color,
+}
+
+impl Theme {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "blue" => Theme {
+                primary: Color::Rgb { r: 96, g: 165, b: 250 },
+                dim: Color::Rgb { r: 147, g: 197, b: 253 },
+                urgent: Color::Rgb { r: 248, g: 113, b: 113 },
+            },
+            "purple" => Theme {
+                primary: Color::Rgb { r: 192, g: 132, b: 252 },
+                dim: Color::Rgb { r: 233, g: 213, b: 255 },
+                urgent: Color::Rgb { r: 251, g: 113, b: 133 },
+            },
+            "green" => Theme {
+                primary: Color::Rgb { r: 74, g: 222, b: 128 },
+                dim: Color::Rgb { r: 134, g: 239, b: 172 },
+                urgent: Color::Rgb { r: 251, g: 191, b: 36 },
+            },
+            "red" => Theme {
+                primary: Color::Rgb { r: 248, g: 113, b: 113 },
+                dim: Color::Rgb { r: 254, g: 202, b: 202 },
+                urgent: Color::Rgb { r: 251, g: 191, b: 36 },
+            },
+            "orange" => Theme {
+                primary: Color::Rgb { r: 251, g: 191, b: 36 },
+                dim: Color::Rgb { r: 253, g: 224, b: 71 },
+                urgent: Color::Rgb { r: 248, g: 113, b: 113 },
+            },
+            "cyan" => Theme {
+                primary: Color::Rgb { r: 34, g: 211, b: 238 },
+                dim: Color::Rgb { r: 103, g: 232, b: 249 },
+                urgent: Color::Rgb { r: 248, g: 113, b: 113 },
+            },
+            _ => Theme::from_name("blue"),
+        }
+    }
+
+    // The config's own theme by that name, else a built-in one. Custom
+    // themes keep blue's urgent color.
+    pub fn from_config(config: &Config) -> Self {
+        let Some(custom) = config.themes.get(&config.theme) else {
+            return Theme::from_name(&config.theme);
+        };
+        let base = Theme::from_name("blue");
+        Theme {
+            primary: parse_hex_color(&custom.primary).unwrap_or(base.primary),
+            dim: parse_hex_color(&custom.dim).unwrap_or(base.dim),
+            ..base
+        }
+    }
+
+    // The pastel dim shades vanish on a light background, so they're
+    // darkened there
+    pub fn on_background(self, background: Option<Color>) -> Self {
+        if !background.is_some_and(|bg| luminance(bg) > 0.5) {
+            return self;
+        }
+        Theme { dim: blend(self.dim, Color::Rgb { r: 0, g: 0, b: 0 }, 0.5), ..self }
+    }
+
+    // Whether the main color stands out enough from the background to read
+    pub fn readable_on(&self, background: Option<Color>) -> bool {
+        background.is_none_or(|bg| contrast(self.primary, bg) >= MIN_CONTRAST)
+    }
+}
+
+// WCAG contrast ratio below which text is hard to make out
+const MIN_CONTRAST: f32 = 2.0;
+
+// Relative luminance (WCAG), 0 for black to 1 for white. Non-RGB colors
+// count as mid-grey.
+pub fn luminance(color: Color) -> f32 {
+    let Color::Rgb { r, g, b } = color else {
+        return 0.5;
+    };
+    let linear = |c: u8| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+pub fn contrast(a: Color, b: Color) -> f32 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// The theme editor's working copy: red, green and blue of the primary
+// color, then of dim
+pub struct ThemeEditor {
+    pub channels: [u8; 6],
+    pub row: usize,
+    // The name being typed, once saving was asked for
+    pub name: Option<String>,
+}
+
+impl ThemeEditor {
+    pub fn new(theme: &Theme) -> Self {
+        let ([r, g, b], [dr, dg, db]) = (rgb(theme.primary), rgb(theme.dim));
+        ThemeEditor { channels: [r, g, b, dr, dg, db], row: 0, name: None }
+    }
+
+    pub fn adjust(&mut self, delta: i16) {
+        let channel = &mut self.channels[self.row];
+        *channel = (*channel as i16 + delta).clamp(0, 255) as u8;
+    }
+
+    pub fn primary(&self) -> Color {
+        let [r, g, b, ..] = self.channels;
+        Color::Rgb { r, g, b }
+    }
+
+    pub fn dim(&self) -> Color {
+        let [.., r, g, b] = self.channels;
+        Color::Rgb { r, g, b }
+    }
+
+    pub fn custom(&self) -> CustomTheme {
+        CustomTheme { primary: to_hex(self.primary()), dim: to_hex(self.dim()) }
+    }
+}
+
+fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb { r, g, b } => [r, g, b],
+        _ => [128; 3],
+    }
+}
+
+pub fn to_hex(color: Color) -> String {
+    let [r, g, b] = rgb(color);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Parse "#rrggbb" (leading # optional)
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(Color::Rgb { r: channel(0)?, g: channel(2)?, b: channel(4)? })
+}
+
+// Linear blend between two RGB colors, t in [0, 1]
+pub fn blend(from: Color, to: Color, t: f32) -> Color {
+    match (from, to) {
+        (Color::Rgb { r: r1, g: g1, b: b1 }, Color::Rgb { r: r2, g: g2, b: b2 }) => {
+            let t = t.clamp(0.0, 1.0);
+            let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb { r: mix(r1, r2), g: mix(g1, g2), b: mix(b1, b2) }
+        }
+        _ => if t < 0.5 { from } else { to },
+    }
+}
+
+// `color` dimmed and brightened on a sine wave of `per_minute` cycles,
+// `seconds` into it: full brightness at the top of each breath, down to
+// `depth` of the way toward black at the bottom
+pub fn pulse(color: Color, seconds: f32, per_minute: f32, depth: f32) -> Color {
+    let phase = seconds * per_minute / 60.0 * std::f32::consts::TAU;
+    let darkness = (1.0 - phase.cos()) / 2.0 * depth;
+    blend(color, Color::Rgb { r: 0, g: 0, b: 0 }, darkness)
+}
+
+// Best guess at the terminal's color depth from the environment
+pub fn color_support() -> &'static str {
+    let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+    let term = std::env::var("TERM").unwrap_or_default();
+    if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+        "truecolor"
+    } else if term.contains("256color") {
+        "256"
+    } else if term.is_empty() || term == "dumb" {
+        "none"
+    } else {
+        "16"
+    }
+}
+
+// How many colors frames are sent with. Themes are 24-bit; on terminals
+// with fewer colors each one is mapped to the nearest the terminal has.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    // For the "color_mode" setting; "auto" goes by color_support()
+    pub fn from_setting(setting: &str) -> Self {
+        let setting = if setting == "auto" { color_support() } else { setting };
+        match setting {
+            "truecolor" => ColorDepth::TrueColor,
+            "256" => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    pub fn map(self, color: Color) -> Color {
+        let Color::Rgb { r, g, b } = color else {
+            return color;
+        };
+        match self {
+            ColorDepth::TrueColor => color,
+            ColorDepth::Ansi256 => Color::AnsiValue(nearest_256([r, g, b])),
+            ColorDepth::Ansi16 => ANSI_16.iter().min_by_key(|(_, rgb)| distance(*rgb, [r, g, b])).map_or(color, |&(ansi, _)| ansi),
+        }
+    }
+}
+
+// The 16 ANSI colors as xterm draws them by default
+const ANSI_16: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::DarkRed, [205, 0, 0]),
+    (Color::DarkGreen, [0, 205, 0]),
+    (Color::DarkYellow, [205, 205, 0]),
+    (Color::DarkBlue, [0, 0, 238]),
+    (Color::DarkMagenta, [205, 0, 205]),
+    (Color::DarkCyan, [0, 205, 205]),
+    (Color::Grey, [229, 229, 229]),
+    (Color::DarkGrey, [127, 127, 127]),
+    (Color::Red, [255, 0, 0]),
+    (Color::Green, [0, 255, 0]),
+    (Color::Yellow, [255, 255, 0]),
+    (Color::Blue, [92, 92, 255]),
+    (Color::Magenta, [255, 0, 255]),
+    (Color::Cyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+// The closer of the 6x6x6 color cube entry and the grey ramp entry
+fn nearest_256(rgb: [u8; 3]) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let level = |c: u8| match c {
+        0..=47 => 0,
+        48..=114 => 1,
+        c => (c as usize - 35) / 40,
+    };
+    let [r, g, b] = rgb.map(level);
+    let cube = [LEVELS[r], LEVELS[g], LEVELS[b]];
+
+    let average = rgb.iter().map(|&c| c as u32).sum::<u32>() / 3;
+    let grey = (average.saturating_sub(3) / 10).min(23) as u8;
+    let grey_level = 8 + 10 * grey;
+
+    if distance([grey_level; 3], rgb) < distance(cube, rgb) {
+        232 + grey
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> u32 {
+    a.iter().zip(b).map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_backgrounds_get_darker_dims() {
+        let (white, black) = (Color::Rgb { r: 255, g: 255, b: 255 }, Color::Rgb { r: 0, g: 0, b: 0 });
+        for name in crate::config::THEMES {
+            let theme = Theme::from_name(name);
+            assert_eq!(theme.on_background(Some(black)).dim, theme.dim);
+            assert!(contrast(theme.on_background(Some(white)).dim, white) > contrast(theme.dim, white));
+            assert!(theme.readable_on(Some(black)) && theme.readable_on(None));
+        }
+        assert!(!Theme::from_name("green").readable_on(Some(white)));
+        assert!(Theme::from_name("red").readable_on(Some(white)));
+    }
+
+    #[test]
+    fn edited_theme_is_saved_by_name() {
+        use crate::app::App;
+        use crossterm::event::{KeyCode, KeyEvent};
+
+        let mut app = App::for_test(Config::default());
+        app.config_mode = true;
+        app.config_view.page = 1;
+        let keys = [KeyCode::Enter, KeyCode::Char('L'), KeyCode::Char('l'), KeyCode::Enter];
+        let name = "mine".chars().map(KeyCode::Char);
+        for code in keys.into_iter().chain(name).chain([KeyCode::Enter]) {
+            app.handle_config_input(KeyEvent::from(code)).unwrap();
+        }
+
+        assert!(app.theme_editor.is_none());
+        assert_eq!(app.config.theme, "mine");
+        assert_eq!(app.config.themes["mine"], CustomTheme { primary: "#71a5fa".to_string(), dim: "#93c5fd".to_string() });
+        assert_eq!(Theme::from_config(&app.config).primary, Color::Rgb { r: 113, g: 165, b: 250 });
+    }
+
+    #[test]
+    fn pulse_breathes_from_full_to_dim() {
+        let color = Color::Rgb { r: 200, g: 100, b: 50 };
+        assert_eq!(pulse(color, 0.0, 5.0, 0.6), color);
+        // Five breaths a minute is one every 12 seconds, darkest halfway
+        assert_eq!(pulse(color, 6.0, 5.0, 0.6), Color::Rgb { r: 80, g: 40, b: 20 });
+        assert_eq!(pulse(color, 12.0, 5.0, 0.6), color);
+    }
+
+    #[test]
+    fn colors_map_to_smaller_palettes() {
+        let blue = Theme::from_name("blue");
+        assert_eq!(ColorDepth::TrueColor.map(blue.primary), blue.primary);
+        assert_eq!(ColorDepth::Ansi16.map(blue.primary), Color::Blue);
+        assert_eq!(ColorDepth::Ansi16.map(blue.dim), Color::Grey);
+        assert_eq!(ColorDepth::Ansi256.map(Color::Rgb { r: 255, g: 135, b: 0 }), Color::AnsiValue(208));
+        assert_eq!(ColorDepth::Ansi256.map(Color::Rgb { r: 128, g: 128, b: 128 }), Color::AnsiValue(244));
+        assert_eq!(ColorDepth::Ansi256.map(Color::Reset), Color::Reset);
+    }
+}
@@ -0,0 +1,52 @@
+use serde_json::json;
+
+use crate::events::Command;
+use crate::config::NOTIFIERS;
+
+// Cargo features this binary was built with
+pub fn compiled_features() -> Vec<&'static str> {
+    [
+        ("control", cfg!(feature = "control")),
+        ("encryption", cfg!(feature = "encryption")),
+        ("remote", cfg!(feature = "remote")),
+        ("dbus", cfg!(feature = "dbus")),
+        ("ffi", cfg!(feature = "ffi")),
+        ("wasm", cfg!(feature = "wasm")),
+    ]
+    .into_iter()
+    .filter(|&(_, enabled)| enabled)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+// `--version`, or with `--json` a capability report for scripts and plugins
+// that need to know what this build supports
+pub fn run_version(as_json: bool) {
+    let version = env!("CARGO_PKG_VERSION");
+    if !as_json {
+        println!("rpomodoro {}", version);
+        return;
+    }
+
+    let report = json!({
+        "name": "rpomodoro",
+        "version": version,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "features": compiled_features(),
+        "capabilities": {
+            "notifiers": NOTIFIERS,
+            "control": match (cfg!(feature = "control"), cfg!(windows)) {
+                (false, _) => None,
+                (true, true) => Some("named-pipe"),
+                (true, false) => Some("unix-socket"),
+            },
+            "commands": Command::NAMES.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+            "signals": cfg!(all(unix, feature = "control")),
+            "encryption": cfg!(feature = "encryption"),
+            "remote": cfg!(feature = "remote"),
+            "dbus": cfg!(all(target_os = "linux", feature = "dbus")),
+        },
+    });
+    println!("{}", serde_json::to_string_pretty(&report).expect("a json! value always serializes"));
+}
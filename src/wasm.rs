@@ -0,0 +1,97 @@
+// Browser interface to PomodoroEngine, so a web frontend shares the timer's
+// sequencing and the config schema with the terminal app. Build with
+// `cargo rustc --lib --release --target wasm32-unknown-unknown
+// --no-default-features --features wasm --crate-type cdylib` and generate
+// the JS bindings with wasm-bindgen.
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+use crate::config::Config;
+use crate::engine::{Clock, ClockedEngine, PomodoroEngine};
+use crate::flow::EXTEND_STEP;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+struct PerformanceClock;
+
+impl Clock for PerformanceClock {
+    fn now(&self) -> Duration {
+        Duration::from_secs_f64(performance_now().max(0.0) / 1000.0)
+    }
+}
+
+// config.json as the terminal app writes it, defaults filled in
+#[wasm_bindgen(js_name = defaultConfig)]
+pub fn default_config() -> String {
+    serde_json::to_string_pretty(&Config::default()).unwrap_or_default()
+}
+
+#[wasm_bindgen]
+pub struct Timer {
+    inner: ClockedEngine<PerformanceClock>,
+}
+
+#[wasm_bindgen]
+impl Timer {
+    // Lengths and any custom sequence from a config.json's contents, or the
+    // defaults for "". Paused at the start of the first session.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: &str) -> Result<Timer, JsError> {
+        let config = if config.trim().is_empty() { Config::default() } else { Config::from_json(config)? };
+        let mut engine = PomodoroEngine::new(config.lengths());
+        engine.set_sequence(config.sequence());
+        Ok(Timer { inner: ClockedEngine::new(engine, PerformanceClock) })
+    }
+
+    // Call from requestAnimationFrame or a timer. Returns the session that
+    // began ("work", "short_break" or "long_break") when one ended.
+    pub fn update(&mut self) -> Option<String> {
+        self.inner.update().map(|transition| transition.next.name().to_string())
+    }
+
+    #[wasm_bindgen(js_name = togglePause)]
+    pub fn toggle_pause(&mut self) {
+        self.inner.toggle_pause();
+    }
+
+    pub fn skip(&mut self) -> String {
+        self.inner.engine.advance(true).next.name().to_string()
+    }
+
+    pub fn extend(&mut self) {
+        self.inner.engine.extend(EXTEND_STEP);
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.engine.reset();
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> String {
+        self.inner.engine.state.name().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn paused(&self) -> bool {
+        self.inner.engine.paused
+    }
+
+    #[wasm_bindgen(getter, js_name = remainingMs)]
+    pub fn remaining_ms(&self) -> f64 {
+        self.inner.engine.time_remaining.as_millis() as f64
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cycle(&self) -> u32 {
+        self.inner.engine.cycle_count
+    }
+
+    #[wasm_bindgen(getter, js_name = cyclesBeforeLong)]
+    pub fn cycles_before_long(&self) -> u32 {
+        self.inner.engine.cycles_before_long()
+    }
+}